@@ -0,0 +1,172 @@
+// =============================================================================
+// COMANDO: BANCO
+// =============================================================================
+// Operaciones sobre los TXT bancarios ya generados.
+//
+// Uso:
+//   sandra banco verificar out/mercantil_aporte_2026-02.txt
+//   sandra banco verificar out/banfanb_2026-02.txt --nomina out --tipo npr --columna garantias
+//   sandra banco verificar out/nuevo.txt --diseno config/mercantil_2026.json --json
+//   sandra banco respuesta out/banfanb_2026-02.txt respuestas/banfanb_2026-02.txt --salida out
+// =============================================================================
+
+use sandra_core::banco::lectura;
+use sandra_core::banco::respuesta::{self, DisenoRespuesta, EstadoPago};
+use sandra_core::banco::DisenoArchivo;
+use sandra_core::nomina::diferencias::NominaExportada;
+
+pub fn verificar(
+    archivo: String,
+    diseno: Option<String>,
+    nomina: Option<String>,
+    tipo: Option<String>,
+    columna: String,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let diseno = match &diseno {
+        Some(ruta) => Some((ruta.clone(), DisenoArchivo::cargar(ruta)?)),
+        None => None,
+    };
+    let (leido, diseno) = lectura::cargar(&archivo, diseno)?;
+
+    let nomina = match &nomina {
+        Some(ruta) => Some(NominaExportada::cargar(ruta, tipo.as_deref())?),
+        None => None,
+    };
+    let columnas: Vec<String> = columna.split('+').map(|c| c.trim().to_string()).collect();
+    let v = lectura::verificar(
+        &leido,
+        &diseno,
+        nomina.as_ref().map(|n| (n, columnas.as_slice())),
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&v)?);
+    } else {
+        println!("\n{:=<80}", "");
+        println!("{:^80}", "SANDRA SENTINEL - VERIFICACIÓN DE ARCHIVO BANCARIO");
+        println!("{:=<80}", "");
+        println!("  {:<12} : {}", "Archivo", v.archivo);
+        println!("  {:<12} : {}", "Diseño", v.diseno);
+        if let Some(n) = &v.nomina {
+            println!("  {:<12} : {} ({})", "Nómina", n, columnas.join("+"));
+        }
+        println!("{:-<80}", "");
+
+        for o in &v.observaciones {
+            println!(
+                "  ! línea {:>6} {:<12} {}",
+                o.linea,
+                o.cedula.as_deref().unwrap_or(""),
+                o.motivo
+            );
+        }
+        if !v.observaciones.is_empty() {
+            println!("{:-<80}", "");
+        }
+
+        println!("  {:<12} : {:>15}", "Registros", v.registros);
+        println!("  {:<12} : {:>15.2}", "Total", v.total);
+        println!(
+            "  {:<12} : {:>15}",
+            "Resultado",
+            if v.correcto() { "OK" } else { "CON OBSERVACIONES" }
+        );
+        println!("{:=<80}\n", "");
+    }
+
+    if !v.correcto() {
+        return Err(format!(
+            "{}: {} observaciones",
+            v.archivo,
+            v.observaciones.len()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+pub fn respuesta(
+    archivo: String,
+    archivo_respuesta: String,
+    diseno: Option<String>,
+    diseno_respuesta: Option<String>,
+    salida: String,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let diseno = match &diseno {
+        Some(ruta) => Some((ruta.clone(), DisenoArchivo::cargar(ruta)?)),
+        None => None,
+    };
+    let (enviado, diseno) = lectura::cargar(&archivo, diseno)?;
+    let diseno_respuesta = match &diseno_respuesta {
+        Some(ruta) => DisenoRespuesta::cargar(ruta)?,
+        None => DisenoRespuesta::para(&enviado.diseno).ok_or_else(|| {
+            format!(
+                "no hay diseño de respuesta para '{}'; indíquelo con --diseno-respuesta",
+                enviado.diseno
+            )
+        })?,
+    };
+
+    let contenido = std::fs::read_to_string(&archivo_respuesta)?;
+    let pagos = match respuesta::cargar_estado(&salida, &archivo)? {
+        Some(pagos) => pagos,
+        None => respuesta::pagos_enviados(&enviado),
+    };
+    let leida = diseno_respuesta.leer(&contenido);
+    let mut r = respuesta::aplicar(pagos, &leida, &diseno_respuesta);
+    r.archivo = archivo.clone();
+    r.respuesta = archivo_respuesta.clone();
+    let resultados = r.exportar(&enviado, &diseno, &salida)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&r)?);
+        return Ok(());
+    }
+
+    println!("\n{:=<80}", "");
+    println!("{:^80}", "SANDRA SENTINEL - RESPUESTA DEL BANCO");
+    println!("{:=<80}", "");
+    println!("  {:<12} : {}", "Archivo", r.archivo);
+    println!("  {:<12} : {}", "Respuesta", r.respuesta);
+    println!("{:-<80}", "");
+
+    for p in r.a_reprocesar() {
+        println!(
+            "  ! línea {:>6} {:<12} {:<10} {}",
+            p.linea,
+            p.cedula,
+            p.estado.as_str(),
+            p.motivo
+        );
+    }
+    for p in r.pagos.iter().filter(|p| p.estado == EstadoPago::Desconocido) {
+        println!(
+            "  ~ línea {:>6} {:<12} {:<10} {}",
+            p.linea,
+            p.cedula,
+            p.estado.as_str(),
+            p.motivo
+        );
+    }
+    for o in &r.sin_coincidencia {
+        println!(
+            "  ? línea {:>6} {:<12} {}",
+            o.linea,
+            o.cedula.as_deref().unwrap_or(""),
+            o.motivo
+        );
+    }
+    println!("{:-<80}", "");
+
+    for (estado, n, total) in r.por_estado() {
+        println!("  {:<12} : {:>8} {:>15.2}", estado.as_str(), n, total);
+    }
+    println!("{:-<80}", "");
+    for res in &resultados {
+        println!("  {:<12} : {}", res.tipo, res.ruta);
+    }
+    println!("{:=<80}\n", "");
+    Ok(())
+}
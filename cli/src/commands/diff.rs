@@ -0,0 +1,110 @@
+// =============================================================================
+// COMANDO: DIFF
+// =============================================================================
+// Compara la nómina exportada de dos ciclos cruzando por cédula: altas, bajas,
+// campos modificados y anomalías según los umbrales `diferencias` del manifiesto.
+//
+// Uso:
+//   sandra diff --prev out/2026-01 --curr out/2026-02 --tipo nact
+//   sandra diff --prev out/2026-01 --curr out/2026-02 -m nomina.json --salida out/diff
+// =============================================================================
+
+use sandra_core::model::manifiesto::DiferenciasConfig;
+use sandra_core::model::Manifiesto;
+use sandra_core::nomina::diferencias::{self, NominaExportada};
+
+pub fn execute(
+    prev: String,
+    curr: String,
+    tipo: Option<String>,
+    manifest_path: Option<String>,
+    salida: Option<String>,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = match &manifest_path {
+        Some(path) => Manifiesto::cargar_desde_archivo(path)?.diferencias,
+        None => DiferenciasConfig::default(),
+    };
+
+    let nomina_prev = NominaExportada::cargar(&prev, tipo.as_deref())?;
+    let nomina_curr = NominaExportada::cargar(&curr, tipo.as_deref())?;
+    let d = diferencias::comparar(&nomina_prev, &nomina_curr, &config);
+
+    if let Some(destino) = &salida {
+        std::fs::create_dir_all(destino)?;
+        for r in d.exportar(destino)? {
+            if !json {
+                println!("  • {:<20} : {}", r.tipo, r.ruta);
+            }
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&d)?);
+        return Ok(());
+    }
+
+    println!("\n{:=<80}", "");
+    println!("{:^80}", "SANDRA SENTINEL - DIFERENCIAS DE NÓMINA");
+    println!("{:=<80}", "");
+    println!(
+        "  {:<12} : {} ({} registros)",
+        "Anterior",
+        d.archivo_prev,
+        nomina_prev.registros.len()
+    );
+    println!(
+        "  {:<12} : {} ({} registros)",
+        "Actual",
+        d.archivo_curr,
+        nomina_curr.registros.len()
+    );
+    println!("{:-<80}", "");
+
+    for a in &d.altas {
+        println!("  + {:<12} {:<40} {:>15.2}", a.cedula, a.nombre, a.neto);
+    }
+    for b in &d.bajas {
+        println!("  - {:<12} {:<40} {:>15.2}", b.cedula, b.nombre, b.neto);
+    }
+
+    let por_campo = d.cambios_por_campo();
+    if !por_campo.is_empty() {
+        println!("{:-<80}", "");
+        println!("  Campos modificados ({} registros):", d.modificados.len());
+        for (campo, n) in &por_campo {
+            println!("  ~ {:<40} : {:>8}", campo, n);
+        }
+    }
+
+    if !d.anomalias.is_empty() {
+        println!("{:-<80}", "");
+        println!("  Anomalías:");
+        for a in &d.anomalias {
+            println!("  ! {:<12} [{}] {}", a.cedula, a.tipo, a.detalle);
+        }
+    }
+
+    println!("{:-<80}", "");
+    println!(
+        "  {:<12} : {:>8}\n  {:<12} : {:>8}\n  {:<12} : {:>8}\n  {:<12} : {:>8}",
+        "Altas",
+        d.altas.len(),
+        "Bajas",
+        d.bajas.len(),
+        "Modificados",
+        d.modificados.len(),
+        "Anomalías",
+        d.anomalias.len()
+    );
+    println!(
+        "  {:<12} : {:>15.2} -> {:.2} ({:+.2})",
+        "Neto total",
+        d.total_prev,
+        d.total_curr,
+        d.total_curr - d.total_prev
+    );
+    println!("{:=<80}\n", "");
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+// =============================================================================
+// COMANDO: EXPLAIN
+// =============================================================================
+// Traza el cálculo de un solo beneficiario (entradas, directiva, tiempo de
+// servicio, fórmulas, cálculos, conceptos y neto).
+//
+// Uso:
+//   sandra explain --cedula 17818665 --manifest nomina.json
+// =============================================================================
+
+use sandra_core::kernel::logica::logger;
+use sandra_core::model::Manifiesto;
+use sandra_core::tipos::TipoNomina;
+use sandra_core::System;
+
+use std::path::PathBuf;
+
+pub async fn execute(
+    cedula: String,
+    manifest_path: Option<String>,
+    tipo: TipoNomina,
+    debug: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if debug {
+        std::env::set_var("SANDRA_DEBUG", "1");
+        println!("[DEBUG] Modo debug habilitado");
+    }
+
+    println!("\n{:=<80}", "");
+    println!("{:^80}", "SANDRA SENTINEL - EXPLICACIÓN DE CÁLCULO");
+    println!("{:^80}", format!("CÉDULA: {} | TIPO: {}", cedula, tipo));
+    println!("{:=<80}", "");
+
+    let mut system = System::init();
+
+    if let Some(path) = manifest_path {
+        println!("> Cargando manifiesto desde '{}'...", path);
+        let m = Manifiesto::cargar_desde_archivo(&path)?;
+        println!("{:<20} : {}", "[CONFIG] Manifiesto", m.nombre);
+        println!("{:<20} : {}", "[CICLO ] Periodo", m.ciclo);
+        system.kernel.config = m;
+    } else {
+        println!("{:<20} : Estándar (Sin manifiesto)", "[CONFIG] Modo");
+    }
+    logger::init(false, &system.kernel.config.salida.destino);
+    println!("{:-<80}", "");
+
+    let url = system.config.get_url();
+    if let Err(e) = system.connect_sandra(url.clone()).await {
+        let msg = format!("Error conectando a Sandra Server: {}", e);
+        logger::log_error("CONEXION", &msg);
+        return Err(msg.into());
+    }
+
+    let traza = system
+        .kernel
+        .explicar_beneficiario(&cedula, tipo)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let texto = traza.a_texto();
+    println!("\n{}", texto);
+
+    // Salidas: explicacion_<cedula>_<ciclo>.json / .txt en salida.destino
+    let destino = &system.kernel.config.salida.destino;
+    let nombre = format!("explicacion_{}_{}", cedula, traza.ciclo);
+    let base_path = if destino == "." || destino.is_empty() {
+        PathBuf::from(&nombre)
+    } else {
+        std::fs::create_dir_all(destino)?;
+        PathBuf::from(destino).join(&nombre)
+    };
+
+    let ruta_json = base_path.with_extension("json");
+    std::fs::write(&ruta_json, serde_json::to_string_pretty(&traza)?)?;
+    let ruta_txt = base_path.with_extension("txt");
+    std::fs::write(&ruta_txt, &texto)?;
+
+    println!("  {:<20} : {}", "Traza JSON", ruta_json.display());
+    println!("  {:<20} : {}", "Reporte", ruta_txt.display());
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+// =============================================================================
+// COMANDO: FORMULAS
+// =============================================================================
+// Compara los snapshots de fórmulas (primas y conceptos) de dos ejecuciones.
+//
+// Uso:
+//   sandra formulas diff out/2026-01 out/2026-02
+//   sandra formulas diff out/2026-01/manifest.json out/2026-02/formulas.json --json
+// =============================================================================
+
+use sandra_core::calc::formulas::{self, FormulaSnapshot, SnapshotFormulas};
+
+pub fn diff(run_a: String, run_b: String, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let a = SnapshotFormulas::cargar(&run_a)?;
+    let b = SnapshotFormulas::cargar(&run_b)?;
+    let d = formulas::diff(&a, &b);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&d)?);
+        return Ok(());
+    }
+
+    println!("\n{:=<80}", "");
+    println!("{:^80}", "SANDRA SENTINEL - DIFERENCIAS DE FÓRMULAS");
+    println!("{:=<80}", "");
+    println!("  {:<12} : {} (ciclo {}, {})", "A", run_a, a.ciclo, corto(&a.hash_conjunto));
+    println!("  {:<12} : {} (ciclo {}, {})", "B", run_b, b.ciclo, corto(&b.hash_conjunto));
+    println!("{:-<80}", "");
+
+    if d.sin_cambios() {
+        println!("  Sin cambios: {} primas, {} conceptos idénticos.", b.primas.len(), b.conceptos.len());
+        println!("{:=<80}\n", "");
+        return Ok(());
+    }
+
+    for (origen, f) in &d.agregadas {
+        println!("  + [{:<8}] {}", origen, describir(f));
+        println!("      rhai : {}", f.codigo_rhai);
+    }
+    for (origen, f) in &d.eliminadas {
+        println!("  - [{:<8}] {}", origen, describir(f));
+        println!("      rhai : {}", f.codigo_rhai);
+    }
+    for c in &d.modificadas {
+        println!("  ~ [{:<8}] {} ({})", c.origen, c.codigo, c.campos.join(", "));
+        if c.antes.nombre != c.despues.nombre {
+            println!("      nombre        : {} -> {}", c.antes.nombre, c.despues.nombre);
+        }
+        if c.antes.monto_nominal != c.despues.monto_nominal {
+            println!(
+                "      monto_nominal : {:.2} -> {:.2}",
+                c.antes.monto_nominal, c.despues.monto_nominal
+            );
+        }
+        if c.antes.tipo != c.despues.tipo {
+            println!("      tipo          : {:?} -> {:?}", c.antes.tipo, c.despues.tipo);
+        }
+        if c.antes.codigo_rhai != c.despues.codigo_rhai {
+            println!("      - rhai : {}", c.antes.codigo_rhai);
+            println!("      + rhai : {}", c.despues.codigo_rhai);
+        }
+    }
+
+    println!("{:-<80}", "");
+    println!(
+        "  {:<12} : {:>5}\n  {:<12} : {:>5}\n  {:<12} : {:>5}",
+        "Agregadas",
+        d.agregadas.len(),
+        "Eliminadas",
+        d.eliminadas.len(),
+        "Modificadas",
+        d.modificadas.len()
+    );
+    println!("{:=<80}\n", "");
+
+    Ok(())
+}
+
+fn describir(f: &FormulaSnapshot) -> String {
+    format!("{} ({}) monto_nominal={:.2}", f.codigo, f.nombre, f.monto_nominal)
+}
+
+fn corto(hash: &str) -> &str {
+    &hash[..12.min(hash.len())]
+}
@@ -1,7 +1,13 @@
+pub mod banco;
 pub mod conciliate;
 pub mod conciliacion;
+pub mod diff;
+pub mod explain;
+pub mod formulas;
 pub mod patria;
+pub mod proyectar;
 pub mod reconcile_stream;
+pub mod simular;
 pub mod start;
 pub mod validar;
 pub mod version;
@@ -0,0 +1,95 @@
+// =============================================================================
+// COMANDO: PROYECTAR
+// =============================================================================
+// Proyecta el costo de la nómina a varios meses: avanza la antigüedad de la
+// población Base, aplica ascensos y retiros previstos y recalcula primas y
+// conceptos mes a mes.
+//
+// Uso:
+//   sandra proyectar --manifest nomina.json --meses 12
+// =============================================================================
+
+use sandra_core::kernel::logica::logger;
+use sandra_core::model::Manifiesto;
+use sandra_core::tipos::TipoNomina;
+use sandra_core::System;
+
+use std::path::PathBuf;
+
+pub async fn execute(
+    manifest_path: Option<String>,
+    meses: Option<u32>,
+    tipo: TipoNomina,
+    debug: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if debug {
+        std::env::set_var("SANDRA_DEBUG", "1");
+        println!("[DEBUG] Modo debug habilitado");
+    }
+
+    println!("\n{:=<80}", "");
+    println!("{:^80}", "SANDRA SENTINEL - PROYECCIÓN DE COSTO");
+    println!("{:^80}", format!("TIPO: {}", tipo));
+    println!("{:=<80}", "");
+
+    let mut system = System::init();
+
+    if let Some(path) = manifest_path {
+        println!("> Cargando manifiesto desde '{}'...", path);
+        let m = Manifiesto::cargar_desde_archivo(&path)?;
+        println!("{:<20} : {}", "[CONFIG] Manifiesto", m.nombre);
+        println!("{:<20} : {}", "[CICLO ] Periodo", m.ciclo);
+        system.kernel.config = m;
+    } else {
+        println!("{:<20} : Estándar (Sin manifiesto)", "[CONFIG] Modo");
+    }
+    if let Some(meses) = meses {
+        system.kernel.config.proyeccion.meses = meses;
+    }
+    if system.kernel.config.proyeccion.meses == 0 {
+        return Err("La proyección requiere al menos un mes".into());
+    }
+    println!(
+        "{:<20} : {} desde {}",
+        "[PROY  ] Meses",
+        system.kernel.config.proyeccion.meses,
+        system.kernel.config.fecha_referencia().format("%Y-%m")
+    );
+    logger::init(false, &system.kernel.config.salida.destino);
+    println!("{:-<80}", "");
+
+    let url = system.config.get_url();
+    if let Err(e) = system.connect_sandra(url.clone()).await {
+        let msg = format!("Error conectando a Sandra Server: {}", e);
+        logger::log_error("CONEXION", &msg);
+        return Err(msg.into());
+    }
+
+    let resultado = system
+        .kernel
+        .proyectar(tipo)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    println!("\n{}", resultado.a_texto());
+
+    // Salidas: proyeccion_<ciclo>.json / .csv en salida.destino
+    let destino = &system.kernel.config.salida.destino;
+    let nombre = format!("proyeccion_{}", resultado.ciclo);
+    let base_path = if destino == "." || destino.is_empty() {
+        PathBuf::from(&nombre)
+    } else {
+        std::fs::create_dir_all(destino)?;
+        PathBuf::from(destino).join(&nombre)
+    };
+
+    let ruta_json = base_path.with_extension("json");
+    std::fs::write(&ruta_json, serde_json::to_string_pretty(&resultado)?)?;
+    let ruta_csv = base_path.with_extension("csv");
+    std::fs::write(&ruta_csv, resultado.a_csv()?)?;
+
+    println!("  {:<20} : {}", "Proyección JSON", ruta_json.display());
+    println!("  {:<20} : {}", "Totales CSV", ruta_csv.display());
+
+    Ok(())
+}
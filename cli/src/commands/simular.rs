@@ -0,0 +1,93 @@
+// =============================================================================
+// COMANDO: SIMULAR
+// =============================================================================
+// Compara el costo de la nómina bajo dos manifiestos (Directiva, PrimasFunciones
+// y Conceptos) sobre la misma población. No genera archivos bancarios.
+//
+// Uso:
+//   sandra simular --base-manifest a.json --alt-manifest b.json
+// =============================================================================
+
+use sandra_core::kernel::logica::logger;
+use sandra_core::model::Manifiesto;
+use sandra_core::tipos::TipoNomina;
+use sandra_core::System;
+
+use std::path::PathBuf;
+
+pub async fn execute(
+    base_manifest: String,
+    alt_manifest: String,
+    tipo: TipoNomina,
+    top: usize,
+    debug: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if debug {
+        std::env::set_var("SANDRA_DEBUG", "1");
+        println!("[DEBUG] Modo debug habilitado");
+    }
+
+    println!("\n{:=<80}", "");
+    println!("{:^80}", "SANDRA SENTINEL - SIMULACIÓN DE COSTO");
+    println!("{:^80}", format!("TIPO: {}", tipo));
+    println!("{:=<80}", "");
+
+    let base = Manifiesto::cargar_desde_archivo(&base_manifest)?;
+    let alternativo = Manifiesto::cargar_desde_archivo(&alt_manifest)?;
+    println!(
+        "{:<20} : {} ({})",
+        "[BASE  ] Manifiesto", base.nombre, base_manifest
+    );
+    println!(
+        "{:<20} : {} ({})",
+        "[ALTER ] Manifiesto", alternativo.nombre, alt_manifest
+    );
+    println!("{:<20} : {}", "[CICLO ] Periodo", base.ciclo);
+    if alternativo.ciclo != base.ciclo {
+        println!(
+            "{:<20} : la población se carga con el ciclo base ({}), no con {}",
+            "[AVISO ]", base.ciclo, alternativo.ciclo
+        );
+    }
+
+    let mut system = System::init();
+    system.kernel.config = base;
+    logger::init(false, &system.kernel.config.salida.destino);
+    println!("{:-<80}", "");
+
+    let url = system.config.get_url();
+    if let Err(e) = system.connect_sandra(url.clone()).await {
+        let msg = format!("Error conectando a Sandra Server: {}", e);
+        logger::log_error("CONEXION", &msg);
+        return Err(msg.into());
+    }
+
+    let resultado = system
+        .kernel
+        .simular(&alternativo, tipo, top)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let texto = resultado.a_texto();
+    println!("\n{}", texto);
+
+    // Salidas: simulacion_<ciclo>.json / .txt en salida.destino del manifiesto base
+    let destino = &system.kernel.config.salida.destino;
+    let nombre = format!("simulacion_{}", resultado.ciclo);
+    let base_path = if destino == "." || destino.is_empty() {
+        PathBuf::from(&nombre)
+    } else {
+        std::fs::create_dir_all(destino)?;
+        PathBuf::from(destino).join(&nombre)
+    };
+
+    let ruta_json = base_path.with_extension("json");
+    std::fs::write(&ruta_json, serde_json::to_string_pretty(&resultado)?)?;
+    let ruta_txt = base_path.with_extension("txt");
+    std::fs::write(&ruta_txt, &texto)?;
+
+    println!("  {:<20} : {}", "Resultado JSON", ruta_json.display());
+    println!("  {:<20} : {}", "Reporte", ruta_txt.display());
+
+    Ok(())
+}
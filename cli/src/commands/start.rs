@@ -1,4 +1,5 @@
 use sandra_core::banco::{self, TipoArchivo};
+use sandra_core::calc::formulas::SnapshotFormulas;
 use sandra_core::kernel::logica::{exportador, logger, telemetria};
 use sandra_core::tipos::TipoNomina;
 use sandra_core::System;
@@ -196,6 +197,25 @@ pub async fn execute(
                     };
                     let es_nfcp = matches!(tipo, sandra_core::tipos::TipoNomina::Nfcp);
 
+                    // VALIDACIÓN PREVIA (violaciones de las reglas del manifiesto)
+                    if !system.kernel.validacion.violaciones.is_empty() {
+                        match system.kernel.validacion.exportar(destino) {
+                            Ok(resultado) => {
+                                println!(
+                                    "  {:<25} : {:>10} ({})",
+                                    "Validación",
+                                    "OK",
+                                    path_relative(&resultado.ruta, destino)
+                                );
+                                resultados_export.push(resultado);
+                            }
+                            Err(e) => {
+                                eprintln!("  {:<25} : {:>10}", "Validación", "FALLO");
+                                eprintln!("    └─ [ERROR] {}", e);
+                            }
+                        }
+                    }
+
                     // EXPORTACION NÓMINA POR TIPO
                     let t_export = std::time::Instant::now();
 
@@ -206,7 +226,10 @@ pub async fn execute(
                         destino,
                         comprimir,
                         nivel,
-                        es_nfcp,
+                        exportador::ColumnasNomina {
+                            es_nfcp,
+                            numero_cuenta: system.kernel.config.salida.columna_numero_cuenta,
+                        },
                     ) {
                         Ok(resultados_nomina) => {
                             telemetria::record(
@@ -263,14 +286,23 @@ pub async fn execute(
                             println!("{:^80}", "EXPORTANDO APORTE Y APERTURA CONJUNTOS");
                             println!("{:-<80}\n", "");
 
+                            let limite_apertura = system
+                                .kernel
+                                .config
+                                .salida
+                                .limites
+                                .get(banco::Banco::Venezuela.codigo())
+                                .copied()
+                                .unwrap_or_default();
                             match exportador::exportar_aporte_y_apertura_txt(
                                 &system.kernel.beneficiarios,
                                 ciclo,
                                 destino,
                                 comprimir,
                                 nivel,
+                                &limite_apertura,
                             ) {
-                                Ok((res_aporte, res_apertura)) => {
+                                Ok((res_aporte, res_aperturas, res_no_enrutados)) => {
                                     telemetria::record(
                                         "EXPORT",
                                         "CSV Aporte+Apertura",
@@ -301,7 +333,11 @@ pub async fn execute(
 
                                     resultados_export.push(res_aporte);
 
-                                    if res_apertura.tamano_original > 0 {
+                                    for res_apertura in res_aperturas {
+                                        if res_apertura.tamano_original == 0 {
+                                            println!("  {:<25} : {:>10}", "Exportación Apertura", "SKIP (vacío)");
+                                            continue;
+                                        }
                                         println!(
                                             "  {:<25} : {:>10} ({})",
                                             "Exportación Apertura",
@@ -323,9 +359,15 @@ pub async fn execute(
                                         }
 
                                         resultados_export.push(res_apertura);
-                                    } else {
-                                        println!("  {:<25} : {:>10}", "Exportación Apertura", "SKIP (vacío)");
                                     }
+
+                                    println!(
+                                        "  {:<25} : {:>10} ({})",
+                                        "No Enrutados Apertura",
+                                        "OK",
+                                        path_relative(&res_no_enrutados.ruta, destino)
+                                    );
+                                    resultados_export.push(res_no_enrutados);
                                 }
                                 Err(e) => {
                                     let msg = format!("Error exportando CSV de aporte y apertura: {}", e);
@@ -399,93 +441,268 @@ pub async fn execute(
                                 let tipo = TipoArchivo::from_str(format_txt).unwrap_or(TipoArchivo::Aporte);
                                 let comprimir = system.kernel.config.salida.compresion;
                                 let nivel = system.kernel.config.salida.nivel_compresion;
-                                
+                                let mut registro = banco::RegistroBancos::por_defecto();
+                                if let Err(e) = registro.cargar_disenos(&system.kernel.config.salida.disenos) {
+                                    eprintln!("  {:<25} : {:>10}", "Diseños bancarios", "FALLO");
+                                    eprintln!("    └─ [ERROR] {}", e);
+                                    logger::log_error("BANCO", &e.to_string());
+                                    // Sin el diseño pedido no se generan archivos con el anterior.
+                                    registro = banco::RegistroBancos::vacio();
+                                }
+                                let mut ctx = banco::ContextoArchivo::new(tipo, ciclo, 100.0);
+                                // Sin RIF válido fallan solo los diseños que lo llevan
+                                match system.kernel.config.salida.rif_empresa() {
+                                    Ok(rif) => ctx.rif = rif,
+                                    Err(e) => {
+                                        eprintln!("  {:<25} : {:>10}", "RIF empresa", "FALLO");
+                                        eprintln!("    └─ [ERROR] {}", e);
+                                        logger::log_error("BANCO", &e);
+                                    }
+                                }
+
                                 for codigo_banco in bancos {
-                                println!("> Procesando banco: {}...", codigo_banco);
-                                
-                                match codigo_banco.as_str() {
-                                    "0102" => {
-                                        match banco::venezuela::generar_txt_venezuela(
-                                            &system.kernel.beneficiarios,
-                                            tipo,
-                                            ciclo,
-                                            destino,
-                                            100.0,
-                                            comprimir,
-                                            nivel,
-                                        ) {
-                                            Ok(resultado) => {
-                                                println!(
-                                                    "  {:<25} : {:>10} ({})",
-                                                    "TXT Venezuela",
-                                                    "OK",
-                                                    path_relative(&resultado.ruta, destino)
-                                                );
-                                                resultados_export.push(resultado);
-                                            }
-                                            Err(e) => {
-                                                eprintln!("  {:<25} : {:>10}", "TXT Venezuela", "FALLO");
-                                                eprintln!("    └─ [ERROR] {}", e);
-                                            }
-                                        }
+                                    if registro.por_codigo(codigo_banco).is_none() {
+                                        println!("> Procesando banco: {}...", codigo_banco);
+                                        println!("  {:<25} : {:>10} (banco no soportado)", "TXT", "SKIP");
                                     }
-                                    "0177" => {
-                                        match banco::banfanb::generar_txt_banfanb(
-                                            &system.kernel.beneficiarios,
-                                            ciclo,
-                                            destino,
-                                            "0131",
-                                            comprimir,
-                                            nivel,
-                                        ) {
-                                            Ok(resultado) => {
-                                                println!(
-                                                    "  {:<25} : {:>10} ({})",
-                                                    "TXT Banfanb",
-                                                    "OK",
-                                                    path_relative(&resultado.ruta, destino)
-                                                );
-                                                resultados_export.push(resultado);
-                                            }
-                                            Err(e) => {
-                                                eprintln!("  {:<25} : {:>10}", "TXT Banfanb", "FALLO");
-                                                eprintln!("    └─ [ERROR] {}", e);
-                                            }
-                                        }
+                                }
+
+                                // Cada beneficiario va al banco de su cuenta; el resto, a no_enrutados.
+                                let enrutamiento = banco::enrutamiento::enrutar(
+                                    &system.kernel.beneficiarios,
+                                    &registro,
+                                    bancos,
+                                    &ctx,
+                                );
+
+                                for (banco_destino, renglones) in &enrutamiento.por_banco {
+                                    println!("> Procesando banco: {}...", banco_destino.codigo());
+
+                                    let Some(formato) = registro.obtener(*banco_destino) else {
+                                        continue;
+                                    };
+                                    let etiqueta = format!("TXT {}", formato.banco().nombre());
+                                    if !formato.soporta(tipo) {
+                                        println!(
+                                            "  {:<25} : {:>10} ({} no soportado)",
+                                            etiqueta,
+                                            "SKIP",
+                                            tipo.as_str()
+                                        );
+                                        continue;
                                     }
-                                    "0175" => {
-                                        match banco::bicentenario::generar_txt_bicentenario(
-                                            &system.kernel.beneficiarios,
-                                            ciclo,
-                                            destino,
-                                            "0175",
-                                            comprimir,
-                                            nivel,
-                                        ) {
-                                            Ok(resultado) => {
+
+                                    let limite = system
+                                        .kernel
+                                        .config
+                                        .salida
+                                        .limites
+                                        .get(banco_destino.codigo())
+                                        .copied()
+                                        .unwrap_or_default();
+                                    match banco::formato::escribir_archivos(
+                                        formato,
+                                        renglones,
+                                        &ctx,
+                                        destino,
+                                        comprimir,
+                                        nivel,
+                                        &limite,
+                                    ) {
+                                        Ok(archivos) => {
+                                            for archivo in archivos {
                                                 println!(
                                                     "  {:<25} : {:>10} ({})",
-                                                    "TXT Bicentenario",
+                                                    etiqueta,
                                                     "OK",
-                                                    path_relative(&resultado.ruta, destino)
+                                                    path_relative(&archivo.export.ruta, destino)
                                                 );
-                                                resultados_export.push(resultado);
-                                            }
-                                            Err(e) => {
-                                                eprintln!("  {:<25} : {:>10}", "TXT Bicentenario", "FALLO");
-                                                eprintln!("    └─ [ERROR] {}", e);
+                                                if !archivo.rechazados.is_empty() {
+                                                    println!(
+                                                        "    {:<23} : {} beneficiarios (ver log)",
+                                                        "Rechazados",
+                                                        archivo.rechazados.len()
+                                                    );
+                                                }
+                                                resultados_export.push(archivo.export);
                                             }
                                         }
+                                        Err(e) => {
+                                            eprintln!("  {:<25} : {:>10}", etiqueta, "FALLO");
+                                            eprintln!("    └─ [ERROR] {}", e);
+                                        }
                                     }
-                                    _ => {
-                                        println!("  {:<25} : {:>10} (banco no soportado)", "TXT", "SKIP");
+                                }
+
+                                match enrutamiento.exportar(ciclo, destino) {
+                                    Ok(resultado) => {
+                                        println!(
+                                            "  {:<25} : {:>10} ({})",
+                                            "No Enrutados",
+                                            enrutamiento.no_enrutados.len(),
+                                            path_relative(&resultado.ruta, destino)
+                                        );
+                                        resultados_export.push(resultado);
+                                    }
+                                    Err(e) => {
+                                        eprintln!("  {:<25} : {:>10}", "No Enrutados", "FALLO");
+                                        eprintln!("    └─ [ERROR] {}", e);
                                     }
                                 }
+
+                                // Cuadre: bancos + no enrutados = total de la nómina
+                                println!("\n  {:<25} : {:>15}", "Cuadre por Banco", "Monto");
+                                for (banco_destino, _) in &enrutamiento.por_banco {
+                                    let (lineas, monto) = enrutamiento.lineas_banco(*banco_destino);
+                                    println!(
+                                        "    {:<23} : {:>15.2} ({} líneas)",
+                                        banco_destino.nombre(),
+                                        monto,
+                                        lineas
+                                    );
+                                }
+                                println!(
+                                    "    {:<23} : {:>15.2} ({} beneficiarios)",
+                                    "No enrutados",
+                                    enrutamiento.total_no_enrutado(),
+                                    enrutamiento.no_enrutados.len()
+                                );
+                                println!("    {:<23} : {:>15.2}", "Total nómina", enrutamiento.total);
+                                let diferencia = enrutamiento.diferencia();
+                                println!("    {:<23} : {:>15.2}", "Diferencia", diferencia);
+                                if diferencia.abs() > 0.005 {
+                                    logger::log_warn(
+                                        "BANCO",
+                                        &format!("Cuadre por banco con diferencia de {:.2}", diferencia),
+                                    );
+                                }
                             }
                         }
-                    }
                     }  // Cierre if NPR para TXT bancarios
 
+                    // SNAPSHOT DE FÓRMULAS (primas + conceptos usados en esta ejecución)
+                    let snapshot_formulas = SnapshotFormulas::desde(
+                        ciclo,
+                        &system.kernel.primas_funciones,
+                        &system.kernel.conceptos_nomina,
+                    );
+                    match snapshot_formulas.guardar(destino) {
+                        Ok(resultado) => {
+                            println!(
+                                "  {:<25} : {:>10} ({})",
+                                "Snapshot Fórmulas",
+                                "OK",
+                                path_relative(&resultado.ruta, destino)
+                            );
+                            resultados_export.push(resultado);
+                        }
+                        Err(e) => {
+                            eprintln!("  {:<25} : {:>10}", "Snapshot Fórmulas", "FALLO");
+                            eprintln!("    └─ [ERROR] {}", e);
+                        }
+                    }
+
+                    // COBERTURA DE FÓRMULAS (ejecutadas, ceros, min/max/media/suma)
+                    match system.kernel.cobertura.exportar(destino) {
+                        Ok(resultados) => {
+                            for resultado in resultados {
+                                println!(
+                                    "  {:<25} : {:>10} ({})",
+                                    "Cobertura Fórmulas",
+                                    "OK",
+                                    path_relative(&resultado.ruta, destino)
+                                );
+                                resultados_export.push(resultado);
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("  {:<25} : {:>10}", "Cobertura Fórmulas", "FALLO");
+                            eprintln!("    └─ [ERROR] {}", e);
+                        }
+                    }
+
+                    // RECORTES POR LÍMITE LEGAL DE DEDUCCIONES
+                    if !system.kernel.recortes.is_empty() {
+                        match sandra_core::nomina::limites::exportar_recortes(&system.kernel.recortes, destino) {
+                            Ok(resultado) => {
+                                println!(
+                                    "  {:<25} : {:>10} ({})",
+                                    "Recortes Deducciones",
+                                    "OK",
+                                    path_relative(&resultado.ruta, destino)
+                                );
+                                resultados_export.push(resultado);
+                            }
+                            Err(e) => {
+                                eprintln!("  {:<25} : {:>10}", "Recortes Deducciones", "FALLO");
+                                eprintln!("    └─ [ERROR] {}", e);
+                            }
+                        }
+                    }
+
+                    // CONCILIACIÓN DE LA DISTRIBUCIÓN DE GARANTÍAS
+                    if let Some(distribucion) = &system.kernel.distribucion_garantias {
+                        match distribucion.exportar(destino) {
+                            Ok(resultados) => {
+                                for resultado in resultados {
+                                    println!(
+                                        "  {:<25} : {:>10} ({})",
+                                        "Conciliación Garantías",
+                                        "OK",
+                                        path_relative(&resultado.ruta, destino)
+                                    );
+                                    resultados_export.push(resultado);
+                                }
+                            }
+                            Err(e) => {
+                                eprintln!("  {:<25} : {:>10}", "Conciliación Garantías", "FALLO");
+                                eprintln!("    └─ [ERROR] {}", e);
+                            }
+                        }
+                    }
+
+                    // CRÉDITOS (saldos para el próximo ciclo + ejecución)
+                    if !system.kernel.reporte_creditos.movimientos.is_empty() {
+                        match system.kernel.reporte_creditos.exportar(destino) {
+                            Ok(resultados) => {
+                                for resultado in resultados {
+                                    println!(
+                                        "  {:<25} : {:>10} ({})",
+                                        "Créditos",
+                                        "OK",
+                                        path_relative(&resultado.ruta, destino)
+                                    );
+                                    resultados_export.push(resultado);
+                                }
+                            }
+                            Err(e) => {
+                                eprintln!("  {:<25} : {:>10}", "Créditos", "FALLO");
+                                eprintln!("    └─ [ERROR] {}", e);
+                            }
+                        }
+                    }
+
+                    // EMBARGOS JUDICIALES (ejecución por orden + líneas de pago)
+                    if !system.kernel.embargos.ejecuciones.is_empty() {
+                        match system.kernel.embargos.exportar(destino) {
+                            Ok(resultados) => {
+                                for resultado in resultados {
+                                    println!(
+                                        "  {:<25} : {:>10} ({})",
+                                        "Embargos",
+                                        "OK",
+                                        path_relative(&resultado.ruta, destino)
+                                    );
+                                    resultados_export.push(resultado);
+                                }
+                            }
+                            Err(e) => {
+                                eprintln!("  {:<25} : {:>10}", "Embargos", "FALLO");
+                                eprintln!("    └─ [ERROR] {}", e);
+                            }
+                        }
+                    }
+
                     // GENERAR MANIFEST
                     if !resultados_export.is_empty() {
                         let id_operacion = format!("NOM-{}-{}", ciclo, chrono::Local::now().format("%Y%m%d-%H%M"));
@@ -494,6 +711,7 @@ pub async fn execute(
                             &id_operacion,
                             destino,
                             &resultados_export,
+                            Some(&snapshot_formulas),
                         ) {
                             eprintln!("  {:<25} : {:>10}", "Manifest", "FALLO");
                             eprintln!("    └─ [ERROR] {}", e);
@@ -530,6 +748,14 @@ pub async fn execute(
                 eprintln!("{}", msg);
                 println!("{:=<80}\n", "");
                 logger::log_error("KERNEL", &msg);
+
+                // Un aborto por validación deja el reporte para revisar las violaciones
+                if !system.kernel.validacion.violaciones.is_empty() {
+                    match system.kernel.validacion.exportar(&system.kernel.config.salida.destino) {
+                        Ok(resultado) => eprintln!("  {:<25} : {}", "Validación", resultado.ruta),
+                        Err(e) => eprintln!("    └─ [ERROR] Exportando validación: {}", e),
+                    }
+                }
             }
         }
     } else {
@@ -163,6 +163,132 @@ enum Commands {
         debug: bool,
     },
 
+    /// Explica paso a paso el cálculo de un beneficiario.
+    #[command(
+        long_about = "Carga solo el beneficiario indicado y genera una traza del cálculo: Base cruda, Directiva,\n\
+        tiempo de servicio, cada fórmula con sus entradas, pasos de cálculo (aguinaldo, vacaciones,\n\
+        integral, garantías) y conceptos. Escribe explicacion_<cedula>_<ciclo>.json y .txt en salida.destino.\n\n\
+        Ejemplo:\n  sandra explain --cedula 17818665 --manifest nomina.json"
+    )]
+    Explain {
+        /// Cédula del beneficiario.
+        #[arg(short = 'c', long)]
+        cedula: String,
+
+        /// Ruta al archivo de manifiesto (.json) con configuración.
+        #[arg(short = 'm', long = "manifest")]
+        manifest: Option<String>,
+
+        /// Tipo de nómina.
+        #[arg(short = 't', long, value_enum, default_value = "npr")]
+        tipo: TipoNominaCli,
+
+        /// Activa mensajes de debug para depuración.
+        #[arg(short = 'd', long = "debug")]
+        debug: bool,
+    },
+
+    /// Simula el costo de la nómina bajo otra directiva.
+    #[command(
+        long_about = "Carga una sola vez la población con el manifiesto base y la calcula con las cadenas de cálculo\n\
+        (Directiva, PrimasFunciones, Conceptos) de ambos manifiestos. Reporta la diferencia de costo por\n\
+        componente, grado y concepto, y los beneficiarios con mayores cambios. Escribe simulacion_<ciclo>.json\n\
+        y .txt en salida.destino del manifiesto base; no genera archivos bancarios.\n\n\
+        Ejemplo:\n  sandra simular --base-manifest directiva_81.json --alt-manifest directiva_82.json --tipo nact"
+    )]
+    Simular {
+        /// Manifiesto del escenario base (define también la población).
+        #[arg(long = "base-manifest")]
+        base_manifest: String,
+
+        /// Manifiesto del escenario alternativo.
+        #[arg(long = "alt-manifest")]
+        alt_manifest: String,
+
+        /// Tipo de nómina.
+        #[arg(short = 't', long, value_enum, default_value = "npr")]
+        tipo: TipoNominaCli,
+
+        /// Cantidad de beneficiarios con mayores cambios a reportar.
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+
+        /// Activa mensajes de debug para depuración.
+        #[arg(short = 'd', long = "debug")]
+        debug: bool,
+    },
+
+    /// Proyecta el costo de la nómina a varios meses.
+    #[command(
+        long_about = "Avanza la población Base mes a mes desde el ciclo del manifiesto: antigüedad y antigüedad en grado,\n\
+        ascensos previstos y retiros programados (sección 'proyeccion' del manifiesto), recalculando directiva,\n\
+        primas y conceptos. Escribe proyeccion_<ciclo>.json y .csv (totales por mes, componente y concepto).\n\n\
+        Ejemplo:\n  sandra proyectar --manifest nomina.json --meses 12 --tipo nact"
+    )]
+    Proyectar {
+        /// Ruta al archivo de manifiesto (.json) con configuración.
+        #[arg(short = 'm', long = "manifest")]
+        manifest: Option<String>,
+
+        /// Meses a proyectar (por defecto proyeccion.meses del manifiesto).
+        #[arg(long)]
+        meses: Option<u32>,
+
+        /// Tipo de nómina.
+        #[arg(short = 't', long, value_enum, default_value = "nact")]
+        tipo: TipoNominaCli,
+
+        /// Activa mensajes de debug para depuración.
+        #[arg(short = 'd', long = "debug")]
+        debug: bool,
+    },
+
+    /// Compara la nómina exportada de dos ciclos.
+    #[command(
+        long_about = "Cruza por cédula el nomina_<tipo>_<ciclo>.csv de dos ciclos y reporta altas, bajas,\n\
+        campos modificados y anomalías (variación del neto, cambio de cuenta con monto alto,\n\
+        sueldo_base sin cambio de grado). Los umbrales se leen de `diferencias` en el manifiesto.\n\
+        Cada ciclo puede indicarse por su carpeta de salida o por el archivo .csv / .csv.zst.\n\n\
+        Ejemplo:\n  sandra diff --prev out/2026-01 --curr out/2026-02 --tipo nact -m nomina.json"
+    )]
+    Diff {
+        /// Nómina del ciclo anterior (carpeta o archivo).
+        #[arg(long)]
+        prev: String,
+
+        /// Nómina del ciclo actual (carpeta o archivo).
+        #[arg(long)]
+        curr: String,
+
+        /// Tipo de nómina a buscar en las carpetas (npr, nact, nrcp, nfcp).
+        #[arg(short = 't', long)]
+        tipo: Option<String>,
+
+        /// Manifiesto con los umbrales de anomalías.
+        #[arg(short = 'm', long = "manifest")]
+        manifest: Option<String>,
+
+        /// Carpeta donde escribir diferencias_nomina.csv y anomalias_nomina.csv.
+        #[arg(long)]
+        salida: Option<String>,
+
+        /// Imprime la diferencia como JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Operaciones sobre los TXT bancarios generados.
+    Banco {
+        #[command(subcommand)]
+        accion: BancoAccion,
+    },
+
+    /// Operaciones sobre los snapshots de fórmulas de cada ejecución.
+    Formulas {
+        #[command(subcommand)]
+        accion: FormulasAccion,
+    },
+
     /// Procesa conciliación de nómina desde un archivo local.
     #[command(
         long_about = "Permite procesar archivos de nómina para validación y conciliación manual.\nAnteriormente conocido como modo Lote."
@@ -243,6 +369,96 @@ enum Commands {
     Version,
 }
 
+#[derive(Subcommand)]
+enum BancoAccion {
+    /// Relee un TXT bancario y verifica largos, totales de control y montos.
+    #[command(
+        long_about = "Interpreta un TXT bancario (o .txt.zst) con su diseño de schema/bancos/, elegido por el\n\
+        nombre del archivo o indicado con --diseno. Verifica el largo y los campos numéricos de cada\n\
+        línea, el fin de línea, la cantidad y el total de cabecera y pie, cédulas repetidas y, con\n\
+        --nomina, que el monto de cada cédula coincida con la nómina exportada.\n\
+        Termina con error si hay observaciones.\n\n\
+        Ejemplo:\n  sandra banco verificar out/banfanb_2026-02.txt --nomina out --tipo npr --columna garantias"
+    )]
+    Verificar {
+        /// Archivo TXT generado.
+        archivo: String,
+
+        /// Diseño JSON con el que leer el archivo (por defecto según su nombre).
+        #[arg(long)]
+        diseno: Option<String>,
+
+        /// Nómina exportada del ciclo (carpeta o archivo) para comparar montos.
+        #[arg(long)]
+        nomina: Option<String>,
+
+        /// Tipo de nómina a buscar en la carpeta (npr, nact, nrcp, nfcp).
+        #[arg(short = 't', long)]
+        tipo: Option<String>,
+
+        /// Columna(s) de la nómina con el monto de cada línea, sumadas con '+'.
+        #[arg(long, default_value = "garantias")]
+        columna: String,
+
+        /// Imprime el resultado como JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Procesa la respuesta del banco sobre un TXT enviado.
+    #[command(
+        long_about = "Cruza la respuesta del banco (aceptados, rechazados y reversos con su código de motivo)\n\
+        con las líneas del TXT enviado por cédula y cuenta. Actualiza el estado de cada pago en\n\
+        estado_pagos_<archivo>.csv y escribe rechazos_<archivo>.csv y <archivo>_reproceso.txt con\n\
+        las líneas a reenviar.\n\n\
+        Ejemplo:\n  sandra banco respuesta out/banfanb_2026-02.txt respuestas/banfanb_2026-02.txt --salida out"
+    )]
+    Respuesta {
+        /// Archivo TXT enviado al banco.
+        archivo: String,
+
+        /// Archivo de respuesta del banco.
+        respuesta: String,
+
+        /// Diseño JSON del archivo enviado (por defecto según su nombre).
+        #[arg(long)]
+        diseno: Option<String>,
+
+        /// Diseño JSON de la respuesta (por defecto el del banco en schema/bancos/respuestas/).
+        #[arg(long)]
+        diseno_respuesta: Option<String>,
+
+        /// Carpeta donde escribir el estado, los rechazos y el reproceso.
+        #[arg(long, default_value = ".")]
+        salida: String,
+
+        /// Imprime el resultado como JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum FormulasAccion {
+    /// Muestra fórmulas agregadas, eliminadas y modificadas entre dos ejecuciones.
+    #[command(
+        long_about = "Compara el snapshot de fórmulas (primas y conceptos) de dos ejecuciones.\n\
+        Cada ejecución puede indicarse por su carpeta de salida, su formulas.json o su manifest.json.\n\n\
+        Ejemplo:\n  sandra formulas diff out/2026-01 out/2026-02"
+    )]
+    Diff {
+        /// Ejecución de referencia (A).
+        run_a: String,
+
+        /// Ejecución a comparar (B).
+        run_b: String,
+
+        /// Imprime la diferencia como JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
@@ -321,6 +537,66 @@ Some(Commands::Patria { manifest, conciliacion, debug }) => {
             // Ejecutar comando Patria
             commands::patria::execute(manifest.clone(), *conciliacion, *debug).await?;
         }
+        Some(Commands::Explain { cedula, manifest, tipo, debug }) => {
+            commands::explain::execute(cedula.clone(), manifest.clone(), (*tipo).into(), *debug).await?;
+        }
+        Some(Commands::Simular { base_manifest, alt_manifest, tipo, top, debug }) => {
+            commands::simular::execute(base_manifest.clone(), alt_manifest.clone(), (*tipo).into(), *top, *debug).await?;
+        }
+        Some(Commands::Proyectar { manifest, meses, tipo, debug }) => {
+            commands::proyectar::execute(manifest.clone(), *meses, (*tipo).into(), *debug).await?;
+        }
+        Some(Commands::Diff { prev, curr, tipo, manifest, salida, json }) => {
+            commands::diff::execute(
+                prev.clone(),
+                curr.clone(),
+                tipo.clone(),
+                manifest.clone(),
+                salida.clone(),
+                *json,
+            )?;
+        }
+        Some(Commands::Banco { accion }) => match accion {
+            BancoAccion::Verificar {
+                archivo,
+                diseno,
+                nomina,
+                tipo,
+                columna,
+                json,
+            } => {
+                commands::banco::verificar(
+                    archivo.clone(),
+                    diseno.clone(),
+                    nomina.clone(),
+                    tipo.clone(),
+                    columna.clone(),
+                    *json,
+                )?;
+            }
+            BancoAccion::Respuesta {
+                archivo,
+                respuesta,
+                diseno,
+                diseno_respuesta,
+                salida,
+                json,
+            } => {
+                commands::banco::respuesta(
+                    archivo.clone(),
+                    respuesta.clone(),
+                    diseno.clone(),
+                    diseno_respuesta.clone(),
+                    salida.clone(),
+                    *json,
+                )?;
+            }
+        },
+        Some(Commands::Formulas { accion }) => match accion {
+            FormulasAccion::Diff { run_a, run_b, json } => {
+                commands::formulas::diff(run_a.clone(), run_b.clone(), *json)?;
+            }
+        },
         Some(Commands::Version) => {
             commands::version::execute();
         }
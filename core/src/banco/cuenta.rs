@@ -0,0 +1,89 @@
+//! Validación del Código Cuenta Cliente (CCC) de 20 dígitos.
+//!
+//! | Posición | Largo | Contenido            |
+//! |----------|-------|----------------------|
+//! | 1-4      | 4     | Código del banco     |
+//! | 5-8      | 4     | Oficina (agencia)    |
+//! | 9-10     | 2     | Dígitos de control   |
+//! | 11-20    | 10    | Número de cuenta     |
+//!
+//! El primer dígito de control se calcula sobre banco y oficina con los pesos
+//! `3 2 7 6 5 4 3 2`; el segundo sobre oficina y número de cuenta con
+//! `3 2 7 6 5 4 3 2 7 6 5 4 3 2`. En ambos, `11 - (suma % 11)`, donde 11 vale 0
+//! y 10 vale 1.
+
+const PESOS_BANCO_OFICINA: [u32; 8] = [3, 2, 7, 6, 5, 4, 3, 2];
+const PESOS_OFICINA_CUENTA: [u32; 14] = [3, 2, 7, 6, 5, 4, 3, 2, 7, 6, 5, 4, 3, 2];
+
+fn digito(digitos: &[u32], pesos: &[u32]) -> u32 {
+    let suma: u32 = digitos.iter().zip(pesos).map(|(d, p)| d * p).sum();
+    match 11 - suma % 11 {
+        11 => 0,
+        10 => 1,
+        d => d,
+    }
+}
+
+/// Dígitos de control que corresponden a la cuenta (ignora las posiciones 9-10).
+/// `None` si no tiene 20 dígitos.
+pub fn digitos_control(cuenta: &str) -> Option<String> {
+    let digitos: Vec<u32> = cuenta.chars().filter_map(|c| c.to_digit(10)).collect();
+    if digitos.len() != 20 || cuenta.chars().count() != 20 {
+        return None;
+    }
+    let primero = digito(&digitos[0..8], &PESOS_BANCO_OFICINA);
+    let oficina_cuenta: Vec<u32> = digitos[4..8]
+        .iter()
+        .chain(&digitos[10..20])
+        .copied()
+        .collect();
+    let segundo = digito(&oficina_cuenta, &PESOS_OFICINA_CUENTA);
+    Some(format!("{}{}", primero, segundo))
+}
+
+/// Verifica largo, que sean solo dígitos y los dígitos de control de la cuenta.
+pub fn validar_ccc(cuenta: &str) -> Result<(), String> {
+    let cuenta = cuenta.trim();
+    if cuenta.is_empty() {
+        return Err("sin numero_cuenta".to_string());
+    }
+    let Some(esperado) = digitos_control(cuenta) else {
+        return Err(format!("cuenta {} no tiene 20 dígitos", cuenta));
+    };
+    if cuenta[8..10] != esperado {
+        return Err(format!(
+            "cuenta {}: dígitos de control {}, se esperaba {}",
+            cuenta,
+            &cuenta[8..10],
+            esperado
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digitos_control_ccc() {
+        // 0102 0123: 0*3+1*2+0*7+2*6+0*5+1*4+2*3+3*2 = 30 -> 11 - 8 = 3
+        // 0123 0000000001: 0*3+1*2+2*7+3*6+...+1*2 = 36 -> 11 - 3 = 8
+        assert_eq!(digitos_control("01020123xx0000000001"), None);
+        assert_eq!(digitos_control("01020123000000000001").unwrap(), "38");
+        assert!(validar_ccc("01020123380000000001").is_ok());
+        assert!(validar_ccc(" 01020123380000000001 ").is_ok());
+
+        assert_eq!(
+            validar_ccc("01020123990000000001").unwrap_err(),
+            "cuenta 01020123990000000001: dígitos de control 99, se esperaba 38"
+        );
+        assert_eq!(validar_ccc("").unwrap_err(), "sin numero_cuenta");
+        assert!(validar_ccc("0102-0123-38-0000000001").is_err());
+        assert!(validar_ccc("0102012338000000000").is_err());
+
+        // Resto 0 (11 -> 0) y resto 1 (10 -> 1)
+        assert_eq!(digito(&[0; 8], &PESOS_BANCO_OFICINA), 0);
+        assert_eq!(digito(&[0, 0, 0, 0, 0, 0, 0, 6], &PESOS_BANCO_OFICINA), 1);
+    }
+}
@@ -0,0 +1,638 @@
+//! Diseño de registro declarativo para archivos bancarios de ancho fijo.
+//!
+//! Un `DisenoArchivo` (JSON, ver `schema/bancos/`) describe la cabecera, el
+//! detalle y el pie como listas de campos con columna inicial, largo, tipo,
+//! alineación, relleno, escala numérica y una expresión Rhai de origen.
+//! `FormatoDeclarativo` lo convierte en un `BancoFormato`: si el banco cambia el
+//! diseño basta con editar el JSON (o apuntar `salida.disenos` del manifiesto a
+//! otro archivo).
+//!
+//! Variables disponibles en las expresiones:
+//!
+//! | Registro | Variables |
+//! |----------|-----------|
+//! | Todos    | `tipo`, `ciclo`, `fecha` (DDMMAA), `porcentaje`, `banco` (código), `rif` (`salida.rif`, si está) |
+//! | Detalle  | `cedula`, `nacionalidad`, `nombres`, `apellidos`, `nombre`, `numero_cuenta`, `garantias`, `movimientos`, `monto` |
+//! | Cabecera y pie | `cantidad`, `total` |
+
+use crate::banco::formato::{
+    monto_movimientos, texto_banco, validar_cuenta, BancoFormato, ContextoArchivo, LineaDetalle,
+};
+use crate::banco::tipos::{Banco, TipoArchivo};
+use crate::calc::calculos::redondear_dos;
+use crate::kernel::logica::memoria::Beneficiario;
+use crate::util::cedula::Cedula;
+use crate::util::texto::{pad_left, pad_right};
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Diseño del TXT de Nómina Patria (`FiniquitoPatria::to_line_patria`).
+pub const DISENO_PATRIA: &str = include_str!("../../../schema/bancos/patria.json");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alineacion {
+    Izquierda,
+    Derecha,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TipoCampo {
+    #[default]
+    Texto,
+    /// Solo dígitos; un valor que no cabe en el campo es un error, no se trunca.
+    Numerico,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FinLinea {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl FinLinea {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FinLinea::Lf => "\n",
+            FinLinea::Crlf => "\r\n",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampoDiseno {
+    pub nombre: String,
+    /// Columna inicial, desde 1.
+    pub inicio: usize,
+    pub largo: usize,
+    #[serde(default)]
+    pub tipo: TipoCampo,
+    /// Por defecto a la derecha los numéricos y a la izquierda los textos.
+    #[serde(default)]
+    pub alineacion: Option<Alineacion>,
+    /// Por defecto `0` los numéricos y espacio los textos.
+    #[serde(default)]
+    pub relleno: Option<char>,
+    /// Decimales implícitos: el valor se multiplica por 10^escala y se redondea.
+    #[serde(default)]
+    pub escala: Option<u32>,
+    /// Expresión Rhai que produce el valor: `cedula`, `` `V` ``, `monto`, ...
+    /// Vacía en los diseños que solo se leen (respuestas del banco).
+    #[serde(default)]
+    pub origen: String,
+    /// Conserva solo los dígitos del valor (cédulas, cuentas, ciclo).
+    #[serde(default)]
+    pub solo_digitos: bool,
+    /// Texto en mayúsculas y sin acentos.
+    #[serde(default)]
+    pub mayusculas: bool,
+}
+
+impl CampoDiseno {
+    pub fn alineacion(&self) -> Alineacion {
+        self.alineacion.unwrap_or(match self.tipo {
+            TipoCampo::Numerico => Alineacion::Derecha,
+            TipoCampo::Texto => Alineacion::Izquierda,
+        })
+    }
+
+    pub fn relleno(&self) -> char {
+        self.relleno.unwrap_or(match self.tipo {
+            TipoCampo::Numerico => '0',
+            TipoCampo::Texto => ' ',
+        })
+    }
+
+    /// Valor ya formateado del campo a partir del resultado de `origen`.
+    pub fn formatear(&self, valor: Dynamic) -> Result<String, String> {
+        let mut texto = match self.escala {
+            Some(escala) => {
+                let numero = valor
+                    .as_float()
+                    .ok()
+                    .or_else(|| valor.as_int().ok().map(|n| n as f64))
+                    .ok_or_else(|| format!("campo {}: '{}' no es un número", self.nombre, valor))?;
+                escalar(numero, escala)
+            }
+            None => valor.to_string(),
+        };
+        if self.solo_digitos {
+            texto.retain(|c| c.is_ascii_digit());
+        }
+        if self.mayusculas {
+            texto = texto_banco(&texto);
+        }
+        if self.tipo == TipoCampo::Numerico {
+            if !texto.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("campo {}: '{}' no es numérico", self.nombre, texto));
+            }
+            if texto.len() > self.largo {
+                return Err(format!(
+                    "campo {}: '{}' excede {} posiciones",
+                    self.nombre, texto, self.largo
+                ));
+            }
+        }
+        // Los textos largos se truncan conservando el inicio
+        let texto: String = texto.chars().take(self.largo).collect();
+        Ok(match self.alineacion() {
+            Alineacion::Izquierda => pad_right(&texto, self.largo, self.relleno()),
+            Alineacion::Derecha => pad_left(&texto, self.largo, self.relleno()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisenoRegistro {
+    /// Largo total de la línea; si se indica debe coincidir con los campos.
+    #[serde(default)]
+    pub longitud: Option<usize>,
+    pub campos: Vec<CampoDiseno>,
+}
+
+impl DisenoRegistro {
+    /// Verifica que los campos cubran la línea de forma contigua, sin huecos
+    /// ni solapamientos, y devuelve su largo.
+    pub fn validar(&self) -> Result<usize, String> {
+        let mut campos: Vec<&CampoDiseno> = self.campos.iter().collect();
+        campos.sort_by_key(|c| c.inicio);
+
+        let mut siguiente = 1;
+        for c in &campos {
+            if c.largo == 0 {
+                return Err(format!("campo {}: largo 0", c.nombre));
+            }
+            if c.inicio != siguiente {
+                return Err(format!(
+                    "campo {} inicia en la columna {}, se esperaba {}",
+                    c.nombre, c.inicio, siguiente
+                ));
+            }
+            siguiente += c.largo;
+        }
+
+        let largo = siguiente - 1;
+        match self.longitud {
+            Some(longitud) if longitud != largo => Err(format!(
+                "los campos suman {} columnas y la longitud declarada es {}",
+                largo, longitud
+            )),
+            _ if largo == 0 => Err("registro sin campos".to_string()),
+            _ => Ok(largo),
+        }
+    }
+
+    /// Reescribe en `linea` el valor del campo `nombre` (p. ej. la cantidad y el
+    /// total de una cabecera cuando se arma un archivo con parte del detalle).
+    pub fn reemplazar(&self, linea: &str, nombre: &str, valor: Dynamic) -> Result<String, String> {
+        let campo = self
+            .campos
+            .iter()
+            .find(|c| c.nombre == nombre)
+            .ok_or_else(|| format!("sin campo {}", nombre))?;
+        let mut columnas: Vec<char> = linea.chars().collect();
+        if columnas.len() < campo.inicio - 1 + campo.largo {
+            return Err(format!("línea de {} columnas", columnas.len()));
+        }
+        columnas.splice(
+            campo.inicio - 1..campo.inicio - 1 + campo.largo,
+            campo.formatear(valor)?.chars(),
+        );
+        Ok(columnas.into_iter().collect())
+    }
+
+    /// Verifica el largo de una línea ya generada y que sus campos numéricos
+    /// solo contengan dígitos.
+    pub fn validar_linea(&self, linea: &str) -> Result<(), String> {
+        let largo = self.validar()?;
+        let columnas: Vec<char> = linea.chars().collect();
+        if columnas.len() != largo {
+            return Err(format!(
+                "línea de {} columnas, se esperaban {}",
+                columnas.len(),
+                largo
+            ));
+        }
+        for c in self.campos.iter().filter(|c| c.tipo == TipoCampo::Numerico) {
+            let valor: String = columnas[c.inicio - 1..c.inicio - 1 + c.largo]
+                .iter()
+                .collect();
+            if !valor.chars().all(|d| d.is_ascii_digit()) {
+                return Err(format!(
+                    "campo {} (columna {}): '{}' no es numérico",
+                    c.nombre, c.inicio, valor
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Registro con las expresiones de origen compiladas, en orden de columna.
+pub struct RegistroCompilado {
+    longitud: usize,
+    campos: Vec<(CampoDiseno, AST)>,
+}
+
+impl RegistroCompilado {
+    pub fn compilar(engine: &Engine, diseno: &DisenoRegistro) -> Result<Self, String> {
+        let longitud = diseno.validar()?;
+        let mut campos = Vec::with_capacity(diseno.campos.len());
+        for c in &diseno.campos {
+            let ast = engine
+                .compile(&c.origen)
+                .map_err(|e| format!("campo {}: origen '{}': {}", c.nombre, c.origen, e))?;
+            campos.push((c.clone(), ast));
+        }
+        campos.sort_by_key(|(c, _)| c.inicio);
+        Ok(Self { longitud, campos })
+    }
+
+    pub fn longitud(&self) -> usize {
+        self.longitud
+    }
+
+    pub fn renderizar(&self, engine: &Engine, scope: &mut Scope) -> Result<String, String> {
+        let mut linea = String::with_capacity(self.longitud);
+        for (campo, ast) in &self.campos {
+            let valor = engine
+                .eval_ast_with_scope::<Dynamic>(scope, ast)
+                .map_err(|e| format!("campo {}: {}", campo.nombre, e))?;
+            linea.push_str(&campo.formatear(valor)?);
+        }
+        Ok(linea)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisenoArchivo {
+    /// Código del banco (`0105`, ...).
+    pub banco: String,
+    /// Documento del banco que define el diseño (nombre, versión o fecha).
+    #[serde(default)]
+    pub especificacion: Option<String>,
+    /// Nombre del archivo; admite `{tipo}` y `{ciclo}`.
+    pub archivo: String,
+    /// Nombre del archivo por tipo (`aporte`, ...), en lugar de `archivo`.
+    #[serde(default)]
+    pub archivo_por_tipo: HashMap<String, String>,
+    /// Tipo registrado en manifest.json; por defecto el nombre del banco.
+    #[serde(default)]
+    pub tipo_export: Option<String>,
+    /// Tipos de archivo admitidos (`aporte`, `retiro`, ...); vacío admite todos.
+    #[serde(default)]
+    pub tipos: Vec<String>,
+    #[serde(default)]
+    pub fin_linea: FinLinea,
+    /// Expresión del monto de cada beneficiario.
+    pub monto: String,
+    /// Condición para incluir al beneficiario; por defecto `monto > 0`.
+    #[serde(default)]
+    pub incluir: Option<String>,
+    /// Exige cuenta de 20 dígitos con el prefijo del banco.
+    #[serde(default)]
+    pub validar_cuenta: bool,
+    #[serde(default)]
+    pub cabecera: Option<DisenoRegistro>,
+    pub detalle: DisenoRegistro,
+    #[serde(default)]
+    pub pie: Option<DisenoRegistro>,
+}
+
+impl DisenoArchivo {
+    pub fn desde_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("diseño inválido: {}", e))
+    }
+
+    pub fn cargar(ruta: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let contenido = std::fs::read_to_string(ruta)?;
+        Self::desde_json(&contenido).map_err(|e| format!("{}: {}", ruta, e).into())
+    }
+}
+
+/// `BancoFormato` definido por un `DisenoArchivo`.
+pub struct FormatoDeclarativo {
+    diseno: DisenoArchivo,
+    banco: Banco,
+    tipos: Vec<TipoArchivo>,
+    engine: Engine,
+    monto: AST,
+    incluir: Option<AST>,
+    cabecera: Option<RegistroCompilado>,
+    detalle: RegistroCompilado,
+    pie: Option<RegistroCompilado>,
+}
+
+impl FormatoDeclarativo {
+    pub fn new(diseno: DisenoArchivo) -> Result<Self, String> {
+        let banco = Banco::from_codigo(&diseno.banco);
+        if banco == Banco::Desconocido {
+            return Err(format!("banco '{}' desconocido", diseno.banco));
+        }
+        let tipos = diseno
+            .tipos
+            .iter()
+            .map(|t| TipoArchivo::from_str(t).ok_or_else(|| format!("tipo '{}' desconocido", t)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let engine = crate::util::script::motor();
+        let monto = engine
+            .compile(&diseno.monto)
+            .map_err(|e| format!("monto '{}': {}", diseno.monto, e))?;
+        let incluir = diseno
+            .incluir
+            .as_ref()
+            .map(|e| {
+                engine
+                    .compile(e)
+                    .map_err(|err| format!("incluir '{}': {}", e, err))
+            })
+            .transpose()?;
+        let compilar = |registro: &Option<DisenoRegistro>, nombre: &str| {
+            registro
+                .as_ref()
+                .map(|r| RegistroCompilado::compilar(&engine, r))
+                .transpose()
+                .map_err(|e| format!("{}: {}", nombre, e))
+        };
+        let cabecera = compilar(&diseno.cabecera, "cabecera")?;
+        let pie = compilar(&diseno.pie, "pie")?;
+        let detalle = RegistroCompilado::compilar(&engine, &diseno.detalle)
+            .map_err(|e| format!("detalle: {}", e))?;
+
+        Ok(Self {
+            diseno,
+            banco,
+            tipos,
+            engine,
+            monto,
+            incluir,
+            cabecera,
+            detalle,
+            pie,
+        })
+    }
+
+    pub fn desde_json(json: &str) -> Result<Self, String> {
+        Self::new(DisenoArchivo::desde_json(json)?)
+    }
+
+    pub fn cargar(ruta: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::new(DisenoArchivo::cargar(ruta)?).map_err(|e| format!("{}: {}", ruta, e).into())
+    }
+
+    pub fn diseno(&self) -> &DisenoArchivo {
+        &self.diseno
+    }
+
+    fn alcance(&self, ctx: &ContextoArchivo) -> Scope<'static> {
+        let mut scope = Scope::new();
+        scope.push("tipo", ctx.tipo.as_str().to_string());
+        scope.push("ciclo", ctx.ciclo.clone());
+        scope.push("fecha", ctx.fecha.clone());
+        scope.push("porcentaje", ctx.porcentaje);
+        scope.push("banco", self.banco.codigo().to_string());
+        // Sin `salida.rif` la variable no existe y el diseño que la usa falla
+        if let Some(rif) = &ctx.rif {
+            scope.push("rif", rif.clone());
+        }
+        scope
+    }
+
+    /// Error si la cédula no es válida: sin nacionalidad cierta la línea no se escribe.
+    fn alcance_beneficiario(
+        &self,
+        b: &Beneficiario,
+        ctx: &ContextoArchivo,
+    ) -> Result<Scope<'static>, String> {
+        let nacionalidad = Cedula::parse(&b.cedula)?.nacionalidad;
+        let mut scope = self.alcance(ctx);
+        scope.push("cedula", b.cedula.clone());
+        scope.push("nacionalidad", nacionalidad.as_str().to_string());
+        scope.push("nombres", b.nombres.clone());
+        scope.push("apellidos", b.apellidos.clone());
+        scope.push("nombre", format!("{} {}", b.nombres, b.apellidos));
+        scope.push("numero_cuenta", b.numero_cuenta.clone());
+        scope.push("garantias", b.base.garantias);
+        scope.push("movimientos", monto_movimientos(b));
+        Ok(scope)
+    }
+
+    fn monto(&self, scope: &mut Scope) -> Result<f64, String> {
+        let valor = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(scope, &self.monto)
+            .map_err(|e| format!("monto: {}", e))?;
+        valor
+            .as_float()
+            .ok()
+            .or_else(|| valor.as_int().ok().map(|n| n as f64))
+            .ok_or_else(|| format!("monto: '{}' no es un número", valor))
+    }
+
+    fn linea_detalle(
+        &self,
+        b: &Beneficiario,
+        ctx: &ContextoArchivo,
+    ) -> Result<Option<LineaDetalle>, String> {
+        let mut scope = self.alcance_beneficiario(b, ctx)?;
+        // En céntimos, como se escribe: la línea y los totales usan el mismo monto
+        let monto = redondear_dos(self.monto(&mut scope)?);
+        scope.push("monto", monto);
+        let incluido = match &self.incluir {
+            Some(ast) => self
+                .engine
+                .eval_ast_with_scope::<bool>(&mut scope, ast)
+                .map_err(|e| format!("incluir: {}", e))?,
+            None => monto > 0.0,
+        };
+        if !incluido {
+            return Ok(None);
+        }
+        let texto = self.detalle.renderizar(&self.engine, &mut scope)?;
+        Ok(Some(LineaDetalle { texto, monto }))
+    }
+
+    /// Cabecera o pie con los totales de control. Solo dependen del diseño y
+    /// los totales: un error aquí es un diseño mal escrito y anula el archivo.
+    fn linea_control(
+        &self,
+        registro: &Option<RegistroCompilado>,
+        ctx: &ContextoArchivo,
+        cantidad: usize,
+        total: f64,
+    ) -> Result<Option<String>, String> {
+        let Some(registro) = registro.as_ref() else {
+            return Ok(None);
+        };
+        let mut scope = self.alcance(ctx);
+        scope.push("cantidad", cantidad as i64);
+        scope.push("total", total);
+        registro.renderizar(&self.engine, &mut scope).map(Some)
+    }
+}
+
+impl BancoFormato for FormatoDeclarativo {
+    fn banco(&self) -> Banco {
+        self.banco
+    }
+
+    fn nombre_archivo(&self, ctx: &ContextoArchivo) -> String {
+        self.diseno
+            .archivo_por_tipo
+            .get(ctx.tipo.as_str())
+            .unwrap_or(&self.diseno.archivo)
+            .replace("{tipo}", ctx.tipo.as_str())
+            .replace("{ciclo}", &ctx.ciclo)
+    }
+
+    fn tipo_export(&self, ctx: &ContextoArchivo) -> String {
+        match &self.diseno.tipo_export {
+            Some(tipo) => tipo.clone(),
+            None => self.banco.nombre().to_lowercase().replace(' ', "_"),
+        }
+        .replace("{tipo}", ctx.tipo.as_str())
+    }
+
+    fn detalle(
+        &self,
+        b: &Beneficiario,
+        ctx: &ContextoArchivo,
+    ) -> Result<Option<LineaDetalle>, String> {
+        let linea = self.linea_detalle(b, ctx)?;
+        if linea.is_some() && self.diseno.validar_cuenta {
+            validar_cuenta(&b.numero_cuenta, self.banco)?;
+        }
+        Ok(linea)
+    }
+
+    fn cabecera(
+        &self,
+        ctx: &ContextoArchivo,
+        cantidad: usize,
+        total: f64,
+    ) -> Result<Option<String>, String> {
+        self.linea_control(&self.cabecera, ctx, cantidad, total)
+            .map_err(|e| format!("cabecera: {}", e))
+    }
+
+    fn pie(
+        &self,
+        ctx: &ContextoArchivo,
+        cantidad: usize,
+        total: f64,
+    ) -> Result<Option<String>, String> {
+        self.linea_control(&self.pie, ctx, cantidad, total)
+            .map_err(|e| format!("pie: {}", e))
+    }
+
+    fn fin_linea(&self) -> &'static str {
+        self.diseno.fin_linea.as_str()
+    }
+
+    fn soporta(&self, tipo: TipoArchivo) -> bool {
+        self.tipos.is_empty() || self.tipos.contains(&tipo)
+    }
+}
+
+/// Monto sin separador decimal con `escala` decimales implícitos (2 = céntimos).
+pub fn escalar(valor: f64, escala: u32) -> String {
+    (valor * 10f64.powi(escala as i32)).round().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISENO: &str = r#"{
+        "banco": "0105",
+        "archivo": "prueba_{tipo}_{ciclo}.txt",
+        "tipos": ["aporte"],
+        "monto": "garantias * porcentaje / 100.0",
+        "cabecera": { "campos": [
+            { "nombre": "registro", "inicio": 1, "largo": 1, "origen": "`H`" },
+            { "nombre": "cantidad", "inicio": 2, "largo": 4, "tipo": "numerico", "origen": "cantidad" },
+            { "nombre": "total", "inicio": 6, "largo": 10, "tipo": "numerico", "escala": 2, "origen": "total" }
+        ]},
+        "detalle": { "longitud": 26, "campos": [
+            { "nombre": "cedula", "inicio": 1, "largo": 8, "tipo": "numerico", "solo_digitos": true, "origen": "cedula" },
+            { "nombre": "monto", "inicio": 9, "largo": 8, "tipo": "numerico", "escala": 2, "origen": "monto" },
+            { "nombre": "nombre", "inicio": 17, "largo": 10, "mayusculas": true, "origen": "nombre" }
+        ]}
+    }"#;
+
+    #[test]
+    fn test_diseno_declarativo() {
+        let formato = FormatoDeclarativo::desde_json(DISENO).unwrap();
+        let mut ctx = ContextoArchivo::new(TipoArchivo::Aporte, "2026-02", 50.0);
+        ctx.fecha = "280226".to_string();
+        assert_eq!(formato.nombre_archivo(&ctx), "prueba_aporte_2026-02.txt");
+        assert!(!formato.soporta(TipoArchivo::Retiro));
+
+        let mut b: Beneficiario = serde_json::from_str(
+            r#"{"cedula":"V-1234567","nombres":"María José","apellidos":"Núñez"}"#,
+        )
+        .unwrap();
+        b.base.garantias = 201.0;
+        let linea = formato.detalle(&b, &ctx).unwrap().unwrap();
+        assert_eq!(linea.texto, "0123456700010050MARIA JOSE");
+        assert_eq!(linea.monto, 100.5);
+        assert!(formato.diseno().detalle.validar_linea(&linea.texto).is_ok());
+        assert!(formato
+            .diseno()
+            .detalle
+            .validar_linea("01234567000100X0MARIA JOSE")
+            .is_err());
+        assert_eq!(
+            formato.cabecera(&ctx, 1, 100.5).unwrap().unwrap(),
+            "H00010000010050"
+        );
+        // Cabecera que no cabe: error, no una línea en blanco
+        assert!(formato
+            .cabecera(&ctx, 10_000, 100.5)
+            .unwrap_err()
+            .contains("excede"));
+
+        // Monto que no cabe en el campo: rechazado, no truncado.
+        b.base.garantias = 2_000_000.0;
+        assert!(formato.detalle(&b, &ctx).unwrap_err().contains("excede"));
+
+        // Nómina Patria: cuenta rellena a la derecha, nombre sin normalizar.
+        let fq: crate::kernel::logica::memoria::FiniquitoPatria = serde_json::from_str(
+            r#"{"cedula":"E81234","apellidos":"Peña Ana","numero_cuenta":"0102123","monto":12.5,"f_contable":"","observaciones":""}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            fq.to_line_patria().unwrap(),
+            format!("E00081234{:0<20}00000001250{:<40}", "0102123", "Peña Ana")
+        );
+
+        // Campos que se solapan.
+        let solapado = DISENO.replace(r#""inicio": 9"#, r#""inicio": 8"#);
+        assert!(FormatoDeclarativo::desde_json(&solapado).is_err());
+    }
+
+    #[test]
+    fn test_cedula_invalida_rechaza_la_linea() {
+        let formato = FormatoDeclarativo::desde_json(DISENO).unwrap();
+        let ctx = ContextoArchivo::new(TipoArchivo::Aporte, "2026-02", 100.0);
+        let mut b: Beneficiario =
+            serde_json::from_str(r#"{"cedula":"X-1234567","nombres":"Ana"}"#).unwrap();
+        b.base.garantias = 10.0;
+        // Sin letra cierta no se escribe con `V` supuesta: el enrutamiento lo informa
+        assert_eq!(
+            formato.detalle(&b, &ctx).unwrap_err(),
+            "cédula X-1234567: nacionalidad 'X' inválida"
+        );
+
+        let fq: crate::kernel::logica::memoria::FiniquitoPatria = serde_json::from_str(
+            r#"{"cedula":"X81234","apellidos":"Peña","numero_cuenta":"0102","monto":1.0,"f_contable":"","observaciones":""}"#,
+        )
+        .unwrap();
+        assert!(fq.to_line_patria().unwrap_err().contains("nacionalidad 'X'"));
+    }
+}
@@ -0,0 +1,288 @@
+//! Enrutamiento de beneficiarios al banco de su cuenta.
+//!
+//! Cada beneficiario va al banco de los cuatro primeros dígitos de su
+//! `numero_cuenta` (`Banco::from_codigo`). Los que no pueden enviarse a ningún
+//! archivo (sin cuenta, cuenta mal formada o con dígitos de control inválidos,
+//! banco desconocido, sin formato o
+//! fuera de `salida.bancos`) quedan en `no_enrutados_<ciclo>.csv` con el motivo,
+//! igual que los que el formato del banco rechaza al armar su línea o a los que
+//! no les escribe línea teniendo monto en la nómina. El cuadre compara el total
+//! de la nómina con lo que las líneas de cada banco escriben más ese reporte.
+
+use crate::banco::cuenta::validar_ccc;
+use crate::banco::formato::{monto_movimientos, ContextoArchivo, RegistroBancos, Renglon};
+use crate::banco::tipos::{Banco, TipoArchivo};
+use crate::calc::calculos::redondear_dos;
+use crate::kernel::logica::exportador::{generar_hash, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::kernel::logica::memoria::Beneficiario;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Beneficiario que no se pudo asignar a un archivo bancario.
+#[derive(Debug, Clone, Serialize)]
+pub struct NoEnrutado {
+    pub cedula: String,
+    pub nombre: String,
+    pub numero_cuenta: String,
+    pub monto: f64,
+    pub motivo: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Enrutamiento<'a> {
+    /// Beneficiarios por banco con su línea ya evaluada, en el orden de
+    /// `salida.bancos`. No incluye a los que el formato rechazó.
+    pub por_banco: Vec<(Banco, Vec<Renglon<'a>>)>,
+    pub no_enrutados: Vec<NoEnrutado>,
+    /// Suma de `monto_nomina` de todos los beneficiarios.
+    pub total: f64,
+}
+
+/// Monto de referencia del beneficiario para el cuadre: garantías en aporte,
+/// movimientos en retiro o mixto, nada en apertura; por el porcentaje del archivo.
+pub fn monto_nomina(b: &Beneficiario, ctx: &ContextoArchivo) -> f64 {
+    let monto = match ctx.tipo {
+        TipoArchivo::Aporte => b.base.garantias,
+        TipoArchivo::Retiro | TipoArchivo::Mixto => monto_movimientos(b),
+        TipoArchivo::Apertura => 0.0,
+    };
+    monto * ctx.porcentaje / 100.0
+}
+
+/// Banco de la cuenta, o el motivo por el que no se puede determinar.
+pub fn banco_de_cuenta(cuenta: &str) -> Result<Banco, String> {
+    let cuenta = cuenta.trim();
+    validar_ccc(cuenta)?;
+    match Banco::from_codigo(&cuenta[0..4]) {
+        Banco::Desconocido => Err(format!("código de banco {} desconocido", &cuenta[0..4])),
+        banco => Ok(banco),
+    }
+}
+
+/// Agrupa los beneficiarios por el banco de su cuenta entre los `bancos` pedidos.
+pub fn enrutar<'a>(
+    beneficiarios: &'a [Beneficiario],
+    registro: &RegistroBancos,
+    bancos: &[String],
+    ctx: &ContextoArchivo,
+) -> Enrutamiento<'a> {
+    let mut enrutamiento = Enrutamiento {
+        por_banco: bancos
+            .iter()
+            .map(|c| Banco::from_codigo(c))
+            .filter(|b| *b != Banco::Desconocido && registro.obtener(*b).is_some())
+            .map(|b| (b, Vec::new()))
+            .collect(),
+        ..Default::default()
+    };
+
+    for b in beneficiarios {
+        let monto = redondear_dos(monto_nomina(b, ctx));
+        enrutamiento.total = redondear_dos(enrutamiento.total + monto);
+
+        let destino =
+            banco_de_cuenta(&b.numero_cuenta).and_then(|banco| match registro.obtener(banco) {
+                None => Err(format!("{} sin formato de archivo", banco.nombre())),
+                Some(formato) if !formato.soporta(ctx.tipo) => Err(format!(
+                    "{} no admite archivos de {}",
+                    banco.nombre(),
+                    ctx.tipo.as_str()
+                )),
+                Some(_) => enrutamiento
+                    .por_banco
+                    .iter()
+                    .position(|(x, _)| *x == banco)
+                    .ok_or_else(|| {
+                        format!(
+                            "{} ({}) no está en salida.bancos",
+                            banco.nombre(),
+                            banco.codigo()
+                        )
+                    }),
+            });
+
+        // La línea se evalúa aquí una vez: la usan el archivo y el cuadre
+        let destino = destino.and_then(|i| {
+            let banco = enrutamiento.por_banco[i].0;
+            let formato = registro.obtener(banco).expect("banco con formato");
+            let linea = formato
+                .detalle(b, ctx)
+                .map_err(|motivo| format!("rechazado por {}: {}", banco.nombre(), motivo))?;
+            // Sin línea y con monto: saldría de la nómina sin que nadie lo pague
+            if linea.is_none() && monto > 0.0 {
+                return Err(format!(
+                    "{} no escribe línea de {} para el monto de la nómina",
+                    banco.nombre(),
+                    ctx.tipo.as_str()
+                ));
+            }
+            Ok((i, linea))
+        });
+
+        match destino {
+            Ok((i, linea)) => enrutamiento.por_banco[i].1.push(Renglon {
+                beneficiario: b,
+                linea: Ok(linea),
+            }),
+            Err(motivo) => enrutamiento.no_enrutados.push(NoEnrutado {
+                cedula: b.cedula.clone(),
+                nombre: format!("{} {}", b.nombres, b.apellidos).trim().to_string(),
+                numero_cuenta: b.numero_cuenta.clone(),
+                monto,
+                motivo,
+            }),
+        }
+    }
+
+    enrutamiento
+}
+
+impl Enrutamiento<'_> {
+    /// Renglones de un banco (vacío si no está en el enrutamiento).
+    pub fn renglones(&self, banco: Banco) -> &[Renglon<'_>] {
+        self.por_banco
+            .iter()
+            .find(|(b, _)| *b == banco)
+            .map(|(_, renglones)| renglones.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Líneas y monto que escribe el archivo del banco.
+    pub fn lineas_banco(&self, banco: Banco) -> (usize, f64) {
+        self.renglones(banco)
+            .iter()
+            .filter_map(|r| r.linea.as_ref().ok().and_then(|l| l.as_ref()))
+            .fold((0, 0.0), |(n, total), l| {
+                (n + 1, redondear_dos(total + l.monto))
+            })
+    }
+
+    pub fn total_no_enrutado(&self) -> f64 {
+        redondear_dos(self.no_enrutados.iter().map(|n| n.monto).sum())
+    }
+
+    /// Total de la nómina menos lo escrito en los bancos y lo no enrutado. Distinto
+    /// de cero si un formato escribe en la línea otro monto que el de la nómina.
+    pub fn diferencia(&self) -> f64 {
+        let bancos: f64 = self
+            .por_banco
+            .iter()
+            .map(|(banco, _)| self.lineas_banco(*banco).1)
+            .sum();
+        redondear_dos(self.total - bancos - self.total_no_enrutado())
+    }
+
+    /// Escribe `no_enrutados_<ciclo>.csv` (solo encabezado si todos se enrutaron).
+    pub fn exportar(
+        &self,
+        ciclo: &str,
+        destino: &str,
+    ) -> Result<ResultadoExport, Box<dyn std::error::Error>> {
+        let nombre = format!("no_enrutados_{}.csv", ciclo);
+        let ruta = if destino == "." || destino.is_empty() {
+            PathBuf::from(&nombre)
+        } else {
+            PathBuf::from(destino).join(&nombre)
+        };
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .has_headers(false)
+            .from_writer(vec![]);
+        wtr.write_record(["cedula", "nombre", "numero_cuenta", "monto", "motivo"])?;
+        for n in &self.no_enrutados {
+            wtr.serialize(n)?;
+        }
+        let datos = wtr.into_inner()?;
+        std::fs::write(&ruta, &datos)?;
+        let hash = generar_hash(&datos);
+
+        if !self.no_enrutados.is_empty() {
+            logger::log_warn(
+                "BANCO",
+                &format!(
+                    "{} beneficiarios sin banco de destino ({:.2})",
+                    self.no_enrutados.len(),
+                    self.total_no_enrutado()
+                ),
+            );
+        }
+
+        Ok(ResultadoExport {
+            ruta: ruta.display().to_string(),
+            tipo: "no_enrutados".to_string(),
+            tamano_original: datos.len() as u64,
+            tamano_comprimido: None,
+            hash_sha256: Some(hash),
+            hash_sha256_original: None,
+            compresion_aplicada: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::banco::cuenta::digitos_control;
+
+    /// Cuenta con dígitos de control válidos.
+    fn ccc(banco: &str, numero: &str) -> String {
+        let cuenta = format!("{}0001{}", banco, numero);
+        let dc = digitos_control(&format!("{}00{}", &cuenta[..8], numero)).unwrap();
+        format!("{}{}{}", &cuenta[..8], dc, numero)
+    }
+
+    fn ben(cedula: &str, cuenta: &str, garantias: f64) -> Beneficiario {
+        let mut b: Beneficiario = serde_json::from_str(&format!(
+            r#"{{"cedula":"{}","numero_cuenta":"{}"}}"#,
+            cedula, cuenta
+        ))
+        .unwrap();
+        b.base.garantias = garantias;
+        b
+    }
+
+    #[test]
+    fn test_enrutar_por_prefijo_de_cuenta() {
+        let beneficiarios = vec![
+            ben("1", &ccc("0177", "0000000001"), 100.0),
+            ben("2", &ccc("0102", "0000000002"), 50.0),
+            ben("3", &ccc("0177", "0000000003"), 25.0),
+            ben("4", "", 10.0),
+            ben("5", "0177-123", 5.0),
+            ben("6", &ccc("0999", "0000000006"), 2.5),
+            ben("7", &ccc("0105", "0000000007"), 1.0),
+            ben("8", "01770001990000000008", 0.5),
+        ];
+        let registro = RegistroBancos::por_defecto();
+        let ctx = ContextoArchivo::new(TipoArchivo::Aporte, "2026-02", 100.0);
+        let bancos = vec!["0177".to_string(), "0102".to_string()];
+        let e = enrutar(&beneficiarios, &registro, &bancos, &ctx);
+
+        assert_eq!(e.por_banco.len(), 2);
+        assert_eq!(e.por_banco[0].0, Banco::Banfanb);
+        assert_eq!(e.por_banco[0].1.len(), 2);
+
+        let motivos: Vec<&str> = e.no_enrutados.iter().map(|n| n.motivo.as_str()).collect();
+        assert_eq!(
+            motivos,
+            vec![
+                "Banco de Venezuela no escribe línea de aporte para el monto de la nómina",
+                "sin numero_cuenta",
+                "cuenta 0177-123 no tiene 20 dígitos",
+                "código de banco 0999 desconocido",
+                "Mercantil (0105) no está en salida.bancos",
+                "cuenta 01770001990000000008: dígitos de control 99, se esperaba 40",
+            ]
+        );
+
+        assert_eq!(e.lineas_banco(Banco::Banfanb), (2, 125.0));
+        assert_eq!(e.total, 194.0);
+        // El APORT de Venezuela paga movimientos: sin ellos no escribe la línea
+        // de 50.00 y el beneficiario queda en no enrutados con su monto
+        assert!(e.renglones(Banco::Venezuela).is_empty());
+        assert_eq!(e.total_no_enrutado(), 69.0);
+        assert_eq!(e.diferencia(), 0.0);
+    }
+}
@@ -0,0 +1,683 @@
+//! Formato de archivo TXT por banco y registro de formatos disponibles.
+//!
+//! Cada banco implementa `BancoFormato` (nombre del archivo, cabecera, línea de
+//! detalle según `TipoArchivo`, pie y validación por beneficiario) y se registra
+//! en `RegistroBancos` con su `Banco`. `generar_archivo` se encarga de escribir,
+//! comprimir y firmar el archivo igual para todos los bancos.
+
+use crate::banco::cuenta::validar_ccc;
+use crate::banco::tipos::{Banco, TipoArchivo};
+use crate::calc::calculos::redondear_dos;
+use crate::kernel::logica::exportador::{comprimir_y_sellar, generar_hash, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::kernel::logica::memoria::Beneficiario;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Datos del archivo que se está generando, comunes a todas las líneas.
+#[derive(Debug, Clone)]
+pub struct ContextoArchivo {
+    pub tipo: TipoArchivo,
+    pub ciclo: String,
+    /// Porcentaje del monto a pagar (100 = completo).
+    pub porcentaje: f64,
+    /// Fecha de generación (`DDMMAA`), usada en cabeceras.
+    pub fecha: String,
+    /// Número de parte cuando el archivo se divide por límites (`_001`, `_002`).
+    pub parte: Option<usize>,
+    /// RIF de la empresa ordenante (`salida.rif`), para las cabeceras que lo llevan.
+    pub rif: Option<String>,
+}
+
+impl ContextoArchivo {
+    pub fn new(tipo: TipoArchivo, ciclo: &str, porcentaje: f64) -> Self {
+        Self {
+            tipo,
+            ciclo: ciclo.to_string(),
+            porcentaje,
+            fecha: Local::now().format("%d%m%y").to_string(),
+            parte: None,
+            rif: None,
+        }
+    }
+}
+
+/// Línea de detalle de un beneficiario y el monto escrito en ella (en céntimos).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineaDetalle {
+    pub texto: String,
+    pub monto: f64,
+}
+
+pub trait BancoFormato: Send + Sync {
+    fn banco(&self) -> Banco;
+
+    /// Nombre del archivo (sin carpeta) para el tipo y ciclo del contexto.
+    fn nombre_archivo(&self, ctx: &ContextoArchivo) -> String;
+
+    /// Tipo registrado en el `ResultadoExport` (y en manifest.json).
+    fn tipo_export(&self, _ctx: &ContextoArchivo) -> String {
+        self.banco().nombre().to_lowercase().replace(' ', "_")
+    }
+
+    /// Línea del beneficiario: `None` si no corresponde a este tipo de archivo
+    /// y error con el motivo si el banco no lo acepta.
+    fn detalle(
+        &self,
+        b: &Beneficiario,
+        ctx: &ContextoArchivo,
+    ) -> Result<Option<LineaDetalle>, String>;
+
+    /// Cabecera con los totales de control; un error anula el archivo.
+    fn cabecera(
+        &self,
+        _ctx: &ContextoArchivo,
+        _cantidad: usize,
+        _total: f64,
+    ) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    /// Pie con los totales de control; un error anula el archivo.
+    fn pie(
+        &self,
+        _ctx: &ContextoArchivo,
+        _cantidad: usize,
+        _total: f64,
+    ) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    /// Terminador de cada línea escrita.
+    fn fin_linea(&self) -> &'static str {
+        "\n"
+    }
+
+    /// Tipos de archivo que el banco acepta.
+    fn soporta(&self, _tipo: TipoArchivo) -> bool {
+        true
+    }
+}
+
+/// Monto de movimientos (finiquito, anticipo, días adicionales y garantías) del beneficiario.
+pub fn monto_movimientos(b: &Beneficiario) -> f64 {
+    let m = &b.movimientos;
+    m.finiquito_capital_banco
+        + m.anticipo
+        + m.deposito_de_dias_adicionales
+        + m.deposito_de_garantias
+}
+
+/// Texto en mayúsculas, sin acentos ni caracteres fuera de ASCII.
+pub fn texto_banco(valor: &str) -> String {
+    valor
+        .trim()
+        .to_uppercase()
+        .chars()
+        .map(|c| match c {
+            'Á' | 'À' | 'Ä' => 'A',
+            'É' | 'È' | 'Ë' => 'E',
+            'Í' | 'Ì' | 'Ï' => 'I',
+            'Ó' | 'Ò' | 'Ö' => 'O',
+            'Ú' | 'Ù' | 'Ü' => 'U',
+            'Ñ' => 'N',
+            c if c.is_ascii() => c,
+            _ => ' ',
+        })
+        .collect()
+}
+
+/// Cuenta de 20 dígitos con dígitos de control válidos (`cuenta::validar_ccc`)
+/// que comienza por el código del banco.
+pub fn validar_cuenta(cuenta: &str, banco: Banco) -> Result<(), String> {
+    validar_ccc(cuenta)?;
+    let cuenta = cuenta.trim();
+    if !cuenta.starts_with(banco.codigo()) {
+        return Err(format!(
+            "cuenta {} no pertenece a {}",
+            cuenta,
+            banco.nombre()
+        ));
+    }
+    Ok(())
+}
+
+/// Límites de un archivo que acepta el banco (`salida.limites` del manifiesto).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LimiteArchivo {
+    /// Máximo de líneas de detalle por archivo.
+    #[serde(default)]
+    pub max_registros: Option<usize>,
+    /// Máximo del total de control por archivo.
+    #[serde(default)]
+    pub max_total: Option<f64>,
+}
+
+impl LimiteArchivo {
+    pub fn sin_limite(&self) -> bool {
+        self.max_registros.is_none() && self.max_total.is_none()
+    }
+}
+
+/// Beneficiario con su línea de detalle, evaluada una sola vez por archivo.
+#[derive(Debug, Clone)]
+pub struct Renglon<'a> {
+    pub beneficiario: &'a Beneficiario,
+    /// `None` si no va en el archivo; error con el motivo si el banco lo rechaza.
+    pub linea: Result<Option<LineaDetalle>, String>,
+}
+
+/// Evalúa la línea de detalle de cada beneficiario.
+pub fn renglones<'a, B: Borrow<Beneficiario>>(
+    formato: &dyn BancoFormato,
+    beneficiarios: &'a [B],
+    ctx: &ContextoArchivo,
+) -> Vec<Renglon<'a>> {
+    beneficiarios
+        .iter()
+        .map(|b| {
+            let b = b.borrow();
+            Renglon {
+                beneficiario: b,
+                linea: formato.detalle(b, ctx),
+            }
+        })
+        .collect()
+}
+
+/// Reparte los renglones en partes que no superan el límite, en orden. Una
+/// línea que por sí sola supera `max_total` va en una parte propia; los que no
+/// generan línea quedan en la parte en curso.
+pub fn dividir<'r, 'a>(
+    renglones: &'r [Renglon<'a>],
+    limite: &LimiteArchivo,
+) -> Vec<&'r [Renglon<'a>]> {
+    let mut partes = Vec::new();
+    let mut inicio = 0;
+    let mut cantidad = 0;
+    let mut total = 0.0;
+
+    for (i, renglon) in renglones.iter().enumerate() {
+        let Ok(Some(linea)) = &renglon.linea else {
+            continue;
+        };
+        let excede_registros = limite.max_registros.is_some_and(|m| cantidad + 1 > m);
+        let excede_total = limite
+            .max_total
+            .is_some_and(|m| total + linea.monto > m + 0.005);
+        if cantidad > 0 && (excede_registros || excede_total) {
+            partes.push(&renglones[inicio..i]);
+            inicio = i;
+            cantidad = 0;
+            total = 0.0;
+        }
+        cantidad += 1;
+        total += linea.monto;
+    }
+    partes.push(&renglones[inicio..]);
+    partes
+}
+
+/// Nombre del archivo con el número de parte antes de la extensión.
+pub fn nombre_parte(nombre: &str, parte: Option<usize>) -> String {
+    let Some(parte) = parte else {
+        return nombre.to_string();
+    };
+    match nombre.rfind('.') {
+        Some(punto) => format!("{}_{:03}{}", &nombre[..punto], parte, &nombre[punto..]),
+        None => format!("{}_{:03}", nombre, parte),
+    }
+}
+
+/// Resultado de generar el archivo de un banco.
+#[derive(Debug, Clone)]
+pub struct ArchivoBanco {
+    pub export: ResultadoExport,
+    pub registros: usize,
+    pub total: f64,
+    /// Beneficiarios rechazados por `BancoFormato::validar` (cédula, motivo).
+    pub rechazados: Vec<(String, String)>,
+}
+
+/// Texto del archivo, cantidad y total de control, y rechazados (cédula, motivo).
+pub type Contenido = (String, usize, f64, Vec<(String, String)>);
+
+/// Arma el contenido del archivo: cabecera, detalle y pie.
+pub fn armar_contenido<B: Borrow<Beneficiario>>(
+    formato: &dyn BancoFormato,
+    beneficiarios: &[B],
+    ctx: &ContextoArchivo,
+) -> Result<Contenido, String> {
+    componer(formato, &renglones(formato, beneficiarios, ctx), ctx)
+}
+
+/// Contenido del archivo a partir de renglones ya evaluados.
+fn componer(
+    formato: &dyn BancoFormato,
+    renglones: &[Renglon],
+    ctx: &ContextoArchivo,
+) -> Result<Contenido, String> {
+    let fin = formato.fin_linea();
+    let mut detalle = String::new();
+    let mut cantidad = 0;
+    let mut total = 0.0;
+    let mut rechazados = Vec::new();
+
+    for renglon in renglones {
+        let linea = match &renglon.linea {
+            Ok(Some(linea)) => linea,
+            Ok(None) => continue,
+            Err(motivo) => {
+                rechazados.push((renglon.beneficiario.cedula.clone(), motivo.clone()));
+                continue;
+            }
+        };
+        detalle.push_str(&linea.texto);
+        detalle.push_str(fin);
+        cantidad += 1;
+        // Total de control: suma de los montos tal como quedaron en las líneas
+        total = redondear_dos(total + linea.monto);
+    }
+
+    let mut contenido = String::new();
+    if let Some(cabecera) = formato.cabecera(ctx, cantidad, total)? {
+        contenido.push_str(&cabecera);
+        contenido.push_str(fin);
+    }
+    contenido.push_str(&detalle);
+    if let Some(pie) = formato.pie(ctx, cantidad, total)? {
+        contenido.push_str(&pie);
+        contenido.push_str(fin);
+    }
+
+    Ok((contenido, cantidad, total, rechazados))
+}
+
+/// Escribe el archivo del banco en `destino`, opcionalmente comprimido con zstd.
+pub fn generar_archivo<B: Borrow<Beneficiario>>(
+    formato: &dyn BancoFormato,
+    beneficiarios: &[B],
+    ctx: &ContextoArchivo,
+    destino: &str,
+    comprimir: bool,
+    nivel_compresion: i32,
+) -> Result<ArchivoBanco, Box<dyn std::error::Error>> {
+    escribir_archivo(
+        formato,
+        &renglones(formato, beneficiarios, ctx),
+        ctx,
+        destino,
+        comprimir,
+        nivel_compresion,
+    )
+}
+
+/// Escribe el archivo con renglones ya evaluados (ver `generar_archivos`).
+fn escribir_archivo(
+    formato: &dyn BancoFormato,
+    renglones: &[Renglon],
+    ctx: &ContextoArchivo,
+    destino: &str,
+    comprimir: bool,
+    nivel_compresion: i32,
+) -> Result<ArchivoBanco, Box<dyn std::error::Error>> {
+    if !formato.soporta(ctx.tipo) {
+        return Err(format!(
+            "{} no admite archivos de {}",
+            formato.banco().nombre(),
+            ctx.tipo.as_str()
+        )
+        .into());
+    }
+
+    let nombre_archivo = nombre_parte(&formato.nombre_archivo(ctx), ctx.parte);
+    let ruta = if destino == "." || destino.is_empty() {
+        PathBuf::from(&nombre_archivo)
+    } else {
+        PathBuf::from(destino).join(&nombre_archivo)
+    };
+    let banco = formato.banco().nombre();
+
+    println!(
+        "> Generando TXT {} ({}) en '{}' ({} registros, {}% monto)...",
+        banco,
+        ctx.tipo.as_str(),
+        ruta.display(),
+        renglones.len(),
+        ctx.porcentaje
+    );
+
+    let (contenido, cantidad, total, rechazados) =
+        componer(formato, renglones, ctx).map_err(|e| format!("{}: {}", banco, e))?;
+    if !rechazados.is_empty() {
+        logger::log_warn(
+            "BANCO",
+            &format!(
+                "{}: {} beneficiarios rechazados por validación del formato",
+                banco,
+                rechazados.len()
+            ),
+        );
+    }
+
+    let datos = contenido.into_bytes();
+    let tamano_original = datos.len() as u64;
+    let hash_original = generar_hash(&datos);
+    let tipo_export = formato.tipo_export(ctx);
+
+    let export = if comprimir {
+        println!(
+            "    > Comprimiendo archivo {} con zstd (nivel {})...",
+            banco, nivel_compresion
+        );
+
+        let (comprimido, hash) = comprimir_y_sellar(&datos, nivel_compresion);
+        let ruta_zst = ruta.with_extension("txt.zst");
+        let mut archivo_zst = File::create(&ruta_zst)?;
+        archivo_zst.write_all(&comprimido)?;
+
+        let tamano_comprimido = comprimido.len() as u64;
+        let nombre_final = ruta_zst
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        println!(
+            "    > {} generado: Original: {} bytes, Comprimido: {} bytes, Hash: {}",
+            banco,
+            tamano_original,
+            tamano_comprimido,
+            &hash[..16]
+        );
+
+        ResultadoExport {
+            ruta: nombre_final,
+            tipo: tipo_export,
+            tamano_original,
+            tamano_comprimido: Some(tamano_comprimido),
+            hash_sha256: Some(hash),
+            hash_sha256_original: Some(hash_original),
+            compresion_aplicada: true,
+        }
+    } else {
+        let mut archivo = File::create(&ruta)?;
+        archivo.write_all(&datos)?;
+        archivo.flush()?;
+        archivo.sync_all()?;
+
+        println!(
+            "    > {} generado: {} bytes, {} registros, Total: {:.2}, Hash: {}",
+            banco,
+            tamano_original,
+            cantidad,
+            total,
+            &hash_original[..16]
+        );
+
+        ResultadoExport {
+            ruta: ruta.display().to_string(),
+            tipo: tipo_export,
+            tamano_original,
+            tamano_comprimido: None,
+            hash_sha256: Some(hash_original),
+            hash_sha256_original: None,
+            compresion_aplicada: false,
+        }
+    };
+
+    Ok(ArchivoBanco {
+        export,
+        registros: cantidad,
+        total,
+        rechazados,
+    })
+}
+
+/// Como `generar_archivo`, pero divide la salida en `_001`, `_002`, ... cuando
+/// supera el límite del banco; cada parte lleva su cabecera y totales de control.
+pub fn generar_archivos<B: Borrow<Beneficiario>>(
+    formato: &dyn BancoFormato,
+    beneficiarios: &[B],
+    ctx: &ContextoArchivo,
+    destino: &str,
+    comprimir: bool,
+    nivel_compresion: i32,
+    limite: &LimiteArchivo,
+) -> Result<Vec<ArchivoBanco>, Box<dyn std::error::Error>> {
+    // Cada línea se evalúa una vez y sirve para dividir y para escribir
+    let renglones = renglones(formato, beneficiarios, ctx);
+    escribir_archivos(
+        formato,
+        &renglones,
+        ctx,
+        destino,
+        comprimir,
+        nivel_compresion,
+        limite,
+    )
+}
+
+/// Como `generar_archivos`, con renglones ya evaluados (p. ej. por el
+/// enrutamiento, que ya apartó los rechazados).
+pub fn escribir_archivos(
+    formato: &dyn BancoFormato,
+    renglones: &[Renglon],
+    ctx: &ContextoArchivo,
+    destino: &str,
+    comprimir: bool,
+    nivel_compresion: i32,
+    limite: &LimiteArchivo,
+) -> Result<Vec<ArchivoBanco>, Box<dyn std::error::Error>> {
+    let partes = if limite.sin_limite() {
+        vec![renglones]
+    } else {
+        dividir(renglones, limite)
+    };
+    if partes.len() == 1 {
+        let archivo = escribir_archivo(
+            formato,
+            partes[0],
+            ctx,
+            destino,
+            comprimir,
+            nivel_compresion,
+        )?;
+        return Ok(vec![archivo]);
+    }
+
+    logger::log_info(
+        "BANCO",
+        &format!(
+            "{}: archivo dividido en {} partes por límite",
+            formato.banco().nombre(),
+            partes.len()
+        ),
+    );
+    let mut archivos = Vec::with_capacity(partes.len());
+    for (i, parte) in partes.iter().enumerate() {
+        let mut ctx_parte = ctx.clone();
+        ctx_parte.parte = Some(i + 1);
+        archivos.push(escribir_archivo(
+            formato,
+            parte,
+            &ctx_parte,
+            destino,
+            comprimir,
+            nivel_compresion,
+        )?);
+    }
+    Ok(archivos)
+}
+
+/// Formatos de archivo disponibles, por banco.
+pub struct RegistroBancos {
+    formatos: HashMap<Banco, Box<dyn BancoFormato>>,
+}
+
+impl RegistroBancos {
+    pub fn vacio() -> Self {
+        Self {
+            formatos: HashMap::new(),
+        }
+    }
+
+    /// Registro con todos los formatos implementados en `banco`, cada uno con
+    /// su diseño de `schema/bancos/`.
+    pub fn por_defecto() -> Self {
+        let mut registro = Self::vacio();
+        registro.registrar(Box::new(crate::banco::venezuela::formato()));
+        registro.registrar(Box::new(crate::banco::banfanb::formato()));
+        registro.registrar(Box::new(crate::banco::bicentenario::formato()));
+        registro.registrar(Box::new(crate::banco::mercantil::formato()));
+        registro.registrar(Box::new(crate::banco::provincial::formato()));
+        registro.registrar(Box::new(crate::banco::tesoro::formato()));
+        registro
+    }
+
+    /// Reemplaza formatos por diseños declarativos (código de banco -> ruta del
+    /// JSON), p. ej. los de `salida.disenos` del manifiesto.
+    pub fn cargar_disenos(
+        &mut self,
+        disenos: &HashMap<String, String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for (codigo, ruta) in disenos {
+            let formato = crate::banco::diseno::FormatoDeclarativo::cargar(ruta)?;
+            if formato.banco().codigo() != codigo {
+                return Err(format!(
+                    "{}: el diseño es del banco {}, no de {}",
+                    ruta,
+                    formato.banco().codigo(),
+                    codigo
+                )
+                .into());
+            }
+            logger::log_info(
+                "BANCO",
+                &format!(
+                    "{}: diseño cargado desde {}",
+                    formato.banco().nombre(),
+                    ruta
+                ),
+            );
+            self.registrar(Box::new(formato));
+        }
+        Ok(())
+    }
+
+    /// Agrega o reemplaza el formato de su banco.
+    pub fn registrar(&mut self, formato: Box<dyn BancoFormato>) {
+        self.formatos.insert(formato.banco(), formato);
+    }
+
+    pub fn obtener(&self, banco: Banco) -> Option<&dyn BancoFormato> {
+        self.formatos.get(&banco).map(|f| f.as_ref())
+    }
+
+    pub fn por_codigo(&self, codigo: &str) -> Option<&dyn BancoFormato> {
+        self.obtener(Banco::from_codigo(codigo))
+    }
+
+    pub fn bancos(&self) -> Vec<Banco> {
+        let mut bancos: Vec<Banco> = self.formatos.keys().copied().collect();
+        bancos.sort_by_key(|b| b.codigo());
+        bancos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ben(cedula: &str, cuenta: &str, garantias: f64) -> Beneficiario {
+        let mut b: Beneficiario = serde_json::from_str(&format!(
+            r#"{{"cedula":"{}","numero_cuenta":"{}"}}"#,
+            cedula, cuenta
+        ))
+        .unwrap();
+        b.base.garantias = garantias;
+        b
+    }
+
+    #[test]
+    fn test_registro_y_contenido_banfanb() {
+        let registro = RegistroBancos::por_defecto();
+        assert_eq!(
+            registro.bancos(),
+            vec![
+                Banco::Venezuela,
+                Banco::Mercantil,
+                Banco::Provincial,
+                Banco::Tesoro,
+                Banco::Bicentenario,
+                Banco::Banfanb
+            ]
+        );
+        assert!(registro.por_codigo("0191").is_none());
+
+        let formato = registro.por_codigo("0177").unwrap();
+        let mut ctx = ContextoArchivo::new(TipoArchivo::Aporte, "2026-02", 100.0);
+        ctx.fecha = "010226".to_string();
+        assert_eq!(formato.nombre_archivo(&ctx), "banfanb_2026-02.txt");
+
+        let beneficiarios = vec![
+            ben("V-123", "01770000690000000001", 150.5),
+            ben("456", "", 80.0),
+            ben("789", "01770000650000000003", 0.0),
+        ];
+        let (contenido, cantidad, total, rechazados) =
+            armar_contenido(formato, &beneficiarios, &ctx).unwrap();
+
+        assert_eq!(cantidad, 1);
+        assert_eq!(total, 150.5);
+        assert_eq!(rechazados.len(), 1);
+        assert_eq!(rechazados[0].0, "456");
+        assert_eq!(
+            contenido,
+            "0131010226000000000000001510001\r\n\
+             013100000001505001770000690000000001000000012300000000\r\n"
+        );
+    }
+
+    #[test]
+    fn test_dividir_por_limite() {
+        let registro = RegistroBancos::por_defecto();
+        let formato = registro.por_codigo("0177").unwrap();
+        let ctx = ContextoArchivo::new(TipoArchivo::Aporte, "2026-02", 100.0);
+        let beneficiarios = vec![
+            ben("1", "01770000690000000001", 60.0),
+            ben("2", "", 10.0),
+            ben("3", "01770000650000000003", 30.0),
+            ben("4", "01770000630000000004", 500.0),
+            ben("5", "01770000610000000005", 5.0),
+            ben("6", "01770000610000000006", 5.0),
+            ben("7", "01770000680000000007", 5.0),
+        ];
+        let limite = LimiteArchivo {
+            max_registros: Some(2),
+            max_total: Some(100.0),
+        };
+        let renglones = renglones(formato, &beneficiarios, &ctx);
+        let cedulas: Vec<Vec<&str>> = dividir(&renglones, &limite)
+            .iter()
+            .map(|p| p.iter().map(|r| r.beneficiario.cedula.as_str()).collect())
+            .collect();
+        assert_eq!(
+            cedulas,
+            vec![vec!["1", "2", "3"], vec!["4"], vec!["5", "6"], vec!["7"]]
+        );
+
+        assert_eq!(
+            nombre_parte("banfanb_2026-02.txt", Some(2)),
+            "banfanb_2026-02_002.txt"
+        );
+        assert_eq!(
+            nombre_parte("banfanb_2026-02.txt", None),
+            "banfanb_2026-02.txt"
+        );
+    }
+}
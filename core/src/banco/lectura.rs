@@ -0,0 +1,540 @@
+//! Lectura de los TXT bancarios generados (`sandra banco verificar`).
+//!
+//! Cada archivo se interpreta con su `DisenoArchivo` (el mismo JSON de
+//! `schema/bancos/` que describe su generación): la primera línea es la
+//! cabecera y la última el pie cuando el diseño los tiene. `verificar` revisa el
+//! largo y los campos numéricos de cada línea, el fin de línea, la cantidad y el
+//! total de control de cabecera y pie, y opcionalmente los montos contra la
+//! nómina exportada del ciclo.
+
+use crate::banco::diseno::{DisenoArchivo, DisenoRegistro, FinLinea, TipoCampo, DISENO_PATRIA};
+use crate::banco::{banfanb, bicentenario, mercantil, provincial, tesoro, venezuela};
+use crate::nomina::diferencias::NominaExportada;
+use crate::util::cedula;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Prefijo del nombre de archivo y diseño con el que se lee.
+const DISENOS_POR_ARCHIVO: &[(&str, &str, &str)] = &[
+    ("APERT", "venezuela_apertura", venezuela::DISENO_APERTURA),
+    ("APORT", "venezuela", venezuela::DISENO),
+    ("RETIR", "venezuela", venezuela::DISENO),
+    ("MIXTO", "venezuela", venezuela::DISENO),
+    ("banfanb_", "banfanb", banfanb::DISENO),
+    ("bicentenario_", "bicentenario", bicentenario::DISENO),
+    ("mercantil_", "mercantil", mercantil::DISENO),
+    ("provincial_", "provincial", provincial::DISENO),
+    ("tesoro_", "tesoro", tesoro::DISENO),
+    ("patria_", "patria", DISENO_PATRIA),
+];
+
+/// Diferencia tolerada entre montos (medio céntimo).
+const TOLERANCIA: f64 = 0.005;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ValorCampo {
+    Texto(String),
+    /// Campo numérico sin escala (cédula, cuenta, cantidad): se conservan los ceros.
+    Digitos(String),
+    /// Campo numérico con escala, ya dividido (p. ej. céntimos a bolívares).
+    Monto(f64),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegistroLeido {
+    /// Número de línea en el archivo, desde 1.
+    pub linea: usize,
+    /// Línea original, sin el terminador.
+    pub texto: String,
+    pub campos: Vec<(String, ValorCampo)>,
+}
+
+impl RegistroLeido {
+    pub fn valor(&self, nombre: &str) -> Option<&ValorCampo> {
+        self.campos
+            .iter()
+            .find(|(n, _)| n == nombre)
+            .map(|(_, v)| v)
+    }
+
+    pub fn texto(&self, nombre: &str) -> Option<&str> {
+        match self.valor(nombre)? {
+            ValorCampo::Texto(t) | ValorCampo::Digitos(t) => Some(t),
+            ValorCampo::Monto(_) => None,
+        }
+    }
+
+    /// Cédula canónica del registro, con la letra del campo `nacionalidad` (o
+    /// `nac`) si el diseño lo tiene; sin él se asume `V`.
+    pub fn cedula(&self) -> Option<String> {
+        let numero = self.texto("cedula")?;
+        let letra = self.nacionalidad().unwrap_or_default();
+        cedula::clave(&format!("{}{}", letra, numero))
+    }
+
+    fn nacionalidad(&self) -> Option<&str> {
+        self.texto("nacionalidad")
+            .or_else(|| self.texto("nac"))
+            .map(str::trim)
+    }
+
+    pub fn numero(&self, nombre: &str) -> Option<f64> {
+        match self.valor(nombre)? {
+            ValorCampo::Monto(m) => Some(*m),
+            ValorCampo::Digitos(d) => d.parse().ok(),
+            ValorCampo::Texto(_) => None,
+        }
+    }
+}
+
+/// Observación de la verificación sobre una línea del archivo.
+#[derive(Debug, Clone, Serialize)]
+pub struct Observacion {
+    pub linea: usize,
+    pub cedula: Option<String>,
+    pub motivo: String,
+}
+
+impl Observacion {
+    fn new(linea: usize, cedula: Option<&str>, motivo: String) -> Self {
+        Self {
+            linea,
+            cedula: cedula.map(str::to_string),
+            motivo,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivoLeido {
+    pub archivo: String,
+    /// Nombre del diseño usado (`mercantil`, `venezuela`, ... o la ruta del JSON).
+    pub diseno: String,
+    pub cabecera: Option<RegistroLeido>,
+    pub detalle: Vec<RegistroLeido>,
+    pub pie: Option<RegistroLeido>,
+    /// Líneas que no se pudieron interpretar con el diseño.
+    pub errores: Vec<Observacion>,
+}
+
+impl ArchivoLeido {
+    pub fn total_detalle(&self) -> f64 {
+        self.detalle.iter().filter_map(|r| r.numero("monto")).sum()
+    }
+}
+
+/// Diseño que corresponde al archivo según su nombre.
+pub fn diseno_para(archivo: &str) -> Option<(&'static str, DisenoArchivo)> {
+    let nombre = Path::new(archivo)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (_, diseno, json) = DISENOS_POR_ARCHIVO
+        .iter()
+        .find(|(prefijo, _, _)| nombre.starts_with(prefijo))?;
+    DisenoArchivo::desde_json(json).ok().map(|d| (*diseno, d))
+}
+
+/// Lee el TXT (o `.txt.zst`) en `ruta`; sin `diseno` se elige por el nombre.
+/// Devuelve también el diseño usado, para `verificar`.
+pub fn cargar(
+    ruta: &str,
+    diseno: Option<(String, DisenoArchivo)>,
+) -> Result<(ArchivoLeido, DisenoArchivo), Box<dyn std::error::Error>> {
+    let (nombre_diseno, diseno) = match diseno {
+        Some(d) => d,
+        None => diseno_para(ruta.trim_end_matches(".zst"))
+            .map(|(n, d)| (n.to_string(), d))
+            .ok_or_else(|| format!("No hay diseño para '{}' (use --diseno)", ruta))?,
+    };
+    let bytes = std::fs::read(ruta).map_err(|e| format!("No se pudo leer '{}': {}", ruta, e))?;
+    let datos = if ruta.ends_with(".zst") {
+        zstd::stream::decode_all(bytes.as_slice())
+            .map_err(|e| format!("No se pudo descomprimir '{}': {}", ruta, e))?
+    } else {
+        bytes
+    };
+    let contenido = String::from_utf8_lossy(&datos);
+    let mut archivo = leer(&diseno, &contenido);
+    archivo.archivo = ruta.to_string();
+    archivo.diseno = nombre_diseno;
+    Ok((archivo, diseno))
+}
+
+/// Interpreta el contenido de un archivo con su diseño.
+pub fn leer(diseno: &DisenoArchivo, contenido: &str) -> ArchivoLeido {
+    leer_lineas(
+        diseno.cabecera.as_ref(),
+        &diseno.detalle,
+        diseno.pie.as_ref(),
+        diseno.fin_linea,
+        contenido,
+    )
+}
+
+/// Interpreta el contenido con los registros indicados: la primera línea es la
+/// cabecera y la última el pie, si los hay.
+pub fn leer_lineas(
+    cabecera: Option<&DisenoRegistro>,
+    detalle: &DisenoRegistro,
+    pie: Option<&DisenoRegistro>,
+    fin_linea: FinLinea,
+    contenido: &str,
+) -> ArchivoLeido {
+    let mut errores = Vec::new();
+    let mut lineas: Vec<(usize, &str)> = Vec::new();
+    let esperado = fin_linea == FinLinea::Crlf;
+    for (i, linea) in contenido.split_inclusive('\n').enumerate() {
+        let numero = i + 1;
+        // La última línea puede no tener terminador
+        let terminada = linea.ends_with('\n');
+        let linea = linea.trim_end_matches('\n');
+        let (texto, crlf) = match linea.strip_suffix('\r') {
+            Some(t) => (t, true),
+            None => (linea, false),
+        };
+        if texto.is_empty() {
+            continue;
+        }
+        if terminada && crlf != esperado {
+            errores.push(Observacion::new(
+                numero,
+                None,
+                format!("fin de línea distinto de {:?}", fin_linea),
+            ));
+        }
+        lineas.push((numero, texto));
+    }
+
+    let mut leer_con = |registro: &DisenoRegistro, (numero, texto): (usize, &str)| {
+        leer_registro(registro, texto, numero)
+            .map_err(|motivo| errores.push(Observacion::new(numero, None, motivo)))
+            .ok()
+    };
+
+    let mut restantes = lineas.as_slice();
+    let cabecera = match (cabecera, restantes.split_first()) {
+        (Some(registro), Some((primera, resto))) => {
+            restantes = resto;
+            leer_con(registro, *primera)
+        }
+        _ => None,
+    };
+    let pie = match (pie, restantes.split_last()) {
+        (Some(registro), Some((ultima, resto))) => {
+            restantes = resto;
+            leer_con(registro, *ultima)
+        }
+        _ => None,
+    };
+    let detalle = restantes
+        .iter()
+        .filter_map(|l| leer_con(detalle, *l))
+        .collect();
+
+    ArchivoLeido {
+        archivo: String::new(),
+        diseno: String::new(),
+        cabecera,
+        detalle,
+        pie,
+        errores,
+    }
+}
+
+/// Separa una línea en los campos del registro, validando largo y numéricos.
+pub fn leer_registro(
+    registro: &DisenoRegistro,
+    linea: &str,
+    numero: usize,
+) -> Result<RegistroLeido, String> {
+    registro.validar_linea(linea)?;
+    let columnas: Vec<char> = linea.chars().collect();
+    let mut campos: Vec<_> = registro.campos.iter().collect();
+    campos.sort_by_key(|c| c.inicio);
+
+    let campos = campos
+        .into_iter()
+        .map(|c| {
+            let valor: String = columnas[c.inicio - 1..c.inicio - 1 + c.largo]
+                .iter()
+                .collect();
+            let valor = match (c.tipo, c.escala) {
+                (TipoCampo::Numerico, Some(escala)) => {
+                    let n: f64 = valor.parse().unwrap_or(0.0);
+                    ValorCampo::Monto(n / 10f64.powi(escala as i32))
+                }
+                (TipoCampo::Numerico, None) => ValorCampo::Digitos(valor),
+                (TipoCampo::Texto, _) => ValorCampo::Texto(valor.trim().to_string()),
+            };
+            (c.nombre.clone(), valor)
+        })
+        .collect();
+
+    Ok(RegistroLeido {
+        linea: numero,
+        texto: linea.to_string(),
+        campos,
+    })
+}
+
+/// Resultado de `sandra banco verificar` para un archivo.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificacionArchivo {
+    pub archivo: String,
+    pub diseno: String,
+    pub registros: usize,
+    pub total: f64,
+    /// Nómina contra la que se compararon los montos, si se indicó.
+    pub nomina: Option<String>,
+    pub observaciones: Vec<Observacion>,
+}
+
+impl VerificacionArchivo {
+    pub fn correcto(&self) -> bool {
+        self.observaciones.is_empty()
+    }
+}
+
+/// Verifica estructura y totales de control del archivo y, con `nomina`, que
+/// el monto de cada cédula coincida con la suma de `columnas` en la nómina.
+pub fn verificar(
+    archivo: &ArchivoLeido,
+    diseno: &DisenoArchivo,
+    nomina: Option<(&NominaExportada, &[String])>,
+) -> VerificacionArchivo {
+    let mut observaciones = archivo.errores.clone();
+    let cantidad = archivo.detalle.len();
+    let total = archivo.total_detalle();
+
+    for (nombre, registro, leido) in [
+        ("cabecera", &diseno.cabecera, &archivo.cabecera),
+        ("pie", &diseno.pie, &archivo.pie),
+    ] {
+        let (Some(registro), Some(leido)) = (registro, leido) else {
+            continue;
+        };
+        if let Some(declarada) = leido.numero("cantidad") {
+            if declarada as usize != cantidad {
+                observaciones.push(Observacion::new(
+                    leido.linea,
+                    None,
+                    format!(
+                        "{}: cantidad {} y el archivo tiene {} registros",
+                        nombre, declarada, cantidad
+                    ),
+                ));
+            }
+        }
+        if let Some(declarado) = leido.numero("total") {
+            // El total puede tener menos decimales que el detalle (Banfanb: enteros)
+            let escala = registro
+                .campos
+                .iter()
+                .find(|c| c.nombre == "total")
+                .and_then(|c| c.escala)
+                .unwrap_or(2);
+            let tolerancia = 0.5 / 10f64.powi(escala as i32) + TOLERANCIA;
+            if (declarado - total).abs() > tolerancia {
+                observaciones.push(Observacion::new(
+                    leido.linea,
+                    None,
+                    format!(
+                        "{}: total {:.2} y el detalle suma {:.2}",
+                        nombre, declarado, total
+                    ),
+                ));
+            }
+        }
+    }
+
+    let mut vistas: HashMap<String, usize> = HashMap::new();
+    for r in &archivo.detalle {
+        let Some(cedula) = r.cedula() else {
+            continue;
+        };
+        if let Some(anterior) = vistas.insert(cedula.clone(), r.linea) {
+            observaciones.push(Observacion::new(
+                r.linea,
+                Some(&cedula),
+                format!("cédula repetida (línea {})", anterior),
+            ));
+        }
+    }
+
+    if let Some((nomina, columnas)) = nomina {
+        let indice: HashMap<String, &HashMap<String, String>> = nomina
+            .registros
+            .iter()
+            .filter_map(|(cedula, registro)| Some((cedula::clave(cedula)?, registro)))
+            .collect();
+        // Diseños sin nacionalidad: se cruza por número si no es ambiguo
+        let mut por_numero: HashMap<String, Vec<&str>> = HashMap::new();
+        for clave in indice.keys() {
+            por_numero
+                .entry(numero_clave(clave).to_string())
+                .or_default()
+                .push(clave);
+        }
+        for r in &archivo.detalle {
+            let (Some(cedula), Some(monto)) = (r.cedula(), r.numero("monto")) else {
+                continue;
+            };
+            let cedula = match (r.nacionalidad(), por_numero.get(numero_clave(&cedula))) {
+                (None, Some(claves)) if claves.len() > 1 => {
+                    observaciones.push(Observacion::new(
+                        r.linea,
+                        Some(&cedula),
+                        format!(
+                            "cédula ambigua sin nacionalidad en el archivo: {}",
+                            claves.join(", ")
+                        ),
+                    ));
+                    continue;
+                }
+                (None, Some(claves)) => claves[0].to_string(),
+                _ => cedula,
+            };
+            let Some(registro) = indice.get(&cedula) else {
+                observaciones.push(Observacion::new(
+                    r.linea,
+                    Some(&cedula),
+                    "cédula no está en la nómina".to_string(),
+                ));
+                continue;
+            };
+            let esperado: f64 = columnas
+                .iter()
+                .map(|c| {
+                    registro
+                        .get(c)
+                        .and_then(|v| v.parse::<f64>().ok())
+                        .unwrap_or(0.0)
+                })
+                .sum();
+            if (esperado - monto).abs() > TOLERANCIA {
+                observaciones.push(Observacion::new(
+                    r.linea,
+                    Some(&cedula),
+                    format!(
+                        "monto {:.2} en el archivo y {:.2} en la nómina ({})",
+                        monto,
+                        esperado,
+                        columnas.join("+")
+                    ),
+                ));
+            }
+        }
+    }
+
+    observaciones.sort_by_key(|o| o.linea);
+    VerificacionArchivo {
+        archivo: archivo.archivo.clone(),
+        diseno: archivo.diseno.clone(),
+        registros: cantidad,
+        total,
+        nomina: nomina.map(|(n, _)| n.archivo.clone()),
+        observaciones,
+    }
+}
+
+/// Número de una clave canónica (`V-12345678` -> `12345678`).
+fn numero_clave(clave: &str) -> &str {
+    clave.split_once('-').map_or(clave, |(_, numero)| numero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::banco::formato::{armar_contenido, ContextoArchivo};
+    use crate::banco::tipos::TipoArchivo;
+    use crate::banco::RegistroBancos;
+    use crate::kernel::logica::memoria::Beneficiario;
+
+    fn ben(cedula: &str, cuenta: &str, garantias: f64) -> Beneficiario {
+        let mut b: Beneficiario = serde_json::from_str(&format!(
+            r#"{{"cedula":"{}","nombres":"ANA","apellidos":"PEREZ","numero_cuenta":"{}"}}"#,
+            cedula, cuenta
+        ))
+        .unwrap();
+        b.base.garantias = garantias;
+        b.movimientos.anticipo = garantias;
+        b
+    }
+
+    #[test]
+    fn test_ida_y_vuelta_y_verificacion() {
+        let registro = RegistroBancos::por_defecto();
+        let beneficiarios = vec![
+            ben("V-123", "01770000690000000001", 150.5),
+            ben("456", "01770000670000000002", 80.25),
+        ];
+
+        // Cada formato registrado se vuelve a leer con su diseño sin errores
+        for banco in registro.bancos() {
+            let formato = registro.obtener(banco).unwrap();
+            for tipo in [
+                TipoArchivo::Apertura,
+                TipoArchivo::Aporte,
+                TipoArchivo::Retiro,
+            ] {
+                if !formato.soporta(tipo) {
+                    continue;
+                }
+                let mut ctx = ContextoArchivo::new(tipo, "2026-02", 100.0);
+                ctx.rif = Some("G200000001".to_string());
+                let (contenido, cantidad, _, _) =
+                    armar_contenido(formato, &beneficiarios, &ctx).unwrap();
+                let (_, diseno) = diseno_para(&formato.nombre_archivo(&ctx)).unwrap();
+                let leido = leer(&diseno, &contenido);
+                let v = verificar(&leido, &diseno, None);
+                assert!(
+                    v.correcto(),
+                    "{:?} {:?}: {:?}",
+                    banco,
+                    tipo,
+                    v.observaciones
+                );
+                assert_eq!(v.registros, cantidad);
+            }
+        }
+
+        // Banfanb contra la nómina: un monto distinto y un total de control alterado
+        let ctx = ContextoArchivo::new(TipoArchivo::Aporte, "2026-02", 100.0);
+        let formato = registro.obtener(crate::banco::Banco::Banfanb).unwrap();
+        let (contenido, _, _, _) = armar_contenido(formato, &beneficiarios, &ctx).unwrap();
+        let (_, diseno) = diseno_para("banfanb_2026-02.txt").unwrap();
+        let nomina =
+            NominaExportada::desde_csv(b"cedula,garantias\nV-123,150.50\n456,80.00\n").unwrap();
+        let columnas = vec!["garantias".to_string()];
+
+        let leido = leer(&diseno, &contenido);
+        assert_eq!(leido.detalle[0].texto("cedula"), Some("0000000123"));
+        assert_eq!(leido.detalle[0].cedula().as_deref(), Some("V-123"));
+        assert_eq!(leido.detalle[0].numero("monto"), Some(150.5));
+        let v = verificar(&leido, &diseno, Some((&nomina, &columnas)));
+        assert_eq!(v.observaciones.len(), 1);
+        assert_eq!(v.observaciones[0].linea, 3);
+        assert_eq!(v.observaciones[0].cedula.as_deref(), Some("V-456"));
+
+        // Banfanb no trae nacionalidad: V-123 y E-123 en la nómina son ambiguas
+        let nomina =
+            NominaExportada::desde_csv(b"cedula,garantias\nV-123,150.50\nE-123,10.00\n456,80.25\n")
+                .unwrap();
+        let v = verificar(&leido, &diseno, Some((&nomina, &columnas)));
+        assert_eq!(v.observaciones.len(), 1);
+        assert!(v.observaciones[0].motivo.starts_with("cédula ambigua"));
+
+        let alterado = contenido.replacen("0002\r\n", "0003\r\n", 1);
+        let v = verificar(&leer(&diseno, &alterado), &diseno, None);
+        assert!(v.observaciones[0].motivo.contains("cantidad 3"));
+        let v = verificar(
+            &leer(&diseno, &contenido.replace("\r\n", "\n")),
+            &diseno,
+            None,
+        );
+        assert!(v.observaciones[0].motivo.contains("fin de línea"));
+    }
+}
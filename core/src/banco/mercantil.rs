@@ -0,0 +1,61 @@
+//! Banco Mercantil (0105): abono en cuenta de garantías (aporte) o de
+//! movimientos (retiro). Registros de ancho fijo terminados en CRLF,
+//! descritos en `schema/bancos/mercantil.json`:
+//!
+//! | Registro | Campos |
+//! |----------|--------|
+//! | Cabecera `1` | banco (4), RIF empresa `salida.rif` (10), fecha DDMMAA (6), operación `01` aporte / `02` retiro (2), cantidad (8), total en céntimos (15) |
+//! | Detalle `2`  | nacionalidad (1), cédula (10), cuenta (20), monto en céntimos (15), nombre (40) |
+//! | Pie `3`      | cantidad (8), total en céntimos (15) |
+
+use crate::banco::diseno::FormatoDeclarativo;
+
+pub const DISENO: &str = include_str!("../../../schema/bancos/mercantil.json");
+
+pub fn formato() -> FormatoDeclarativo {
+    FormatoDeclarativo::desde_json(DISENO).expect("schema/bancos/mercantil.json inválido")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::banco::formato::{armar_contenido, BancoFormato, ContextoArchivo};
+    use crate::banco::tipos::TipoArchivo;
+    use crate::kernel::logica::memoria::Beneficiario;
+
+    #[test]
+    fn test_archivo_mercantil_aporte() {
+        let mut b: Beneficiario = serde_json::from_str(
+            r#"{"cedula":"V-12345678","nombres":"JOSÉ","apellidos":"PEÑA","numero_cuenta":"01050000190000000001"}"#,
+        )
+        .unwrap();
+        b.base.garantias = 1234.56;
+        let mut ajena = b.clone();
+        ajena.cedula = "99".to_string();
+        ajena.numero_cuenta = "01020000890000000001".to_string();
+
+        let mut ctx = ContextoArchivo::new(TipoArchivo::Aporte, "2026-02", 100.0);
+        ctx.fecha = "280226".to_string();
+        // Sin `salida.rif` no hay cabecera ni archivo
+        assert!(armar_contenido(&formato(), &[b.clone()], &ctx)
+            .unwrap_err()
+            .contains("rif"));
+        ctx.rif = Some("G200000001".to_string());
+        let (contenido, cantidad, total, rechazados) =
+            armar_contenido(&formato(), &[b, ajena], &ctx).unwrap();
+
+        let lineas: Vec<&str> = contenido.split("\r\n").collect();
+        assert_eq!(
+            lineas,
+            vec![
+                "10105G2000000012802260100000001000000000123456",
+                "2V001234567801050000190000000001000000000123456JOSE PENA                               ",
+                "300000001000000000123456",
+                "",
+            ]
+        );
+        assert_eq!((cantidad, total), (1, 1234.56));
+        assert_eq!(rechazados[0].0, "99");
+        assert!(!formato().soporta(TipoArchivo::Apertura));
+    }
+}
@@ -1,26 +1,46 @@
 pub mod banfanb;
 pub mod bicentenario;
+pub mod cuenta;
+pub mod diseno;
+pub mod enrutamiento;
+pub mod formato;
+pub mod lectura;
 pub mod loki;
+pub mod mercantil;
+pub mod patria;
+pub mod provincial;
+pub mod respuesta;
+pub mod tesoro;
 pub mod tipos;
 pub mod venezuela;
 
+pub use diseno::{DisenoArchivo, FormatoDeclarativo};
+pub use formato::{ArchivoBanco, BancoFormato, ContextoArchivo, LineaDetalle, RegistroBancos};
 pub use loki::GeneradorLoki;
 pub use tipos::{Banco, CampoBanco, TipoArchivo};
 
+use crate::kernel::logica::memoria::Beneficiario;
+
+/// Genera el TXT del banco indicado por su código con el formato registrado por
+/// defecto. Error si el banco no tiene formato.
 pub fn generar_txt_bancario(
-    _codigo_banco: &str,
-    _tipo: TipoArchivo,
-    _ciclo: &str,
-    _destino: &str,
-    _porcentaje: f64,
-) -> Result<crate::kernel::logica::exportador::ResultadoExport, Box<dyn std::error::Error>> {
-    Ok(crate::kernel::logica::exportador::ResultadoExport {
-        ruta: String::new(),
-        tipo: String::new(),
-        tamano_original: 0,
-        tamano_comprimido: None,
-        hash_sha256: None,
-        hash_sha256_original: None,
-        compresion_aplicada: false,
-    })
+    beneficiarios: &[Beneficiario],
+    codigo_banco: &str,
+    ctx: &ContextoArchivo,
+    destino: &str,
+    comprimir: bool,
+    nivel_compresion: i32,
+) -> Result<ArchivoBanco, Box<dyn std::error::Error>> {
+    let registro = RegistroBancos::por_defecto();
+    let formato = registro
+        .por_codigo(codigo_banco)
+        .ok_or_else(|| format!("Banco '{}' sin formato de archivo registrado", codigo_banco))?;
+    formato::generar_archivo(
+        formato,
+        beneficiarios,
+        ctx,
+        destino,
+        comprimir,
+        nivel_compresion,
+    )
 }
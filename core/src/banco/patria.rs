@@ -0,0 +1,379 @@
+//! Nómina Patria (`sandra patria`).
+//!
+//! Un TXT por banco admitido (`patria.bancos` del manifiesto) con el diseño
+//! `schema/bancos/patria.json`: cabecera `ONTNOM` con RIF, cantidad, total,
+//! moneda y fecha de pago; las líneas de `FiniquitoPatria::to_line_patria` y
+//! pie `FINNOM` con cantidad y total. Los finiquitos que no entran quedan en los
+//! reportes de conciliación, que se firman en el mismo `manifest.json`.
+
+use crate::banco::diseno::{DisenoArchivo, RegistroCompilado, DISENO_PATRIA};
+use crate::calc::calculos::redondear_dos;
+use crate::kernel::logica::exportador::{comprimir_y_sellar, generar_hash, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::kernel::logica::memoria::{FiniquitoPatria, RechazoPatria};
+use crate::model::manifiesto::PatriaConfig;
+use crate::util::cedula;
+use rhai::{Engine, Scope};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+struct DisenoPatria {
+    engine: Engine,
+    archivo: String,
+    cabecera: Option<RegistroCompilado>,
+    pie: Option<RegistroCompilado>,
+}
+
+fn diseno() -> &'static DisenoPatria {
+    static DISENO: OnceLock<DisenoPatria> = OnceLock::new();
+    DISENO.get_or_init(|| {
+        let engine = crate::util::script::motor();
+        let diseno =
+            DisenoArchivo::desde_json(DISENO_PATRIA).expect("schema/bancos/patria.json inválido");
+        let compilar = |r: &Option<_>| {
+            r.as_ref().map(|r| {
+                RegistroCompilado::compilar(&engine, r).expect("schema/bancos/patria.json inválido")
+            })
+        };
+        let cabecera = compilar(&diseno.cabecera);
+        let pie = compilar(&diseno.pie);
+        DisenoPatria {
+            engine,
+            archivo: diseno.archivo,
+            cabecera,
+            pie,
+        }
+    })
+}
+
+/// TXT de Patria de un banco.
+#[derive(Debug, Clone)]
+pub struct ArchivoPatria {
+    pub banco: String,
+    pub nombre: String,
+    pub contenido: String,
+    pub registros: usize,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NominaPatria {
+    pub archivos: Vec<ArchivoPatria>,
+    pub negativos: Vec<FiniquitoPatria>,
+    pub sin_cuenta: Vec<FiniquitoPatria>,
+    pub cuenta_invalida: Vec<(FiniquitoPatria, String)>,
+    pub invalidos: Vec<(FiniquitoPatria, String)>,
+    /// Registros de cédulas que aparecen más de una vez. No van al TXT salvo
+    /// con `patria.incluir_duplicados`; se informan para revisión.
+    pub duplicados: Vec<FiniquitoPatria>,
+}
+
+/// Arma los TXT por banco y clasifica los finiquitos que no entran.
+/// `fecha_pago` es `YYYY-MM-DD`. Error si falta `patria.rif` o no es válido.
+pub fn generar(
+    finiquitos: &[FiniquitoPatria],
+    config: &PatriaConfig,
+    ciclo: &str,
+    fecha_pago: &str,
+) -> Result<NominaPatria, String> {
+    let rif = config.rif_ordenante()?;
+    let mut nomina = NominaPatria::default();
+    let mut lineas: Vec<(Vec<String>, f64)> = vec![(Vec::new(), 0.0); config.bancos.len()];
+
+    let clave = |fq: &FiniquitoPatria| {
+        cedula::clave(&fq.cedula).unwrap_or_else(|| fq.cedula.trim().to_string())
+    };
+    let mut repeticiones: HashMap<String, usize> = HashMap::new();
+    for fq in finiquitos {
+        *repeticiones.entry(clave(fq)).or_default() += 1;
+    }
+
+    for fq in finiquitos {
+        if repeticiones.get(&clave(fq)).is_some_and(|n| *n > 1) {
+            nomina.duplicados.push(fq.clone());
+            if !config.incluir_duplicados {
+                continue;
+            }
+        }
+
+        match fq.validar(&config.bancos) {
+            Ok(banco) => match fq.to_line_patria() {
+                Ok(linea) => {
+                    let i = config.bancos.iter().position(|b| *b == banco).unwrap_or(0);
+                    lineas[i].0.push(linea);
+                    // Total de control: suma de los montos tal como se escriben
+                    lineas[i].1 = redondear_dos(lineas[i].1 + redondear_dos(fq.monto));
+                }
+                Err(e) => {
+                    logger::log_warn("PATRIA", &format!("{}: {}", fq.cedula, e));
+                    nomina.invalidos.push((fq.clone(), e));
+                }
+            },
+            Err(RechazoPatria::Negativo) => nomina.negativos.push(fq.clone()),
+            Err(RechazoPatria::SinCuenta) => nomina.sin_cuenta.push(fq.clone()),
+            Err(RechazoPatria::CuentaInvalida(motivo)) => {
+                nomina.cuenta_invalida.push((fq.clone(), motivo))
+            }
+            Err(RechazoPatria::CedulaInvalida(motivo)) => {
+                nomina.invalidos.push((fq.clone(), motivo))
+            }
+        }
+    }
+
+    let d = diseno();
+    let base = d.archivo.replace("{ciclo}", ciclo);
+    for (banco, (detalle, total)) in config.bancos.iter().zip(lineas) {
+        let nombre = if config.bancos.len() > 1 {
+            match base.rfind('.') {
+                Some(punto) => format!("{}_{}{}", &base[..punto], banco, &base[punto..]),
+                None => format!("{}_{}", base, banco),
+            }
+        } else {
+            base.clone()
+        };
+
+        let mut scope = Scope::new();
+        scope.push("cantidad", detalle.len() as i64);
+        scope.push("total", total);
+        scope.push("rif", rif.clone());
+        scope.push("moneda", config.moneda.clone());
+        scope.push("fecha", fecha_pago.to_string());
+        scope.push("banco", banco.clone());
+        scope.push("ciclo", ciclo.to_string());
+
+        let mut contenido = String::new();
+        if let Some(cabecera) = &d.cabecera {
+            contenido.push_str(&cabecera.renderizar(&d.engine, &mut scope)?);
+            contenido.push('\n');
+        }
+        for linea in &detalle {
+            contenido.push_str(linea);
+            contenido.push('\n');
+        }
+        if let Some(pie) = &d.pie {
+            contenido.push_str(&pie.renderizar(&d.engine, &mut scope)?);
+            contenido.push('\n');
+        }
+
+        nomina.archivos.push(ArchivoPatria {
+            banco: banco.clone(),
+            nombre,
+            contenido,
+            registros: detalle.len(),
+            total,
+        });
+    }
+
+    Ok(nomina)
+}
+
+impl NominaPatria {
+    pub fn registros(&self) -> usize {
+        self.archivos.iter().map(|a| a.registros).sum()
+    }
+
+    pub fn total(&self) -> f64 {
+        redondear_dos(self.archivos.iter().map(|a| a.total).sum())
+    }
+
+    /// Escribe los TXT en `destino`, comprimidos con zstd si se pide.
+    pub fn exportar(
+        &self,
+        destino: &str,
+        comprimir: bool,
+        nivel_compresion: i32,
+    ) -> Result<Vec<ResultadoExport>, Box<dyn std::error::Error>> {
+        let mut resultados = Vec::with_capacity(self.archivos.len());
+        for archivo in &self.archivos {
+            let ruta = ruta_salida(destino, &archivo.nombre);
+            let datos = archivo.contenido.as_bytes();
+            let hash_txt = generar_hash(datos);
+            let resultado = if comprimir {
+                let (comprimido, hash) = comprimir_y_sellar(datos, nivel_compresion);
+                let ruta_zst = ruta.with_extension("txt.zst");
+                std::fs::write(&ruta_zst, &comprimido)?;
+                ResultadoExport {
+                    ruta: ruta_zst.display().to_string(),
+                    tipo: "patria".to_string(),
+                    tamano_original: datos.len() as u64,
+                    tamano_comprimido: Some(comprimido.len() as u64),
+                    hash_sha256: Some(hash),
+                    hash_sha256_original: Some(hash_txt),
+                    compresion_aplicada: true,
+                }
+            } else {
+                std::fs::write(&ruta, datos)?;
+                ResultadoExport {
+                    ruta: ruta.display().to_string(),
+                    tipo: "patria".to_string(),
+                    tamano_original: datos.len() as u64,
+                    tamano_comprimido: None,
+                    hash_sha256: Some(hash_txt),
+                    hash_sha256_original: None,
+                    compresion_aplicada: false,
+                }
+            };
+            logger::log_info(
+                "PATRIA",
+                &format!(
+                    "{}: {} registros, {:.2}",
+                    archivo.nombre, archivo.registros, archivo.total
+                ),
+            );
+            resultados.push(resultado);
+        }
+        Ok(resultados)
+    }
+
+    /// Escribe los reportes de conciliación no vacíos (`patria_negativos.csv`,
+    /// `patria_sin_cuenta.csv`, `patria_cuenta_invalida.csv`,
+    /// `patria_invalidos.csv`, `patria_duplicados.csv`). Con `todos` en falso
+    /// solo el de cuentas inválidas, que siempre se informa.
+    pub fn exportar_conciliacion(
+        &self,
+        destino: &str,
+        todos: bool,
+    ) -> Result<Vec<ResultadoExport>, Box<dyn std::error::Error>> {
+        let sin_motivo = |v: &[FiniquitoPatria]| -> Vec<(FiniquitoPatria, String)> {
+            v.iter().map(|fq| (fq.clone(), String::new())).collect()
+        };
+        let reportes = [
+            ("negativos", sin_motivo(&self.negativos), todos),
+            ("sin_cuenta", sin_motivo(&self.sin_cuenta), todos),
+            ("cuenta_invalida", self.cuenta_invalida.clone(), true),
+            ("invalidos", self.invalidos.clone(), todos),
+            ("duplicados", sin_motivo(&self.duplicados), true),
+        ];
+
+        let mut resultados = Vec::new();
+        for (nombre, registros, incluir) in reportes {
+            if !incluir || registros.is_empty() {
+                continue;
+            }
+            let mut wtr = csv::Writer::from_writer(vec![]);
+            wtr.write_record(["cedula", "apellidos", "numero_cuenta", "monto", "motivo"])?;
+            for (fq, motivo) in &registros {
+                wtr.write_record([
+                    fq.cedula.as_str(),
+                    fq.apellidos.as_str(),
+                    fq.numero_cuenta.as_str(),
+                    &fq.monto.to_string(),
+                    motivo.as_str(),
+                ])?;
+            }
+            let datos = wtr.into_inner()?;
+            let ruta = ruta_salida(destino, &format!("patria_{}.csv", nombre));
+            std::fs::write(&ruta, &datos)?;
+            let hash = generar_hash(&datos);
+            resultados.push(ResultadoExport {
+                ruta: ruta.display().to_string(),
+                tipo: format!("patria_{}", nombre),
+                tamano_original: datos.len() as u64,
+                tamano_comprimido: None,
+                hash_sha256: Some(hash.clone()),
+                hash_sha256_original: Some(hash),
+                compresion_aplicada: false,
+            });
+        }
+        Ok(resultados)
+    }
+}
+
+fn ruta_salida(destino: &str, nombre: &str) -> PathBuf {
+    if destino == "." || destino.is_empty() {
+        PathBuf::from(nombre)
+    } else {
+        Path::new(destino).join(nombre)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::banco::cuenta::digitos_control;
+    use crate::banco::lectura;
+
+    fn fq(cedula: &str, numero_cuenta: &str, monto: f64) -> FiniquitoPatria {
+        serde_json::from_value(serde_json::json!({
+            "cedula": cedula,
+            "apellidos": "Pérez José",
+            "numero_cuenta": numero_cuenta,
+            "monto": monto,
+            "f_contable": "",
+            "observaciones": ""
+        }))
+        .unwrap()
+    }
+
+    fn ccc(banco: &str, numero: &str) -> String {
+        let dc = digitos_control(&format!("{}000100{}", banco, numero)).unwrap();
+        format!("{}0001{}{}", banco, dc, numero)
+    }
+
+    #[test]
+    fn test_patria_por_banco_con_control() {
+        let finiquitos = vec![
+            fq("V-12345678", &ccc("0102", "0000000001"), 100.25),
+            fq("E-81234567", &ccc("0163", "0000000002"), 50.0),
+            fq("12345678", &ccc("0102", "0000000003"), 10.0),
+            fq("V-7654321", &ccc("0105", "0000000004"), 5.0),
+            fq("V-7654322", "01020001990000000005", 5.0),
+            fq("V-7654323", &ccc("0102", "0000000006"), 0.0),
+            fq("V-11111111", &ccc("0102", "0000000007"), 20.104),
+        ];
+        let mut config = PatriaConfig {
+            bancos: vec!["0102".to_string(), "0163".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            generar(&finiquitos, &config, "202512", "2025-12-31").unwrap_err(),
+            "patria.rif es obligatorio"
+        );
+        config.rif = Some("J-00000000-0".to_string());
+        assert!(generar(&finiquitos, &config, "202512", "2025-12-31")
+            .unwrap_err()
+            .starts_with("patria.rif: "));
+        config.rif = Some("J-30000000-1".to_string());
+
+        // Las cédulas repetidas no se pagan: quedan solo en `duplicados`
+        let nomina = generar(&finiquitos, &config, "202512", "2025-12-31").unwrap();
+        assert_eq!(nomina.archivos.len(), 2);
+        let venezuela = &nomina.archivos[0];
+        assert_eq!(venezuela.nombre, "patria_202512_0102.txt");
+        assert_eq!((venezuela.registros, venezuela.total), (1, 20.1));
+        assert_eq!(nomina.duplicados.len(), 2);
+        assert_eq!(nomina.sin_cuenta.len(), 1);
+        assert_eq!(nomina.cuenta_invalida.len(), 1);
+        assert_eq!(nomina.negativos.len(), 1);
+        assert_eq!((nomina.registros(), nomina.total()), (2, 70.1));
+
+        config.incluir_duplicados = true;
+        let nomina = generar(&finiquitos, &config, "202512", "2025-12-31").unwrap();
+        let venezuela = &nomina.archivos[0];
+        // 100.25 + 10.00 + 20.10 tal como se escriben, no 130.354
+        assert_eq!((venezuela.registros, venezuela.total), (3, 130.35));
+        let lineas: Vec<&str> = venezuela.contenido.lines().collect();
+        assert_eq!(
+            lineas[0],
+            "ONTNOMJ300000001 0000003000000000013035VES20251231"
+        );
+        assert_eq!(lineas[4], "FINNOM0000003000000000013035");
+        assert!(nomina.archivos[1]
+            .contenido
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("E81234567"));
+        assert_eq!(nomina.duplicados.len(), 2);
+        assert_eq!((nomina.registros(), nomina.total()), (4, 180.35));
+
+        // El TXT se relee con el mismo diseño: cabecera y pie cuadran y solo se
+        // observa la cédula repetida, que también está en `duplicados`.
+        let (_, d) = lectura::diseno_para(&venezuela.nombre).unwrap();
+        let leido = lectura::leer(&d, &venezuela.contenido);
+        let v = lectura::verificar(&leido, &d, None);
+        assert_eq!(v.observaciones.len(), 1, "{:?}", v.observaciones);
+        assert!(v.observaciones[0].motivo.contains("repetida"));
+    }
+}
@@ -0,0 +1,52 @@
+//! Banco Provincial (0108): abono en cuenta de garantías (aporte) o de
+//! movimientos (retiro). Registros de ancho fijo terminados en CRLF,
+//! descritos en `schema/bancos/provincial.json`:
+//!
+//! | Registro | Campos |
+//! |----------|--------|
+//! | Cabecera `H` | código de empresa (8), fecha DDMMAA (6), lote AAAAMM del ciclo (6) |
+//! | Detalle `D`  | cuenta (20), nacionalidad (1), cédula (9), monto en céntimos (13), concepto (20) |
+//! | Pie `T`      | cantidad (6), total en céntimos (15) |
+
+use crate::banco::diseno::FormatoDeclarativo;
+
+pub const DISENO: &str = include_str!("../../../schema/bancos/provincial.json");
+
+pub fn formato() -> FormatoDeclarativo {
+    FormatoDeclarativo::desde_json(DISENO).expect("schema/bancos/provincial.json inválido")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::banco::formato::{armar_contenido, ContextoArchivo};
+    use crate::banco::tipos::TipoArchivo;
+    use crate::kernel::logica::memoria::Beneficiario;
+
+    #[test]
+    fn test_archivo_provincial_retiro() {
+        let mut b: Beneficiario =
+            serde_json::from_str(r#"{"cedula":"7654321","numero_cuenta":"01080000570000000002"}"#)
+                .unwrap();
+        b.movimientos.anticipo = 500.0;
+        b.movimientos.deposito_de_garantias = 250.25;
+
+        let mut ctx = ContextoArchivo::new(TipoArchivo::Retiro, "2026-02", 50.0);
+        ctx.fecha = "280226".to_string();
+        let (contenido, cantidad, total, _) =
+            armar_contenido(&formato(), &[b], &ctx).unwrap();
+
+        let lineas: Vec<&str> = contenido.split("\r\n").collect();
+        assert_eq!(
+            lineas,
+            vec![
+                "H00000000280226202602",
+                "D01080000570000000002V0076543210000000037513RETIRO 2026-02      ",
+                "T000001000000000037513",
+                "",
+            ]
+        );
+        // El total es el monto escrito en la línea (375.13), no 375.125
+        assert_eq!((cantidad, total), (1, 375.13));
+    }
+}
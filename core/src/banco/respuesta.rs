@@ -0,0 +1,493 @@
+//! Respuestas del banco sobre un TXT enviado (`sandra banco respuesta`).
+//!
+//! El banco devuelve cada registro con un código de estado y de motivo; el
+//! diseño de ese archivo está en `schema/bancos/respuestas/<banco>.json`. Cada
+//! registro se cruza con la línea que generamos por cédula y cuenta, y el
+//! estado de cada pago (`enviado`, `pagado`, `rechazado`, `reversado` o
+//! `desconocido` si el código no está en el diseño) se guarda
+//! en `estado_pagos_<archivo>.csv` junto al archivo, de modo que las respuestas
+//! posteriores (p. ej. un reverso) actualizan el mismo estado. Los rechazos y
+//! reversos se listan en `rechazos_<archivo>.csv` y se arma
+//! `<archivo>_reproceso.txt` con sus líneas originales y la cabecera y el pie
+//! recalculados; un código desconocido nunca se reprocesa.
+
+use crate::banco::diseno::{DisenoArchivo, DisenoRegistro, FinLinea};
+use crate::banco::lectura::{self, ArchivoLeido, Observacion, RegistroLeido};
+use crate::kernel::logica::exportador::{self, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::util::cedula;
+use rhai::Dynamic;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// Diseño de respuesta por nombre de diseño del archivo enviado.
+const RESPUESTAS: &[(&str, &str)] = &[
+    (
+        "venezuela",
+        include_str!("../../../schema/bancos/respuestas/venezuela.json"),
+    ),
+    (
+        "banfanb",
+        include_str!("../../../schema/bancos/respuestas/banfanb.json"),
+    ),
+    (
+        "bicentenario",
+        include_str!("../../../schema/bancos/respuestas/bicentenario.json"),
+    ),
+    (
+        "mercantil",
+        include_str!("../../../schema/bancos/respuestas/mercantil.json"),
+    ),
+    (
+        "provincial",
+        include_str!("../../../schema/bancos/respuestas/provincial.json"),
+    ),
+    (
+        "tesoro",
+        include_str!("../../../schema/bancos/respuestas/tesoro.json"),
+    ),
+];
+
+const CAMPO_ESTADO: &str = "estado";
+const CAMPO_MOTIVO: &str = "motivo";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisenoRespuesta {
+    pub banco: String,
+    #[serde(default)]
+    pub fin_linea: FinLinea,
+    /// Códigos de `estado` que significan pago acreditado.
+    pub aceptado: Vec<String>,
+    /// Códigos de `estado` de un pago no acreditado (va al reproceso).
+    pub rechazado: Vec<String>,
+    /// Códigos de `estado` de un pago acreditado y luego devuelto.
+    #[serde(default)]
+    pub reversado: Vec<String>,
+    /// Descripción de cada código de `motivo`.
+    #[serde(default)]
+    pub motivos: HashMap<String, String>,
+    #[serde(default)]
+    pub cabecera: Option<DisenoRegistro>,
+    /// Debe tener los campos `estado` y `motivo`; `cedula` y `cuenta` para cruzar.
+    pub detalle: DisenoRegistro,
+    #[serde(default)]
+    pub pie: Option<DisenoRegistro>,
+}
+
+impl DisenoRespuesta {
+    pub fn desde_json(json: &str) -> Result<Self, String> {
+        let diseno: Self =
+            serde_json::from_str(json).map_err(|e| format!("diseño inválido: {}", e))?;
+        for campo in [CAMPO_ESTADO, "cedula"] {
+            if !diseno.detalle.campos.iter().any(|c| c.nombre == campo) {
+                return Err(format!("el detalle no tiene campo '{}'", campo));
+            }
+        }
+        Ok(diseno)
+    }
+
+    pub fn cargar(ruta: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let contenido = std::fs::read_to_string(ruta)?;
+        Self::desde_json(&contenido).map_err(|e| format!("{}: {}", ruta, e).into())
+    }
+
+    /// Diseño de respuesta del banco de un archivo enviado (`venezuela`, `mercantil`, ...).
+    pub fn para(diseno_enviado: &str) -> Option<Self> {
+        let (_, json) = RESPUESTAS.iter().find(|(n, _)| *n == diseno_enviado)?;
+        Self::desde_json(json).ok()
+    }
+
+    pub fn leer(&self, contenido: &str) -> ArchivoLeido {
+        lectura::leer_lineas(
+            self.cabecera.as_ref(),
+            &self.detalle,
+            self.pie.as_ref(),
+            self.fin_linea,
+            contenido,
+        )
+    }
+
+    fn estado(&self, codigo: &str) -> EstadoPago {
+        if self.aceptado.iter().any(|c| c == codigo) {
+            EstadoPago::Pagado
+        } else if self.reversado.iter().any(|c| c == codigo) {
+            EstadoPago::Reversado
+        } else if self.rechazado.iter().any(|c| c == codigo) {
+            EstadoPago::Rechazado
+        } else {
+            EstadoPago::Desconocido
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EstadoPago {
+    Enviado,
+    Pagado,
+    Rechazado,
+    Reversado,
+    /// Código de estado que el diseño no clasifica; se revisa a mano.
+    Desconocido,
+}
+
+impl EstadoPago {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstadoPago::Enviado => "enviado",
+            EstadoPago::Pagado => "pagado",
+            EstadoPago::Rechazado => "rechazado",
+            EstadoPago::Reversado => "reversado",
+            EstadoPago::Desconocido => "desconocido",
+        }
+    }
+
+    /// Pagos que deben volver a enviarse.
+    pub fn reprocesar(&self) -> bool {
+        matches!(self, EstadoPago::Rechazado | EstadoPago::Reversado)
+    }
+}
+
+/// Una línea de detalle del archivo enviado y su estado.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pago {
+    /// Línea en el archivo enviado.
+    pub linea: usize,
+    pub cedula: String,
+    pub cuenta: String,
+    pub monto: f64,
+    pub estado: EstadoPago,
+    pub codigo: String,
+    pub motivo: String,
+}
+
+impl Pago {
+    fn clave(&self) -> (String, String) {
+        let cedula = cedula::clave(&self.cedula).unwrap_or_else(|| self.cedula.clone());
+        (cedula, normalizar_cuenta(&self.cuenta))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RespuestaBanco {
+    pub archivo: String,
+    pub respuesta: String,
+    pub pagos: Vec<Pago>,
+    /// Registros de la respuesta sin línea enviada que les corresponda.
+    pub sin_coincidencia: Vec<Observacion>,
+}
+
+impl RespuestaBanco {
+    pub fn por_estado(&self) -> Vec<(EstadoPago, usize, f64)> {
+        [
+            EstadoPago::Enviado,
+            EstadoPago::Pagado,
+            EstadoPago::Rechazado,
+            EstadoPago::Reversado,
+            EstadoPago::Desconocido,
+        ]
+        .into_iter()
+        .map(|estado| {
+            let pagos = self.pagos.iter().filter(|p| p.estado == estado);
+            let (n, total) = pagos.fold((0, 0.0), |(n, t), p| (n + 1, t + p.monto));
+            (estado, n, total)
+        })
+        .collect()
+    }
+
+    pub fn a_reprocesar(&self) -> impl Iterator<Item = &Pago> {
+        self.pagos.iter().filter(|p| p.estado.reprocesar())
+    }
+
+    /// Escribe el estado de los pagos, el reporte de rechazos y, si hay pagos a
+    /// reprocesar, el TXT de reproceso.
+    pub fn exportar(
+        &self,
+        enviado: &ArchivoLeido,
+        diseno: &DisenoArchivo,
+        destino: &str,
+    ) -> Result<Vec<ResultadoExport>, Box<dyn std::error::Error>> {
+        let base = Path::new(&self.archivo)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let base = base.trim_end_matches(".zst").trim_end_matches(".txt");
+        let mut resultados = Vec::new();
+
+        let ruta = ruta_salida(destino, &ruta_estado(base));
+        resultados.push(escribir_pagos(&ruta, self.pagos.iter(), "estado_pagos")?);
+
+        let ruta = ruta_salida(destino, &format!("rechazos_{}.csv", base));
+        resultados.push(escribir_pagos(&ruta, self.a_reprocesar(), "rechazos")?);
+
+        if self.a_reprocesar().next().is_some() {
+            let ruta = ruta_salida(destino, &format!("{}_reproceso.txt", base));
+            let contenido = self.reproceso(enviado, diseno)?;
+            let datos = contenido.into_bytes();
+            std::fs::write(&ruta, &datos)?;
+            resultados.push(ResultadoExport {
+                ruta: ruta.display().to_string(),
+                tipo: "reproceso".to_string(),
+                tamano_original: datos.len() as u64,
+                tamano_comprimido: None,
+                hash_sha256: None,
+                hash_sha256_original: Some(exportador::generar_hash(&datos)),
+                compresion_aplicada: false,
+            });
+        }
+
+        logger::log_info(
+            "EXPORT",
+            &format!(
+                "Respuesta {}: {} pagos, {} a reprocesar",
+                self.respuesta,
+                self.pagos.len(),
+                self.a_reprocesar().count()
+            ),
+        );
+        Ok(resultados)
+    }
+
+    /// Archivo con las líneas originales de los pagos a reprocesar, con la
+    /// cantidad y el total de cabecera y pie recalculados.
+    pub fn reproceso(
+        &self,
+        enviado: &ArchivoLeido,
+        diseno: &DisenoArchivo,
+    ) -> Result<String, String> {
+        let lineas: Vec<&RegistroLeido> = self
+            .a_reprocesar()
+            .filter_map(|p| enviado.detalle.iter().find(|r| r.linea == p.linea))
+            .collect();
+        let cantidad = lineas.len();
+        let total: f64 = lineas.iter().filter_map(|r| r.numero("monto")).sum();
+        let fin = diseno.fin_linea.as_str();
+
+        let control = |registro: &Option<DisenoRegistro>, leido: &Option<RegistroLeido>| {
+            let (Some(registro), Some(leido)) = (registro, leido) else {
+                return Ok(None);
+            };
+            let mut linea = leido.texto.clone();
+            if registro.campos.iter().any(|c| c.nombre == "cantidad") {
+                linea = registro.reemplazar(&linea, "cantidad", Dynamic::from(cantidad as i64))?;
+            }
+            if registro.campos.iter().any(|c| c.nombre == "total") {
+                linea = registro.reemplazar(&linea, "total", Dynamic::from(total))?;
+            }
+            Ok::<_, String>(Some(linea))
+        };
+
+        let mut contenido = String::new();
+        if let Some(cabecera) = control(&diseno.cabecera, &enviado.cabecera)? {
+            contenido.push_str(&cabecera);
+            contenido.push_str(fin);
+        }
+        for r in lineas {
+            contenido.push_str(&r.texto);
+            contenido.push_str(fin);
+        }
+        if let Some(pie) = control(&diseno.pie, &enviado.pie)? {
+            contenido.push_str(&pie);
+            contenido.push_str(fin);
+        }
+        Ok(contenido)
+    }
+}
+
+/// Pagos del archivo enviado, todos `enviado`.
+pub fn pagos_enviados(enviado: &ArchivoLeido) -> Vec<Pago> {
+    enviado
+        .detalle
+        .iter()
+        .map(|r| Pago {
+            linea: r.linea,
+            cedula: r
+                .cedula()
+                .unwrap_or_else(|| r.texto("cedula").unwrap_or_default().to_string()),
+            cuenta: r.texto("cuenta").unwrap_or_default().to_string(),
+            monto: r.numero("monto").unwrap_or(0.0),
+            estado: EstadoPago::Enviado,
+            codigo: String::new(),
+            motivo: String::new(),
+        })
+        .collect()
+}
+
+/// Aplica la respuesta del banco sobre los pagos (los enviados o el estado
+/// guardado de una respuesta anterior).
+pub fn aplicar(
+    mut pagos: Vec<Pago>,
+    respuesta: &ArchivoLeido,
+    diseno: &DisenoRespuesta,
+) -> RespuestaBanco {
+    let con_cuenta = diseno.detalle.campos.iter().any(|c| c.nombre == "cuenta");
+    // Líneas enviadas por clave, en orden: las filas repetidas de la respuesta
+    // se cruzan con la siguiente línea de la misma clave
+    let mut indice: HashMap<(String, String), VecDeque<usize>> = HashMap::new();
+    for (i, p) in pagos.iter().enumerate() {
+        let (cedula, cuenta) = p.clave();
+        let clave = if con_cuenta {
+            (cedula, cuenta)
+        } else {
+            (cedula, String::new())
+        };
+        indice.entry(clave).or_default().push_back(i);
+    }
+
+    let mut sin_coincidencia = respuesta.errores.clone();
+    for r in &respuesta.detalle {
+        let cedula = r
+            .cedula()
+            .unwrap_or_else(|| r.texto("cedula").unwrap_or_default().to_string());
+        let cuenta = normalizar_cuenta(r.texto("cuenta").unwrap_or_default());
+        let Some(lineas) = indice.get_mut(&(cedula.clone(), cuenta)) else {
+            sin_coincidencia.push(Observacion {
+                linea: r.linea,
+                cedula: Some(cedula),
+                motivo: "sin línea enviada con esa cédula y cuenta".to_string(),
+            });
+            continue;
+        };
+        let Some(i) = lineas.pop_front() else {
+            sin_coincidencia.push(Observacion {
+                linea: r.linea,
+                cedula: Some(cedula),
+                motivo: "fila repetida: ya se cruzaron todas las líneas enviadas con esa cédula y cuenta"
+                    .to_string(),
+            });
+            continue;
+        };
+        let codigo_estado = r.texto(CAMPO_ESTADO).unwrap_or_default();
+        let codigo = r.texto(CAMPO_MOTIVO).unwrap_or_default().to_string();
+        let pago = &mut pagos[i];
+        pago.estado = diseno.estado(codigo_estado);
+        pago.motivo = match pago.estado {
+            EstadoPago::Pagado => String::new(),
+            EstadoPago::Desconocido => format!("código de estado {} desconocido", codigo_estado),
+            _ => diseno.motivos.get(&codigo).cloned().unwrap_or_else(|| {
+                format!("código {} {}", codigo_estado, codigo)
+                    .trim()
+                    .to_string()
+            }),
+        };
+        pago.codigo = codigo;
+    }
+
+    RespuestaBanco {
+        archivo: String::new(),
+        respuesta: String::new(),
+        pagos,
+        sin_coincidencia,
+    }
+}
+
+/// Estado guardado por una respuesta anterior del archivo `enviado`, si existe.
+pub fn cargar_estado(
+    destino: &str,
+    enviado: &str,
+) -> Result<Option<Vec<Pago>>, Box<dyn std::error::Error>> {
+    let base = Path::new(enviado)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let base = base.trim_end_matches(".zst").trim_end_matches(".txt");
+    let ruta = ruta_salida(destino, &ruta_estado(base));
+    if !ruta.exists() {
+        return Ok(None);
+    }
+    let mut rdr = csv::ReaderBuilder::new().delimiter(b';').from_path(&ruta)?;
+    let pagos = rdr.deserialize().collect::<Result<Vec<Pago>, _>>()?;
+    Ok(Some(pagos))
+}
+
+fn ruta_estado(base: &str) -> String {
+    format!("estado_pagos_{}.csv", base)
+}
+
+fn ruta_salida(destino: &str, nombre: &str) -> PathBuf {
+    if destino == "." || destino.is_empty() {
+        PathBuf::from(nombre)
+    } else {
+        PathBuf::from(destino).join(nombre)
+    }
+}
+
+fn escribir_pagos<'a>(
+    ruta: &Path,
+    pagos: impl Iterator<Item = &'a Pago>,
+    tipo: &str,
+) -> Result<ResultadoExport, Box<dyn std::error::Error>> {
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(b';')
+        .has_headers(false)
+        .from_writer(vec![]);
+    wtr.write_record([
+        "linea", "cedula", "cuenta", "monto", "estado", "codigo", "motivo",
+    ])?;
+    for p in pagos {
+        wtr.serialize(p)?;
+    }
+    let datos = wtr.into_inner()?;
+    std::fs::write(ruta, &datos)?;
+    Ok(ResultadoExport {
+        ruta: ruta.display().to_string(),
+        tipo: tipo.to_string(),
+        tamano_original: datos.len() as u64,
+        tamano_comprimido: None,
+        hash_sha256: None,
+        hash_sha256_original: Some(exportador::generar_hash(&datos)),
+        compresion_aplicada: false,
+    })
+}
+
+/// Cuenta con solo sus dígitos, para cruzar archivos.
+fn normalizar_cuenta(valor: &str) -> String {
+    valor.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_respuesta_y_reproceso_banfanb() {
+        let enviado = "0131010226000000000000001000002\r\n\
+                       013100000000600001770000000000000001000000012300000000\r\n\
+                       013100000000400001770000000000000002000000045600000000\r\n\
+                       013100000000100001770000000000000003000000032100000000\r\n";
+        let (_, diseno) = lectura::diseno_para("banfanb_2026-02.txt").unwrap();
+        let leido = lectura::leer(&diseno, enviado);
+        let respuesta_diseno = DisenoRespuesta::para("banfanb").unwrap();
+
+        let respuesta = "01310000000060000177000000000000000100000001230000000000000\r\n\
+                         01310000000040000177000000000000000200000004560000000001001\r\n\
+                         01310000000040000177000000000000000900000007890000000000000\r\n\
+                         013100000000100001770000000000000003000000032100000000PE000\r\n\
+                         01310000000060000177000000000000000100000001230000000000000\r\n";
+        let r = aplicar(
+            pagos_enviados(&leido),
+            &respuesta_diseno.leer(respuesta),
+            &respuesta_diseno,
+        );
+        assert_eq!(r.pagos[0].estado, EstadoPago::Pagado);
+        assert_eq!(r.pagos[1].estado, EstadoPago::Rechazado);
+        assert_eq!(r.pagos[1].motivo, "Cuenta inexistente");
+        // Un código que el diseño no clasifica no se reprocesa
+        assert_eq!(r.pagos[2].estado, EstadoPago::Desconocido);
+        assert_eq!(r.pagos[2].motivo, "código de estado PE desconocido");
+        assert_eq!(r.sin_coincidencia.len(), 2);
+        assert_eq!(r.sin_coincidencia[0].cedula.as_deref(), Some("V-789"));
+        assert!(r.sin_coincidencia[1].motivo.starts_with("fila repetida"));
+
+        // Un reverso posterior sobre el estado guardado
+        let reverso = "013100000000600001770000000000000001000000012300000000RV099\r\n";
+        let r = aplicar(r.pagos, &respuesta_diseno.leer(reverso), &respuesta_diseno);
+        assert_eq!(r.pagos[0].estado, EstadoPago::Reversado);
+        assert_eq!(r.a_reprocesar().count(), 2);
+
+        let reproceso = r.reproceso(&leido, &diseno).unwrap();
+        assert_eq!(reproceso.lines().count(), 3);
+        assert!(reproceso.starts_with("0131010226000000000000001000002\r\n"));
+    }
+}
@@ -0,0 +1,59 @@
+//! Banco del Tesoro (0163): abono en cuenta de garantías (aporte) o de
+//! movimientos (retiro). Registros de ancho fijo terminados en CRLF,
+//! descritos en `schema/bancos/tesoro.json`:
+//!
+//! | Registro | Campos |
+//! |----------|--------|
+//! | Cabecera `01` | RIF empresa `salida.rif` (10), fecha DDMMAA (6), operación `A` aporte / `R` retiro (1), cantidad (6), total en céntimos (15) |
+//! | Detalle `02`  | nacionalidad (1), cédula (10), cuenta (20), monto en céntimos (15), nombre (30) |
+//! | Pie `03`      | cantidad (6), total en céntimos (15) |
+
+use crate::banco::diseno::FormatoDeclarativo;
+
+pub const DISENO: &str = include_str!("../../../schema/bancos/tesoro.json");
+
+pub fn formato() -> FormatoDeclarativo {
+    FormatoDeclarativo::desde_json(DISENO).expect("schema/bancos/tesoro.json inválido")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::banco::formato::{armar_contenido, ContextoArchivo};
+    use crate::banco::tipos::TipoArchivo;
+    use crate::kernel::logica::memoria::Beneficiario;
+
+    #[test]
+    fn test_totales_de_control_tesoro() {
+        // 100.104 + 200.204 se escriben 100.10 + 200.20: el total es 300.30, no 300.31
+        let beneficiarios: Vec<Beneficiario> = [
+            ("1", "01630000490000000001", 100.104),
+            ("2", "01630000470000000002", 200.204),
+            ("3", "01630000450000000003", 0.0),
+        ]
+        .iter()
+        .map(|(cedula, cuenta, garantias)| {
+            let mut b: Beneficiario = serde_json::from_str(&format!(
+                r#"{{"cedula":"{}","numero_cuenta":"{}"}}"#,
+                cedula, cuenta
+            ))
+            .unwrap();
+            b.base.garantias = *garantias;
+            b
+        })
+        .collect();
+
+        let mut ctx = ContextoArchivo::new(TipoArchivo::Aporte, "2026-02", 100.0);
+        ctx.fecha = "280226".to_string();
+        ctx.rif = Some("G200000001".to_string());
+        let (contenido, cantidad, total, _) =
+            armar_contenido(&formato(), &beneficiarios, &ctx).unwrap();
+
+        let lineas: Vec<&str> = contenido.lines().collect();
+        assert_eq!((cantidad, total), (2, 300.30));
+        assert_eq!(lineas.len(), 4);
+        assert_eq!(lineas[0], "01G200000001280226A000002000000000030030");
+        assert_eq!(lineas[3], "03000002000000000030030");
+        assert!(lineas[1..3].iter().all(|l| l.len() == 78));
+    }
+}
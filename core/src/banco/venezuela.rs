@@ -1,282 +1,107 @@
-use crate::banco::tipos::{CampoBanco, TipoArchivo};
-use crate::kernel::logica::exportador::{comprimir_y_sellar, generar_hash, ResultadoExport};
+//! Banco de Venezuela (0102): archivos APERT/APORT/RETIR/MIXTO del plan 03487,
+//! sin cabecera ni pie. Las líneas de aporte, retiro y mixto se describen en
+//! `schema/bancos/venezuela.json` y las de apertura en
+//! `schema/bancos/venezuela_apertura.json`.
+
+use crate::banco::diseno::FormatoDeclarativo;
+use crate::banco::formato::{BancoFormato, ContextoArchivo, LineaDetalle};
+use crate::banco::tipos::{Banco, TipoArchivo};
 use crate::kernel::logica::memoria::Beneficiario;
-use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
 
-const PLAN_VENEZUELA: &str = "03487";
+/// Diseño de las líneas de aporte y retiro (también las de mixto).
+pub const DISENO: &str = include_str!("../../../schema/bancos/venezuela.json");
 
-pub fn generar_linea_apertura(b: &Beneficiario, porcentaje: f64) -> String {
-    let mut campo = CampoBanco::default();
-    campo.plan = PLAN_VENEZUELA.to_string();
-    campo.nac = "V".to_string();
-    campo.cedula = pad_left(&b.cedula.replace(|c: char| !c.is_ascii_digit(), ""), 9, '0');
+/// Diseño de las líneas de apertura.
+pub const DISENO_APERTURA: &str = include_str!("../../../schema/bancos/venezuela_apertura.json");
 
-    let nombre_completo = format!("{} {}", b.nombres.clone(), b.apellidos.clone());
-    campo.nombre = Some(pad_right(&nombre_completo, 60, ' '));
-    campo.edocivil = Some("S".to_string());
-
-    let m = &b.movimientos;
-    let monto_raw = (m.finiquito_capital_banco + m.anticipo + m.deposito_de_dias_adicionales + m.deposito_de_garantias)
-        * porcentaje
-        / 100.0;
-    campo.monto = monto_raw;
-
-    let monto_str = pad_left(&remove_decimal(monto_raw), 13, '0');
-    let nombre_str = campo.nombre.as_deref().unwrap_or("");
-    let edocivil_str = campo.edocivil.as_deref().unwrap_or("S");
-
-    format!(
-        "{}{}{}{}{}{}{}",
-        campo.plan,
-        campo.nac,
-        campo.cedula,
-        nombre_str,
-        edocivil_str,
-        "00000000000000000000000",
-        monto_str
-    )
+/// Banco de Venezuela: apertura incluye a quienes no tienen movimientos; aporte
+/// y retiro a quienes sí; mixto aporta a unos y retira a los otros.
+pub struct FormatoVenezuela {
+    movimientos: FormatoDeclarativo,
+    apertura: FormatoDeclarativo,
 }
 
-pub fn generar_linea_aporte(b: &Beneficiario, porcentaje: f64) -> String {
-    let m = &b.movimientos;
-    let monto_raw = (m.finiquito_capital_banco + m.anticipo + m.deposito_de_dias_adicionales + m.deposito_de_garantias)
-        * porcentaje
-        / 100.0;
-    let monto_str = pad_left(&remove_decimal(monto_raw), 13, '0');
-    let cedula = pad_left(&b.cedula.replace(|c: char| !c.is_ascii_digit(), ""), 9, '0');
-
-    format!(
-        "{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
-        PLAN_VENEZUELA,
-        "V",
-        cedula,
-        "1",
-        "00",
-        "0",
-        monto_str,
-        "N",
-        "0",
-        "0000000000",
-        "000000",
-        " ",
-        "000",
-        "0000000000000",
-        "000",
-        ""
-    )
-}
-
-pub fn generar_linea_retiro(b: &Beneficiario, porcentaje: f64) -> String {
-    let m = &b.movimientos;
-    let monto_raw = (m.finiquito_capital_banco + m.anticipo + m.deposito_de_dias_adicionales + m.deposito_de_garantias)
-        * porcentaje
-        / 100.0;
-    let monto_str = pad_left(&remove_decimal(monto_raw), 13, '0');
-    let cedula = pad_left(&b.cedula.replace(|c: char| !c.is_ascii_digit(), ""), 9, '0');
-
-    format!(
-        "{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
-        PLAN_VENEZUELA,
-        "V",
-        cedula,
-        "3",
-        "00",
-        "A",
-        monto_str,
-        " ",
-        "0",
-        "0000000000",
-        "000000",
-        " ",
-        "000",
-        "0000000000000",
-        "000",
-        ""
-    )
+pub fn formato() -> FormatoVenezuela {
+    FormatoVenezuela {
+        movimientos: FormatoDeclarativo::desde_json(DISENO)
+            .expect("schema/bancos/venezuela.json inválido"),
+        apertura: FormatoDeclarativo::desde_json(DISENO_APERTURA)
+            .expect("schema/bancos/venezuela_apertura.json inválido"),
+    }
 }
 
-pub fn generar_txt_venezuela(
-    beneficiarios: &[Beneficiario],
-    tipo: TipoArchivo,
-    ciclo: &str,
-    destino: &str,
-    porcentaje: f64,
-    comprimir: bool,
-    nivel_compresion: i32,
-) -> Result<ResultadoExport, Box<dyn std::error::Error>> {
-    let prefijo = match tipo {
-        TipoArchivo::Apertura => "APERT",
-        TipoArchivo::Aporte => "APORT",
-        TipoArchivo::Retiro => "RETIR",
-        TipoArchivo::Mixto => "MIXTO",
-    };
-
-    let nombre_archivo = format!("{}{}.txt", prefijo, ciclo);
-    let ruta = if destino == "." || destino.is_empty() {
-        PathBuf::from(&nombre_archivo)
-    } else {
-        PathBuf::from(destino).join(&nombre_archivo)
-    };
-
-    println!(
-        "> Generando TXT {} en '{}' ({} registros, {}% monto)...",
-        prefijo,
-        ruta.display(),
-        beneficiarios.len(),
-        porcentaje
-    );
-
-    let mut archivo = File::create(&ruta)?;
-    let mut cantidad = 0;
-    let mut suma_total = 0.0;
-
-    for b in beneficiarios {
-        let m = &b.movimientos;
-        let tiene_mov =
-            m.finiquito_capital_banco + m.anticipo + m.deposito_de_dias_adicionales + m.deposito_de_garantias > 0.0;
-        let monto_mov = m.finiquito_capital_banco + m.anticipo + m.deposito_de_dias_adicionales + m.deposito_de_garantias;
-
-        let linea = match tipo {
-            TipoArchivo::Apertura => {
-                if !tiene_mov {
-                    generar_linea_apertura(b, porcentaje)
-                } else {
-                    String::new()
-                }
-            }
-            TipoArchivo::Aporte => {
-                if tiene_mov {
-                    generar_linea_aporte(b, porcentaje)
-                } else {
-                    String::new()
-                }
-            }
-            TipoArchivo::Retiro => {
-                if tiene_mov {
-                    generar_linea_retiro(b, porcentaje)
-                } else {
-                    String::new()
-                }
-            }
-            TipoArchivo::Mixto => {
-                if tiene_mov {
-                    generar_linea_aporte(b, porcentaje)
-                } else {
-                    generar_linea_retiro(b, porcentaje)
-                }
-            }
-        };
-
-        if !linea.is_empty() {
-            writeln!(archivo, "{}", linea)?;
-            cantidad += 1;
-            suma_total += monto_mov * porcentaje / 100.0;
+impl FormatoVenezuela {
+    fn diseno(&self, tipo: TipoArchivo) -> &FormatoDeclarativo {
+        match tipo {
+            TipoArchivo::Apertura => &self.apertura,
+            _ => &self.movimientos,
         }
     }
+}
 
-    archivo.flush()?;
-    archivo.sync_all()?;
-
-    let datos = std::fs::read(&ruta)?;
-    let tamano_original = datos.len() as u64;
-    let hash_original = generar_hash(&datos);
-
-    let tipo_str = match tipo {
-        TipoArchivo::Apertura => "apertura",
-        TipoArchivo::Aporte => "aporte",
-        TipoArchivo::Retiro => "retiro",
-        TipoArchivo::Mixto => "mixto",
-    };
-
-    if comprimir {
-        println!(
-            "    > Comprimiendo archivo {} con zstd (nivel {})...",
-            prefijo, nivel_compresion
-        );
-
-        let (comprimido, hash) = comprimir_y_sellar(&datos, nivel_compresion);
-
-        let ruta_zst = ruta.with_extension("txt.zst");
-        let mut archivo_zst = File::create(&ruta_zst)?;
-        archivo_zst.write_all(&comprimido)?;
-
-        std::fs::remove_file(&ruta)?;
-
-        let tamano_comprimido = comprimido.len() as u64;
-        let nombre_final = ruta_zst
-            .file_name()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_default();
-
-        println!(
-            "    > {} generado: Original: {} bytes, Comprimido: {} bytes, Hash: {}",
-            prefijo,
-            tamano_original,
-            tamano_comprimido,
-            &hash[..16]
-        );
-
-        Ok(ResultadoExport {
-            ruta: nombre_final,
-            tipo: tipo_str.to_string(),
-            tamano_original,
-            tamano_comprimido: Some(tamano_comprimido),
-            hash_sha256: Some(hash),
-            hash_sha256_original: Some(hash_original),
-            compresion_aplicada: true,
-        })
-    } else {
-        println!(
-            "    > {} generado: {} bytes, {} registros, Total: {:.2}, Hash: {}",
-            prefijo,
-            tamano_original,
-            cantidad,
-            suma_total,
-            &hash_original[..16]
-        );
-
-        Ok(ResultadoExport {
-            ruta: ruta.display().to_string(),
-            tipo: tipo_str.to_string(),
-            tamano_original,
-            tamano_comprimido: None,
-            hash_sha256: Some(hash_original),
-            hash_sha256_original: None,
-            compresion_aplicada: false,
-        })
+impl BancoFormato for FormatoVenezuela {
+    fn banco(&self) -> Banco {
+        Banco::Venezuela
     }
-}
 
-fn pad_left(input: &str, target_len: usize, pad_char: char) -> String {
-    let len = input.len();
-    if len >= target_len {
-        return input[..target_len].to_string();
+    fn nombre_archivo(&self, ctx: &ContextoArchivo) -> String {
+        self.diseno(ctx.tipo).nombre_archivo(ctx)
     }
-    let pad_len = target_len - len;
-    let mut result = String::with_capacity(target_len);
-    for _ in 0..pad_len {
-        result.push(pad_char);
+
+    fn tipo_export(&self, ctx: &ContextoArchivo) -> String {
+        self.diseno(ctx.tipo).tipo_export(ctx)
     }
-    result.push_str(input);
-    result
-}
 
-fn pad_right(input: &str, target_len: usize, pad_char: char) -> String {
-    let len = input.len();
-    if len >= target_len {
-        return input[..target_len].to_string();
+    fn detalle(
+        &self,
+        b: &Beneficiario,
+        ctx: &ContextoArchivo,
+    ) -> Result<Option<LineaDetalle>, String> {
+        self.diseno(ctx.tipo).detalle(b, ctx)
     }
-    let pad_len = target_len - len;
-    let mut result = String::with_capacity(target_len);
-    result.push_str(input);
-    for _ in 0..pad_len {
-        result.push(pad_char);
+
+    fn soporta(&self, tipo: TipoArchivo) -> bool {
+        self.diseno(tipo).soporta(tipo)
     }
-    result
 }
 
-fn remove_decimal(value: f64) -> String {
-    (value * 100.0).round().to_string()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::banco::formato::armar_contenido;
+
+    #[test]
+    fn test_archivos_venezuela() {
+        let mut con_mov: Beneficiario =
+            serde_json::from_str(r#"{"cedula":"E-81234","nombres":"Ana","apellidos":"Peña"}"#)
+                .unwrap();
+        con_mov.movimientos.anticipo = 150.25;
+        let sin_mov: Beneficiario =
+            serde_json::from_str(r#"{"cedula":"V-123","nombres":"Luis","apellidos":"Díaz"}"#)
+                .unwrap();
+        let beneficiarios = [con_mov, sin_mov];
+        let f = formato();
+
+        let ctx = ContextoArchivo::new(TipoArchivo::Aporte, "2026-02", 100.0);
+        assert_eq!(f.nombre_archivo(&ctx), "APORT2026-02.txt");
+        assert_eq!(f.tipo_export(&ctx), "aporte");
+        let (contenido, cantidad, total, _) = armar_contenido(&f, &beneficiarios, &ctx).unwrap();
+        assert_eq!((cantidad, total), (1, 150.25));
+        assert_eq!(
+            contenido,
+            "03487E00008123410000000000015025N00000000000000000 0000000000000000000\n"
+        );
+
+        let ctx = ContextoArchivo::new(TipoArchivo::Mixto, "2026-02", 100.0);
+        let (contenido, cantidad, _, _) = armar_contenido(&f, &beneficiarios, &ctx).unwrap();
+        assert_eq!(cantidad, 2);
+        assert_eq!(&contenido[71..87], "03487V0000001233");
+
+        let ctx = ContextoArchivo::new(TipoArchivo::Apertura, "2026-02", 100.0);
+        assert_eq!(f.nombre_archivo(&ctx), "APERT2026-02.txt");
+        let (contenido, cantidad, _, _) = armar_contenido(&f, &beneficiarios, &ctx).unwrap();
+        assert_eq!(cantidad, 1);
+        assert_eq!(contenido.trim_end().chars().count(), 112);
+        assert!(contenido.starts_with("03487V000000123Luis Díaz"));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::calc::distribucion::DistribucionGarantias;
 use crate::kernel::logica::memoria::{Base, Movimiento};
 use chrono::{Datelike, NaiveDate};
 
@@ -88,42 +89,21 @@ pub fn generar_calculos(
     }
 }
 
-/// Algoritmo de distribución exacta para evitar errores de punto flotante
-/// El último registro absorbe la diferencia para cuadrar exactamente el monto aprobado
+/// Distribuye el monto aprobado por resto mayor (ver `calc::distribucion`).
+/// La Base no trae cédula: el desempate usa `patterns`.
 fn aplicar_distribucion_exacta(bases: &mut [Base], monto_aprobado: f64) {
-    // Calcular suma total de garantías originales
-    let suma_garantias: f64 = bases.iter().map(|b| b.garantia_original).sum();
-
-    if suma_garantias == 0.0 {
+    let registros: Vec<(&str, f64)> = bases
+        .iter()
+        .map(|b| (b.patterns.as_str(), b.garantia_original))
+        .collect();
+    let Some(distribucion) = DistribucionGarantias::calcular(&registros, monto_aprobado) else {
         return;
-    }
-
-    // Factor global: monto_aprobado / suma_total_garantias
-    let factor_global = monto_aprobado / suma_garantias;
-
-    // Convertir a multiplicado para evitar errores de punto flotante
-    let monto_aprobado_multiplicado = (monto_aprobado * 100.0).round() as i64;
-
-    let mut acumulado: i64 = 0;
-    let n = bases.len();
-
-    for (i, base) in bases.iter_mut().enumerate() {
-        // Calcular anticipo basado en factor global
-        let anticipo_calculado = base.garantia_original * factor_global;
-        let anticipo_multiplicado = (anticipo_calculado * 100.0).round() as i64;
-
-        if i < n - 1 {
-            // Primeros N-1: truncado a multiplicado (redondeo hacia abajo)
-            base.garantia_anticipo = anticipo_multiplicado as f64 / 100.0;
-            acumulado += anticipo_multiplicado;
-        } else {
-            // Último registro: cuadra exactamente el monto aprobado
-            let anticipo_final_multiplicado = monto_aprobado_multiplicado - acumulado;
-            base.garantia_anticipo = anticipo_final_multiplicado as f64 / 100.0;
-        }
+    };
 
+    for (base, p) in bases.iter_mut().zip(&distribucion.participaciones) {
+        base.garantia_anticipo = p.cuota_redondeada;
         // Guardar factor global aplicado (para referencia/auditoría)
-        base.factor_aplicado = factor_global;
+        base.factor_aplicado = distribucion.factor;
     }
 }
 
@@ -139,10 +119,11 @@ fn parsear_fecha(fecha: &str) -> Option<NaiveDate> {
     NaiveDate::parse_from_str(limpia, "%Y-%m-%d").ok()
 }
 
-fn calcular_alicuota_aguinaldo(sueldo_mensual: f64, f_retiro: &str) -> f64 {
+/// Días de aguinaldo que corresponden según la fecha de retiro (90, 105 o 120).
+pub(crate) fn dias_aguinaldo(f_retiro: &str) -> u32 {
     let f_retiro_date = parsear_fecha(f_retiro);
 
-    let dias = if f_retiro_date.is_none() {
+    if f_retiro_date.is_none() {
         120
     } else if let Some(fr) = f_retiro_date {
         let anio_retiro = fr.year();
@@ -155,7 +136,11 @@ fn calcular_alicuota_aguinaldo(sueldo_mensual: f64, f_retiro: &str) -> f64 {
         }
     } else {
         120
-    };
+    }
+}
+
+fn calcular_alicuota_aguinaldo(sueldo_mensual: f64, f_retiro: &str) -> f64 {
+    let dias = dias_aguinaldo(f_retiro);
 
     // Orden de operaciones alineado con PHP: (sueldo / 30) * (dias / 12)
     // Evita diferencias de 0.01 por punto flotante vs ((dias * sueldo) / 30) / 12
@@ -383,42 +368,21 @@ mod tests {
 pub fn generar_calculos_beneficiarios(
     beneficiarios: &mut [crate::kernel::logica::memoria::Beneficiario],
     monto_aprobado: f64,
-) {
-    // Extraer las bases de los beneficiarios para calcular suma total
-    let suma_garantias: f64 = beneficiarios.iter().map(|b| b.base.garantia_original).sum();
-
-    if suma_garantias == 0.0 {
-        return;
-    }
-
-    // Factor global
-    let factor_global = monto_aprobado / suma_garantias;
+) -> Option<DistribucionGarantias> {
+    let registros: Vec<(&str, f64)> = beneficiarios
+        .iter()
+        .map(|b| (b.cedula.as_str(), b.base.garantia_original))
+        .collect();
+    let distribucion = DistribucionGarantias::calcular(&registros, monto_aprobado)?;
 
     println!(
         "    > DISTRIBUCION: monto_aprobado={}, suma_garantias={}, factor={}",
-        monto_aprobado, suma_garantias, factor_global
+        monto_aprobado, distribucion.suma_garantias, distribucion.factor
     );
 
-    // Convertir a multiplicado
-    let monto_aprobado_multiplicado = (monto_aprobado * 100.0).round() as i64;
-
-    let mut acumulado: i64 = 0;
-    let n = beneficiarios.len();
-
-    for (i, ben) in beneficiarios.iter_mut().enumerate() {
-        let garantia_original = ben.base.garantia_original;
-        let anticipo_calculado = garantia_original * factor_global;
-        let anticipo_multiplicado = (anticipo_calculado * 100.0).round() as i64;
-
-        if i < n - 1 {
-            ben.base.garantia_anticipo = anticipo_multiplicado as f64 / 100.0;
-            acumulado += anticipo_multiplicado;
-        } else {
-            // Último: cuadra exacto
-            let anticipo_final_multiplicado = monto_aprobado_multiplicado - acumulado;
-            ben.base.garantia_anticipo = anticipo_final_multiplicado as f64 / 100.0;
-        }
-
-        ben.base.factor_aplicado = factor_global;
+    for (ben, p) in beneficiarios.iter_mut().zip(&distribucion.participaciones) {
+        ben.base.garantia_anticipo = p.cuota_redondeada;
+        ben.base.factor_aplicado = distribucion.factor;
     }
+    Some(distribucion)
 }
@@ -0,0 +1,216 @@
+//! Casos de prueba "golden" declarados en el manifiesto (o en un archivo acompañante).
+//!
+//! Cada caso define campos de entrada de la Base y los valores esperados de primas
+//! y/o conceptos. Se verifican justo después de compilar las fórmulas; si algún
+//! valor difiere más allá de la tolerancia el ciclo no continúa.
+//!
+//! La Base del caso pasa por el mismo camino que en el ciclo (`preparar_base`):
+//! tiempo de servicio y sueldo de la directiva, primas y `generar_calculos`; un
+//! cambio en la directiva hace fallar los casos que dependen de ella.
+
+use crate::calc::calculos::{generar_calculos, redondear_dos};
+use crate::calc::explicacion::TrazaFormula;
+use crate::calc::motor::SentinelEngine;
+use crate::calc::procesar_registro_base;
+use crate::kernel::logica::memoria::{Base, Directiva};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CasoPrueba {
+    pub nombre: String,
+
+    /// Campos de entrada de la Base (los omitidos toman su valor por defecto).
+    pub base: Base,
+
+    /// Valores esperados por código de prima.
+    #[serde(default)]
+    pub primas: HashMap<String, f64>,
+
+    /// Valores esperados por código de concepto.
+    #[serde(default)]
+    pub conceptos: HashMap<String, f64>,
+
+    /// Tolerancia propia del caso (sustituye la del manifiesto).
+    #[serde(default)]
+    pub tolerancia: Option<f64>,
+}
+
+/// Diferencia detectada al verificar un caso de prueba.
+#[derive(Debug, Clone, Serialize)]
+pub struct FalloCaso {
+    pub caso: String,
+    pub codigo: String,
+    pub esperado: f64,
+    /// `None` si la fórmula/concepto no existe o falló al evaluarse.
+    pub obtenido: Option<f64>,
+    pub detalle: Option<String>,
+}
+
+impl std::fmt::Display for FalloCaso {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.obtenido {
+            Some(v) => write!(
+                f,
+                "[{}] {}: esperado={:.2}, obtenido={:.2}",
+                self.caso, self.codigo, self.esperado, v
+            )?,
+            None => write!(
+                f,
+                "[{}] {}: esperado={:.2}, sin resultado",
+                self.caso, self.codigo, self.esperado
+            )?,
+        }
+        if let Some(d) = &self.detalle {
+            write!(f, " ({})", d)?;
+        }
+        Ok(())
+    }
+}
+
+/// Procesa la Base del caso como en la carga del ciclo: `procesar_registro_base`
+/// con la directiva, primas, total de asignaciones y `generar_calculos`.
+/// Devuelve la Base resultante y la traza de las primas.
+pub(crate) fn preparar_base(
+    caso: &CasoPrueba,
+    directivas: &Vec<Directiva>,
+    motor: &SentinelEngine,
+) -> (Base, Vec<TrazaFormula>) {
+    let mut base = caso.base.clone();
+    procesar_registro_base(&mut base, directivas);
+
+    let trazas = motor.trazar_primas(&base);
+    let primas: HashMap<String, f64> = trazas
+        .iter()
+        .map(|t| (t.codigo.clone(), t.resultado))
+        .collect();
+    base.total_asignaciones = redondear_dos(base.sueldo_base + primas.values().sum::<f64>());
+    base.calculos = Some(primas);
+    generar_calculos(std::slice::from_mut(&mut base), &[], 0.0);
+
+    (base, trazas)
+}
+
+/// Compara los valores esperados de un caso contra los obtenidos.
+/// `errores` contiene los mensajes de evaluación por código, si los hubo.
+pub(crate) fn comparar(
+    caso: &CasoPrueba,
+    esperados: &HashMap<String, f64>,
+    obtenidos: &HashMap<String, f64>,
+    errores: &HashMap<String, String>,
+    tolerancia: f64,
+    fallos: &mut Vec<FalloCaso>,
+) {
+    let tolerancia = caso.tolerancia.unwrap_or(tolerancia);
+
+    let mut codigos: Vec<&String> = esperados.keys().collect();
+    codigos.sort();
+
+    for codigo in codigos {
+        let esperado = esperados[codigo];
+        let error = errores.get(codigo).cloned();
+        match obtenidos.get(codigo) {
+            Some(&v) if error.is_none() && (v - esperado).abs() <= tolerancia => {}
+            Some(&v) if error.is_none() => fallos.push(FalloCaso {
+                caso: caso.nombre.clone(),
+                codigo: codigo.clone(),
+                esperado,
+                obtenido: Some(v),
+                detalle: None,
+            }),
+            Some(_) => fallos.push(FalloCaso {
+                caso: caso.nombre.clone(),
+                codigo: codigo.clone(),
+                esperado,
+                obtenido: None,
+                detalle: error,
+            }),
+            None => fallos.push(FalloCaso {
+                caso: caso.nombre.clone(),
+                codigo: codigo.clone(),
+                esperado,
+                obtenido: None,
+                detalle: Some("código no definido".to_string()),
+            }),
+        }
+    }
+}
+
+/// Une los fallos en un mensaje de error apto para abortar el ciclo.
+pub fn resumen_fallos(origen: &str, fallos: &[FalloCaso]) -> String {
+    let mut msg = format!("{} caso(s) de prueba de {} fallaron:", fallos.len(), origen);
+    for f in fallos {
+        msg.push_str(&format!("\n  - {}", f));
+    }
+    msg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::logica::memoria::PrimaFuncion;
+
+    fn motor() -> SentinelEngine {
+        SentinelEngine::new(vec![PrimaFuncion {
+            codigo: "prima_descendencia".to_string(),
+            nombre: "P_DESCENDENCIA".to_string(),
+            descripcion: "Prueba".to_string(),
+            formula: "12.50 * numero_hijos;".to_string(),
+            monto_nominal: 0.0,
+        }])
+    }
+
+    fn caso(json: &str) -> CasoPrueba {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_caso_que_cuadra_no_falla() {
+        let casos = vec![caso(
+            r#"{"nombre":"dos hijos","base":{"n_hijos":2},"primas":{"prima_descendencia":25.0}}"#,
+        )];
+        assert!(motor().verificar_casos(&casos, &vec![], 0.01).is_empty());
+    }
+
+    #[test]
+    fn test_diferencia_fuera_de_tolerancia_falla() {
+        let casos = vec![caso(
+            r#"{"nombre":"dos hijos","base":{"n_hijos":2},"primas":{"prima_descendencia":25.5}}"#,
+        )];
+        let fallos = motor().verificar_casos(&casos, &vec![], 0.01);
+        assert_eq!(fallos.len(), 1);
+        assert_eq!(fallos[0].codigo, "prima_descendencia");
+        assert_eq!(fallos[0].obtenido, Some(25.0));
+        assert_eq!(
+            fallos[0].to_string(),
+            "[dos hijos] prima_descendencia: esperado=25.50, obtenido=25.00"
+        );
+    }
+
+    #[test]
+    fn test_diferencia_dentro_de_tolerancia_pasa() {
+        let casos = vec![caso(
+            r#"{"nombre":"dos hijos","base":{"n_hijos":2},"primas":{"prima_descendencia":25.004}}"#,
+        )];
+        assert!(motor().verificar_casos(&casos, &vec![], 0.01).is_empty());
+    }
+
+    #[test]
+    fn test_tolerancia_del_caso_sustituye_la_del_manifiesto() {
+        let casos = vec![caso(
+            r#"{"nombre":"dos hijos","base":{"n_hijos":2},"primas":{"prima_descendencia":25.5},"tolerancia":1.0}"#,
+        )];
+        assert!(motor().verificar_casos(&casos, &vec![], 0.01).is_empty());
+    }
+
+    #[test]
+    fn test_codigo_desconocido_falla() {
+        let casos = vec![caso(
+            r#"{"nombre":"sin prima","base":{"n_hijos":2},"primas":{"prima_inexistente":1.0}}"#,
+        )];
+        let fallos = motor().verificar_casos(&casos, &vec![], 0.01);
+        assert_eq!(fallos.len(), 1);
+        assert_eq!(fallos[0].obtenido, None);
+        assert_eq!(fallos[0].detalle.as_deref(), Some("código no definido"));
+    }
+}
@@ -0,0 +1,245 @@
+//! Reporte de cobertura y distribución de resultados por fórmula.
+//!
+//! Por cada prima y concepto: a cuántos beneficiarios se aplicó, cuántos
+//! obtuvieron cero, mínimo/máximo/media/suma y si el circuit breaker la desactivó.
+
+use crate::kernel::logica::exportador::{self, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::kernel::logica::memoria::Beneficiario;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CoberturaFormula {
+    pub origen: String,
+    pub codigo: String,
+    pub nombre: String,
+    pub ejecutadas: usize,
+    pub ceros: usize,
+    pub minimo: f64,
+    pub maximo: f64,
+    pub media: f64,
+    pub suma: f64,
+    pub desactivada: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReporteCobertura {
+    pub total_beneficiarios: usize,
+    pub primas: Vec<CoberturaFormula>,
+    pub conceptos: Vec<CoberturaFormula>,
+}
+
+#[derive(Default)]
+struct Acumulador {
+    ejecutadas: usize,
+    ceros: usize,
+    minimo: f64,
+    maximo: f64,
+    suma: f64,
+}
+
+impl Acumulador {
+    fn agregar(&mut self, valor: f64) {
+        if self.ejecutadas == 0 {
+            self.minimo = valor;
+            self.maximo = valor;
+        } else {
+            self.minimo = self.minimo.min(valor);
+            self.maximo = self.maximo.max(valor);
+        }
+        self.ejecutadas += 1;
+        self.suma += valor;
+        if valor == 0.0 {
+            self.ceros += 1;
+        }
+    }
+}
+
+/// Definición mínima de una fórmula para el reporte: (código, nombre, desactivada).
+pub type DefinicionFormula = (String, String, bool);
+
+impl ReporteCobertura {
+    /// Construye el reporte a partir de los beneficiarios finales: primas desde
+    /// `base.calculos` y conceptos desde `conceptos_calculados`.
+    pub fn desde(
+        beneficiarios: &[Beneficiario],
+        primas: &[DefinicionFormula],
+        conceptos: &[DefinicionFormula],
+    ) -> Self {
+        let mut acc_primas: HashMap<&str, Acumulador> = HashMap::new();
+        let mut acc_conceptos: HashMap<&str, Acumulador> = HashMap::new();
+
+        for ben in beneficiarios {
+            if let Some(calculos) = &ben.base.calculos {
+                for (codigo, valor) in calculos {
+                    acc_primas.entry(codigo.as_str()).or_default().agregar(*valor);
+                }
+            }
+            if let Some(conceptos) = &ben.conceptos_calculados {
+                for (codigo, c) in conceptos {
+                    acc_conceptos.entry(codigo.as_str()).or_default().agregar(c.valor);
+                }
+            }
+        }
+
+        Self {
+            total_beneficiarios: beneficiarios.len(),
+            primas: resumir("prima", primas, &acc_primas),
+            conceptos: resumir("concepto", conceptos, &acc_conceptos),
+        }
+    }
+
+    /// Fórmulas que requieren atención: desactivadas, nunca ejecutadas o siempre en cero.
+    pub fn alertas(&self) -> Vec<String> {
+        self.primas
+            .iter()
+            .chain(self.conceptos.iter())
+            .filter_map(|f| {
+                if f.desactivada {
+                    Some(format!(
+                        "{} '{}' desactivada por error ({} de {} beneficiarios calculados)",
+                        f.origen, f.codigo, f.ejecutadas, self.total_beneficiarios
+                    ))
+                } else if f.ejecutadas == 0 {
+                    Some(format!("{} '{}' no se ejecutó para ningún beneficiario", f.origen, f.codigo))
+                } else if f.ceros == f.ejecutadas {
+                    Some(format!(
+                        "{} '{}' resultó 0.0 en los {} beneficiarios",
+                        f.origen, f.codigo, f.ejecutadas
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Escribe `cobertura_formulas.csv` y `cobertura_formulas.json` en la carpeta de salida.
+    pub fn exportar(&self, destino: &str) -> Result<Vec<ResultadoExport>, Box<dyn std::error::Error>> {
+        let ruta_base = |nombre: &str| {
+            if destino == "." || destino.is_empty() {
+                PathBuf::from(nombre)
+            } else {
+                PathBuf::from(destino).join(nombre)
+            }
+        };
+
+        let mut wtr = csv::WriterBuilder::new().delimiter(b';').from_writer(Vec::new());
+        wtr.write_record([
+            "origen", "codigo", "nombre", "ejecutadas", "ceros", "minimo", "maximo", "media",
+            "suma", "desactivada",
+        ])?;
+        for f in self.primas.iter().chain(self.conceptos.iter()) {
+            wtr.write_record([
+                f.origen.clone(),
+                f.codigo.clone(),
+                f.nombre.clone(),
+                f.ejecutadas.to_string(),
+                f.ceros.to_string(),
+                format!("{:.2}", f.minimo),
+                format!("{:.2}", f.maximo),
+                format!("{:.2}", f.media),
+                format!("{:.2}", f.suma),
+                f.desactivada.to_string(),
+            ])?;
+        }
+        let csv_bytes = wtr.into_inner()?;
+        let json_bytes = serde_json::to_vec_pretty(self)?;
+
+        let mut resultados = Vec::new();
+        for (nombre, tipo, datos) in [
+            ("cobertura_formulas.csv", "cobertura_csv", csv_bytes),
+            ("cobertura_formulas.json", "cobertura_json", json_bytes),
+        ] {
+            let ruta = ruta_base(nombre);
+            std::fs::write(&ruta, &datos)?;
+            resultados.push(ResultadoExport {
+                ruta: ruta.to_string_lossy().to_string(),
+                tipo: tipo.to_string(),
+                tamano_original: datos.len() as u64,
+                tamano_comprimido: None,
+                hash_sha256: None,
+                hash_sha256_original: Some(exportador::generar_hash(&datos)),
+                compresion_aplicada: false,
+            });
+        }
+
+        logger::log_info(
+            "EXPORT",
+            &format!(
+                "Cobertura de fórmulas: {} primas, {} conceptos",
+                self.primas.len(),
+                self.conceptos.len()
+            ),
+        );
+        Ok(resultados)
+    }
+}
+
+fn resumir(
+    origen: &str,
+    definiciones: &[DefinicionFormula],
+    acumulados: &HashMap<&str, Acumulador>,
+) -> Vec<CoberturaFormula> {
+    definiciones
+        .iter()
+        .map(|(codigo, nombre, desactivada)| {
+            let vacio = Acumulador::default();
+            let acc = acumulados.get(codigo.as_str()).unwrap_or(&vacio);
+            CoberturaFormula {
+                origen: origen.to_string(),
+                codigo: codigo.clone(),
+                nombre: nombre.clone(),
+                ejecutadas: acc.ejecutadas,
+                ceros: acc.ceros,
+                minimo: acc.minimo,
+                maximo: acc.maximo,
+                media: if acc.ejecutadas > 0 {
+                    acc.suma / acc.ejecutadas as f64
+                } else {
+                    0.0
+                },
+                suma: acc.suma,
+                desactivada: *desactivada,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beneficiario(primas: &[(&str, f64)]) -> Beneficiario {
+        let mut b: Beneficiario = serde_json::from_str(r#"{"cedula":"1"}"#).unwrap();
+        b.base.calculos = Some(primas.iter().map(|(k, v)| (k.to_string(), *v)).collect());
+        b
+    }
+
+    #[test]
+    fn test_cobertura_estadisticas_y_alertas() {
+        let bens = vec![
+            beneficiario(&[("prima_a", 10.0), ("prima_b", 0.0)]),
+            beneficiario(&[("prima_a", 0.0), ("prima_b", 0.0)]),
+            beneficiario(&[("prima_a", 30.0), ("prima_b", 0.0)]),
+        ];
+        let defs = vec![
+            ("prima_a".to_string(), "A".to_string(), false),
+            ("prima_b".to_string(), "B".to_string(), false),
+            ("prima_c".to_string(), "C".to_string(), true),
+        ];
+
+        let r = ReporteCobertura::desde(&bens, &defs, &[]);
+        let a = &r.primas[0];
+        assert_eq!((a.ejecutadas, a.ceros), (3, 1));
+        assert_eq!((a.minimo, a.maximo, a.suma), (0.0, 30.0, 40.0));
+        assert!((a.media - 40.0 / 3.0).abs() < 1e-9);
+
+        let alertas = r.alertas();
+        assert_eq!(alertas.len(), 2);
+        assert!(alertas[0].contains("prima_b"));
+        assert!(alertas[1].contains("prima_c") && alertas[1].contains("desactivada"));
+    }
+}
@@ -0,0 +1,506 @@
+//! Distribución de `monto_aprobado_garantias` entre los beneficiarios por el
+//! método del resto mayor (Hamilton).
+//!
+//! Cada participación exacta (`garantia_original * factor`) se trunca a céntimos y
+//! los céntimos faltantes para cuadrar el monto aprobado se asignan, uno por
+//! registro, a los de mayor parte fraccionaria; a igual resto decide la cédula.
+//! El resultado no depende del orden en que llegan los registros del stream.
+//!
+//! Con `montos_aprobados_por_particion` el presupuesto viene aprobado por
+//! componente o por banco: la distribución corre de forma independiente dentro
+//! de cada partición y solo con su propio monto.
+
+use crate::kernel::logica::exportador::{self, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::kernel::logica::memoria::Beneficiario;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+
+pub const NOMBRE_ARCHIVO_CONCILIACION: &str = "conciliacion_garantias.csv";
+pub const NOMBRE_ARCHIVO_RESUMEN_PARTICIONES: &str = "resumen_particiones_garantias.csv";
+
+/// Criterio para agrupar beneficiarios en particiones con presupuesto propio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CriterioParticion {
+    /// Por id de componente (Ejército, Armada, Aviación, Guardia).
+    #[default]
+    Componente,
+    /// Por código de banco: los 4 primeros dígitos de la cuenta.
+    Banco,
+}
+
+impl CriterioParticion {
+    pub fn clave(&self, beneficiario: &Beneficiario) -> String {
+        match self {
+            CriterioParticion::Componente => {
+                let id = if beneficiario.base.componente_id != 0 {
+                    beneficiario.base.componente_id
+                } else {
+                    beneficiario.componente_id
+                };
+                id.to_string()
+            }
+            CriterioParticion::Banco => beneficiario.numero_cuenta.trim().chars().take(4).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for CriterioParticion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            CriterioParticion::Componente => "componente",
+            CriterioParticion::Banco => "banco",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Participación de un beneficiario en la distribución.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParticipacionGarantia {
+    pub cedula: String,
+    pub garantia_original: f64,
+    /// Participación exacta: `garantia_original * factor`.
+    pub cuota_bruta: f64,
+    /// Participación truncada a céntimos.
+    pub cuota_truncada: f64,
+    /// Céntimos asignados por resto mayor (0 o 1).
+    pub centavos_ajuste: i64,
+    /// Anticipo final: truncada + ajuste.
+    pub cuota_redondeada: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DistribucionGarantias {
+    /// Clave de la partición; `None` en la distribución global.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub particion: Option<String>,
+    pub monto_aprobado: f64,
+    pub suma_garantias: f64,
+    pub factor: f64,
+    /// En el mismo orden que los registros recibidos.
+    pub participaciones: Vec<ParticipacionGarantia>,
+}
+
+impl DistribucionGarantias {
+    /// Reparte `monto_aprobado` en proporción a la garantía original de cada
+    /// `(cedula, garantia_original)`. `None` si la suma de garantías es cero.
+    pub fn calcular(registros: &[(&str, f64)], monto_aprobado: f64) -> Option<Self> {
+        // Suma en céntimos enteros: no depende del orden de los registros
+        let suma_garantias = registros
+            .iter()
+            .map(|(_, g)| (g * 100.0).round() as i64)
+            .sum::<i64>() as f64
+            / 100.0;
+        if suma_garantias == 0.0 || registros.is_empty() {
+            return None;
+        }
+        let factor = monto_aprobado / suma_garantias;
+        let total_centavos = (monto_aprobado * 100.0).round() as i64;
+
+        let brutos: Vec<f64> = registros.iter().map(|(_, g)| g * factor * 100.0).collect();
+        let mut centavos: Vec<i64> = brutos.iter().map(|b| b.floor() as i64).collect();
+        let truncados = centavos.clone();
+
+        // Orden estable: mayor resto primero, a igual resto la cédula menor
+        let mut orden: Vec<usize> = (0..registros.len()).collect();
+        orden.sort_by(|&a, &b| {
+            let resto_a = brutos[a] - brutos[a].floor();
+            let resto_b = brutos[b] - brutos[b].floor();
+            resto_b
+                .total_cmp(&resto_a)
+                .then_with(|| registros[a].0.cmp(registros[b].0))
+        });
+
+        let mut faltante = total_centavos - centavos.iter().sum::<i64>();
+        while faltante != 0 {
+            let paso = faltante.signum();
+            for &i in &orden {
+                if faltante == 0 {
+                    break;
+                }
+                centavos[i] += paso;
+                faltante -= paso;
+            }
+        }
+
+        let participaciones = registros
+            .iter()
+            .enumerate()
+            .map(|(i, (cedula, garantia))| ParticipacionGarantia {
+                cedula: cedula.to_string(),
+                garantia_original: *garantia,
+                cuota_bruta: brutos[i] / 100.0,
+                cuota_truncada: truncados[i] as f64 / 100.0,
+                centavos_ajuste: centavos[i] - truncados[i],
+                cuota_redondeada: centavos[i] as f64 / 100.0,
+            })
+            .collect();
+
+        Some(Self {
+            particion: None,
+            monto_aprobado,
+            suma_garantias,
+            factor,
+            participaciones,
+        })
+    }
+
+    fn total_distribuido(&self) -> f64 {
+        self.participaciones
+            .iter()
+            .map(|p| (p.cuota_redondeada * 100.0).round() as i64)
+            .sum::<i64>() as f64
+            / 100.0
+    }
+}
+
+/// Aprobado vs. distribuido de una partición.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResumenParticion {
+    pub particion: String,
+    pub beneficiarios: usize,
+    pub suma_garantias: f64,
+    pub monto_aprobado: f64,
+    pub distribuido: f64,
+}
+
+impl ResumenParticion {
+    pub fn diferencia(&self) -> f64 {
+        ((self.monto_aprobado - self.distribuido) * 100.0).round() / 100.0
+    }
+}
+
+/// Resultado de la distribución del ciclo: una sola distribución global o una
+/// por partición con su resumen.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReporteDistribucion {
+    pub criterio: Option<CriterioParticion>,
+    pub distribuciones: Vec<DistribucionGarantias>,
+    /// Solo con partición: todas las claves presentes en la nómina o en el
+    /// manifiesto, ordenadas.
+    pub resumen: Vec<ResumenParticion>,
+}
+
+impl ReporteDistribucion {
+    pub fn global(distribucion: DistribucionGarantias) -> Self {
+        Self {
+            criterio: None,
+            distribuciones: vec![distribucion],
+            resumen: Vec::new(),
+        }
+    }
+
+    /// Escribe `conciliacion_garantias.csv` ordenado por partición y cédula, con
+    /// una fila de totales por distribución que debe cuadrar con su monto
+    /// aprobado; con partición escribe además `resumen_particiones_garantias.csv`.
+    pub fn exportar(
+        &self,
+        destino: &str,
+    ) -> Result<Vec<ResultadoExport>, Box<dyn std::error::Error>> {
+        let ruta_base = |nombre: &str| {
+            if destino == "." || destino.is_empty() {
+                PathBuf::from(nombre)
+            } else {
+                PathBuf::from(destino).join(nombre)
+            }
+        };
+
+        let mut distribuciones: Vec<&DistribucionGarantias> = self.distribuciones.iter().collect();
+        distribuciones.sort_by(|a, b| a.particion.cmp(&b.particion));
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_writer(Vec::new());
+        wtr.write_record([
+            "particion",
+            "cedula",
+            "garantia_original",
+            "factor",
+            "cuota_bruta",
+            "cuota_truncada",
+            "centavos_ajuste",
+            "cuota_redondeada",
+        ])?;
+        for d in &distribuciones {
+            let particion = d.particion.clone().unwrap_or_default();
+            let mut filas: Vec<&ParticipacionGarantia> = d.participaciones.iter().collect();
+            filas.sort_by(|a, b| a.cedula.cmp(&b.cedula));
+            for p in &filas {
+                wtr.write_record([
+                    particion.clone(),
+                    p.cedula.clone(),
+                    format!("{:.2}", p.garantia_original),
+                    format!("{:.10}", d.factor),
+                    format!("{:.6}", p.cuota_bruta),
+                    format!("{:.2}", p.cuota_truncada),
+                    p.centavos_ajuste.to_string(),
+                    format!("{:.2}", p.cuota_redondeada),
+                ])?;
+            }
+            wtr.write_record([
+                particion,
+                "TOTAL".to_string(),
+                format!("{:.2}", d.suma_garantias),
+                format!("{:.10}", d.factor),
+                format!(
+                    "{:.6}",
+                    d.participaciones.iter().map(|p| p.cuota_bruta).sum::<f64>()
+                ),
+                format!(
+                    "{:.2}",
+                    d.participaciones
+                        .iter()
+                        .map(|p| p.cuota_truncada)
+                        .sum::<f64>()
+                ),
+                d.participaciones
+                    .iter()
+                    .map(|p| p.centavos_ajuste)
+                    .sum::<i64>()
+                    .to_string(),
+                format!("{:.2}", d.monto_aprobado),
+            ])?;
+        }
+        let mut archivos = vec![(
+            NOMBRE_ARCHIVO_CONCILIACION,
+            "conciliacion_garantias",
+            wtr.into_inner()?,
+        )];
+
+        if let Some(criterio) = self.criterio {
+            let mut wtr = csv::WriterBuilder::new()
+                .delimiter(b';')
+                .from_writer(Vec::new());
+            wtr.write_record([
+                "criterio",
+                "particion",
+                "beneficiarios",
+                "suma_garantias",
+                "monto_aprobado",
+                "distribuido",
+                "diferencia",
+            ])?;
+            for r in &self.resumen {
+                wtr.write_record([
+                    criterio.to_string(),
+                    r.particion.clone(),
+                    r.beneficiarios.to_string(),
+                    format!("{:.2}", r.suma_garantias),
+                    format!("{:.2}", r.monto_aprobado),
+                    format!("{:.2}", r.distribuido),
+                    format!("{:.2}", r.diferencia()),
+                ])?;
+            }
+            archivos.push((
+                NOMBRE_ARCHIVO_RESUMEN_PARTICIONES,
+                "resumen_particiones_garantias",
+                wtr.into_inner()?,
+            ));
+        }
+
+        let mut resultados = Vec::new();
+        for (nombre, tipo, datos) in archivos {
+            let ruta = ruta_base(nombre);
+            std::fs::write(&ruta, &datos)?;
+            resultados.push(ResultadoExport {
+                ruta: ruta.to_string_lossy().to_string(),
+                tipo: tipo.to_string(),
+                tamano_original: datos.len() as u64,
+                tamano_comprimido: None,
+                hash_sha256: None,
+                hash_sha256_original: Some(exportador::generar_hash(&datos)),
+                compresion_aplicada: false,
+            });
+        }
+
+        logger::log_info(
+            "EXPORT",
+            &format!(
+                "Conciliación de garantías: {} distribuciones, {} registros, monto_aprobado={:.2}",
+                self.distribuciones.len(),
+                self.distribuciones
+                    .iter()
+                    .map(|d| d.participaciones.len())
+                    .sum::<usize>(),
+                self.distribuciones
+                    .iter()
+                    .map(|d| d.monto_aprobado)
+                    .sum::<f64>()
+            ),
+        );
+        Ok(resultados)
+    }
+}
+
+/// Distribuye por separado dentro de cada partición con su monto aprobado. Los
+/// beneficiarios de particiones sin monto no reciben anticipo y quedan en el
+/// resumen con `monto_aprobado` 0.
+pub fn distribuir_por_particion(
+    beneficiarios: &mut [Beneficiario],
+    criterio: CriterioParticion,
+    montos: &HashMap<String, f64>,
+) -> ReporteDistribucion {
+    let mut grupos: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, ben) in beneficiarios.iter().enumerate() {
+        grupos.entry(criterio.clave(ben)).or_default().push(i);
+    }
+    let claves: BTreeSet<String> = grupos
+        .keys()
+        .cloned()
+        .chain(montos.keys().map(|k| k.trim().to_string()))
+        .collect();
+    let montos: HashMap<&str, f64> = montos.iter().map(|(k, v)| (k.trim(), *v)).collect();
+
+    let mut reporte = ReporteDistribucion {
+        criterio: Some(criterio),
+        ..Default::default()
+    };
+
+    for clave in claves {
+        let indices = grupos.get(&clave).map(Vec::as_slice).unwrap_or_default();
+        let monto_aprobado = montos.get(clave.as_str()).copied().unwrap_or(0.0);
+        let registros: Vec<(&str, f64)> = indices
+            .iter()
+            .map(|&i| {
+                (
+                    beneficiarios[i].cedula.as_str(),
+                    beneficiarios[i].base.garantia_original,
+                )
+            })
+            .collect();
+
+        let distribucion = if monto_aprobado > 0.0 {
+            DistribucionGarantias::calcular(&registros, monto_aprobado)
+        } else {
+            None
+        };
+        let suma_garantias = distribucion
+            .as_ref()
+            .map(|d| d.suma_garantias)
+            .unwrap_or_else(|| registros.iter().map(|(_, g)| g).sum());
+
+        let distribuido = match distribucion {
+            Some(mut d) => {
+                for (&i, p) in indices.iter().zip(&d.participaciones) {
+                    beneficiarios[i].base.garantia_anticipo = p.cuota_redondeada;
+                    beneficiarios[i].base.factor_aplicado = d.factor;
+                }
+                d.particion = Some(clave.clone());
+                let distribuido = d.total_distribuido();
+                reporte.distribuciones.push(d);
+                distribuido
+            }
+            None => 0.0,
+        };
+
+        reporte.resumen.push(ResumenParticion {
+            particion: clave,
+            beneficiarios: indices.len(),
+            suma_garantias,
+            monto_aprobado,
+            distribuido,
+        });
+    }
+
+    reporte
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resto_mayor_cuadra_y_no_depende_del_orden() {
+        // 100.00 entre tres garantías iguales: 33.33 c/u y el céntimo restante
+        // va a la cédula menor (empate de restos), no al último registro
+        let registros = [("30", 10.0), ("10", 10.0), ("20", 10.0)];
+        let d = DistribucionGarantias::calcular(&registros, 100.0).unwrap();
+        let cuotas: Vec<(&str, f64, i64)> = d
+            .participaciones
+            .iter()
+            .map(|p| (p.cedula.as_str(), p.cuota_redondeada, p.centavos_ajuste))
+            .collect();
+        assert_eq!(
+            cuotas,
+            vec![("30", 33.33, 0), ("10", 33.34, 1), ("20", 33.33, 0)]
+        );
+
+        let mut invertidos = registros;
+        invertidos.reverse();
+        let e = DistribucionGarantias::calcular(&invertidos, 100.0).unwrap();
+        let cuota_10 = |x: &DistribucionGarantias| {
+            x.participaciones
+                .iter()
+                .find(|p| p.cedula == "10")
+                .unwrap()
+                .cuota_redondeada
+        };
+        assert_eq!(cuota_10(&d), cuota_10(&e));
+
+        // El mayor resto gana aunque la cédula sea mayor: 2 céntimos -> 1.33 / 0.67
+        let d = DistribucionGarantias::calcular(&[("1", 2.0), ("2", 1.0)], 0.02).unwrap();
+        let total: i64 = d
+            .participaciones
+            .iter()
+            .map(|p| (p.cuota_redondeada * 100.0).round() as i64)
+            .sum();
+        assert_eq!(total, 2);
+        assert_eq!(d.participaciones[1].centavos_ajuste, 1);
+        assert!(DistribucionGarantias::calcular(&[("1", 0.0)], 10.0).is_none());
+    }
+
+    #[test]
+    fn test_distribucion_independiente_por_particion() {
+        let ben = |cedula: &str, componente: u32, garantia: f64| {
+            let mut b: Beneficiario =
+                serde_json::from_str(&format!(r#"{{"cedula":"{}"}}"#, cedula)).unwrap();
+            b.base.componente_id = componente;
+            b.base.garantia_original = garantia;
+            b
+        };
+        let mut bens = vec![
+            ben("1", 1, 10.0),
+            ben("2", 2, 30.0),
+            ben("3", 1, 10.0),
+            ben("4", 2, 10.0),
+            ben("5", 4, 50.0),
+        ];
+        let montos: HashMap<String, f64> = [("1", 100.0), ("2", 40.0), ("3", 500.0)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+
+        let reporte = distribuir_por_particion(&mut bens, CriterioParticion::Componente, &montos);
+
+        // Cada partición reparte solo su propio monto
+        let anticipos: Vec<f64> = bens.iter().map(|b| b.base.garantia_anticipo).collect();
+        assert_eq!(anticipos, vec![50.0, 30.0, 50.0, 10.0, 0.0]);
+
+        let resumen: Vec<(&str, usize, f64, f64)> = reporte
+            .resumen
+            .iter()
+            .map(|r| {
+                (
+                    r.particion.as_str(),
+                    r.beneficiarios,
+                    r.monto_aprobado,
+                    r.distribuido,
+                )
+            })
+            .collect();
+        assert_eq!(
+            resumen,
+            vec![
+                ("1", 2, 100.0, 100.0),
+                ("2", 2, 40.0, 40.0),
+                ("3", 0, 500.0, 0.0),
+                ("4", 1, 0.0, 0.0),
+            ]
+        );
+        assert_eq!(reporte.distribuciones.len(), 2);
+        assert_eq!(reporte.resumen[2].diferencia(), 500.0);
+    }
+}
@@ -0,0 +1,603 @@
+//! Modo `explain`: traza estructurada del cálculo de un solo beneficiario.
+//!
+//! Reproduce, en el mismo orden que `Perceptron::ejecutar_ciclo_carga`, la cadena
+//! Base cruda -> Directiva -> Tiempo de servicio -> Primas (Rhai) -> generar_calculos
+//! -> Conceptos -> Fusión con movimientos -> Neto, registrando las entradas y el
+//! resultado de cada paso. Sustituye el flujo de `--debug` + grep `[DEBUG-PTS]`
+//! cuando un beneficiario reclama un pago.
+
+use crate::calc::calculos::{dias_aguinaldo, generar_calculos, redondear_dos};
+use crate::calc::motor::SentinelEngine;
+use crate::calc::{calcular_tiempo_servicio, obtener_directiva, procesar_registro_base, TiempoServicio};
+use crate::kernel::{aplicar_conceptos_y_neto, limitar_y_recalcular};
+use crate::kernel::logica::cargador::{fusionar_movimiento, refrescar_dependientes_asignacion};
+use crate::kernel::logica::memoria::{
+    Base, Beneficiario, ConceptoCalculado, CreditoCuota, Directiva, FaseConcepto, Movimiento, OrdenEmbargo,
+    TipoConcepto,
+};
+use crate::nomina::credito::cuotas_del_ciclo;
+use crate::nomina::embargo::EjecucionEmbargo;
+use crate::nomina::{
+    aplicar_embargos, EjecutorConceptos, EntradaFase, RecorteDeduccion,
+};
+use crate::tipos::TipoNomina;
+use rhai::{Dynamic, Scope};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// Evaluación de una fórmula de prima con las variables del scope que usa.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrazaFormula {
+    pub codigo: String,
+    pub nombre: String,
+    pub codigo_rhai: String,
+    pub monto_nominal: f64,
+    pub entradas: BTreeMap<String, f64>,
+    pub resultado: f64,
+    pub error: Option<String>,
+}
+
+/// Evaluación de un concepto de nómina con las variables del scope que usa.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrazaConcepto {
+    pub codigo: String,
+    pub descripcion: String,
+    pub tipo: TipoConcepto,
+    pub fase: FaseConcepto,
+    pub codigo_rhai: String,
+    pub entradas: BTreeMap<String, f64>,
+    pub resultado: f64,
+    pub error: Option<String>,
+}
+
+/// Un paso de `generar_calculos` (aguinaldo, vacaciones, integral, garantías, ...).
+#[derive(Debug, Clone, Serialize)]
+pub struct TrazaPaso {
+    pub nombre: String,
+    pub formula: String,
+    pub entradas: BTreeMap<String, f64>,
+    pub valor: f64,
+}
+
+/// Una pasada completa de cálculo sobre la Base (inicial o recálculo por f_retiro).
+#[derive(Debug, Clone, Serialize)]
+pub struct TrazaPasada {
+    pub tiempo_servicio: TiempoServicio,
+    pub directiva: Option<Directiva>,
+    pub formulas: Vec<TrazaFormula>,
+    pub pasos: Vec<TrazaPaso>,
+}
+
+/// Traza completa del cálculo de un beneficiario.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrazaCalculo {
+    pub cedula: String,
+    pub ciclo: String,
+    pub tipo_nomina: TipoNomina,
+    pub fecha_generacion: String,
+    pub base_cruda: Base,
+    pub pasada_inicial: TrazaPasada,
+    pub conceptos: Vec<TrazaConcepto>,
+    pub movimiento: Option<Movimiento>,
+    pub recalculo_retiro: Option<TrazaPasada>,
+    pub total_asignaciones: f64,
+    pub total_deducciones: f64,
+    pub porcentaje: f64,
+    pub neto: f64,
+    pub recortes: Vec<RecorteDeduccion>,
+    pub embargos: Vec<EjecucionEmbargo>,
+    pub notas: Vec<String>,
+    pub base_final: Base,
+}
+
+/// Referencias cargadas necesarias para explicar un beneficiario.
+pub struct ContextoExplicacion<'a> {
+    pub ciclo: &'a str,
+    pub directivas: &'a Vec<Directiva>,
+    pub motor: &'a SentinelEngine,
+    pub ejecutor: &'a EjecutorConceptos,
+    pub movimientos: &'a [Movimiento],
+    pub monto_aprobado_garantias: f64,
+    pub porcentaje_maximo_deducciones: f64,
+    pub ordenes_embargo: &'a [OrdenEmbargo],
+    pub creditos: &'a [CreditoCuota],
+    pub porcentaje_maximo_embargo: f64,
+}
+
+/// Reconstruye el cálculo de un beneficiario a partir de su Base cruda y de su
+/// registro de IPSFA_CBeneficiarios (sin fusionar).
+pub fn explicar(
+    ctx: &ContextoExplicacion,
+    base_cruda: Base,
+    mut beneficiario: Beneficiario,
+    tipo_nomina: TipoNomina,
+) -> TrazaCalculo {
+    let mut notas = Vec::new();
+
+    // 1. Pasada inicial (equivalente a Cargador::cargar_base)
+    let mut base = base_cruda.clone();
+    let pasada_inicial = ejecutar_pasada(ctx, &mut base);
+
+    // 2. Conceptos: se evalúan sobre la Base previa a la fusión (con el status
+    // del beneficiario si alguno no lo admite, como en `conceptos_pre_fusion`)
+    let conceptos = if ctx.ejecutor.excluye_status(beneficiario.status_id) {
+        ctx.ejecutor.trazar_con_status(&base, beneficiario.status_id)
+    } else {
+        ctx.ejecutor.trazar(&base)
+    };
+
+    // 3. Fusión con Base y último movimiento (Cargador::cargar_beneficiarios)
+    beneficiario.base = base;
+    let movimiento = ctx
+        .movimientos
+        .iter()
+        .rfind(|m| m.cedula == beneficiario.cedula)
+        .cloned();
+    match &movimiento {
+        Some(mov) => fusionar_movimiento(&mut beneficiario, mov),
+        None => notas.push("Sin movimientos registrados para la cédula.".to_string()),
+    }
+
+    // 4. Recálculo con fecha de retiro
+    let recalculo_retiro = if let Some(fr) = beneficiario.f_retiro.clone() {
+        beneficiario.base.f_retiro = Some(fr.clone());
+        let pasada = ejecutar_pasada(ctx, &mut beneficiario.base);
+        refrescar_dependientes_asignacion(&mut beneficiario.base, &beneficiario.movimientos);
+        notas.push(format!(
+            "Antigüedad y cálculos derivados recalculados con f_retiro={}.",
+            fr
+        ));
+        Some(pasada)
+    } else {
+        None
+    };
+
+    if ctx.monto_aprobado_garantias > 0.0 {
+        notas.push(format!(
+            "La distribución de garantías (monto_aprobado={:.2}) es global sobre toda la nómina y no se reproduce para un solo beneficiario.",
+            ctx.monto_aprobado_garantias
+        ));
+    }
+
+    // 5. Conceptos y neto por fases (PASO 3.5 de ejecutar_ciclo_carga)
+    let mut conceptos = conceptos;
+    let porcentaje_original = beneficiario.porcentaje;
+    let mut acumulados: Option<Vec<ConceptoCalculado>> =
+        (!conceptos.is_empty()).then(|| conceptos.iter().map(a_calculado).collect());
+    if let Some(cuotas) = cuotas_del_ciclo(ctx.creditos, ctx.ciclo, tipo_nomina).remove(&beneficiario.cedula) {
+        for c in &cuotas {
+            notas.push(format!("Cuota de crédito {}: {:.2}", c.codigo, c.valor));
+        }
+        acumulados.get_or_insert_with(Vec::new).extend(cuotas);
+    }
+    aplicar_conceptos_y_neto(&mut beneficiario, acumulados.as_deref(), tipo_nomina);
+
+    // 6. Límite legal de deducciones: antes de post-neto, que ve el neto ya
+    // recortado, y sobre las deducciones que agrega post-neto
+    let mut recortes = Vec::new();
+    for fase in [FaseConcepto::PostFusion, FaseConcepto::PostNeto] {
+        let desde = acumulados.as_ref().map_or(0, Vec::len);
+        if ctx.ejecutor.tiene_fase(fase) {
+            let trazas = ctx.ejecutor.trazar_fase(
+                fase,
+                &EntradaFase {
+                    beneficiario: &beneficiario,
+                    porcentaje: porcentaje_original,
+                    previos: acumulados.as_deref().unwrap_or(&[]),
+                },
+            );
+            acumulados
+                .get_or_insert_with(Vec::new)
+                .extend(trazas.iter().map(a_calculado));
+            conceptos.extend(trazas);
+            beneficiario.porcentaje = porcentaje_original;
+            aplicar_conceptos_y_neto(&mut beneficiario, acumulados.as_deref(), tipo_nomina);
+        }
+
+        if let Some(acumulados) = acumulados.as_mut() {
+            recortes.extend(limitar_y_recalcular(
+                &mut beneficiario,
+                acumulados,
+                if fase == FaseConcepto::PostNeto { desde } else { 0 },
+                porcentaje_original,
+                ctx.ejecutor,
+                tipo_nomina,
+                ctx.porcentaje_maximo_deducciones,
+            ));
+        }
+    }
+
+    // 7. Embargos judiciales sobre el neto
+    let embargos = aplicar_embargos(
+        std::slice::from_mut(&mut beneficiario),
+        ctx.ordenes_embargo,
+        ctx.porcentaje_maximo_embargo,
+    )
+    .ejecuciones;
+
+    TrazaCalculo {
+        cedula: beneficiario.cedula.clone(),
+        ciclo: ctx.ciclo.to_string(),
+        tipo_nomina,
+        fecha_generacion: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        base_cruda,
+        pasada_inicial,
+        conceptos,
+        movimiento,
+        recalculo_retiro,
+        total_asignaciones: beneficiario.total_asignaciones,
+        total_deducciones: beneficiario.total_deducciones,
+        porcentaje: beneficiario.porcentaje,
+        neto: beneficiario.neto,
+        recortes,
+        embargos,
+        notas,
+        base_final: beneficiario.base,
+    }
+}
+
+fn a_calculado(t: &TrazaConcepto) -> ConceptoCalculado {
+    ConceptoCalculado {
+        codigo: t.codigo.clone(),
+        descripcion: t.descripcion.clone(),
+        tipo: t.tipo.clone(),
+        valor: t.resultado,
+        estructura: String::new(),
+        cuenta: String::new(),
+        partida: String::new(),
+    }
+}
+
+/// procesar_registro_base + primas + generar_calculos sobre una Base, con traza.
+fn ejecutar_pasada(ctx: &ContextoExplicacion, base: &mut Base) -> TrazaPasada {
+    procesar_registro_base(base, ctx.directivas);
+    let tiempo_servicio = calcular_tiempo_servicio(base);
+    let directiva =
+        obtener_directiva(base.grado_id, base.antiguedad_grado, ctx.directivas).cloned();
+
+    let formulas = ctx.motor.trazar_primas(base);
+    let calculos: std::collections::HashMap<String, f64> = formulas
+        .iter()
+        .map(|f| (f.codigo.clone(), f.resultado))
+        .collect();
+    let sum_primas: f64 = calculos.values().sum();
+    base.calculos = Some(calculos);
+    base.total_asignaciones = redondear_dos(base.sueldo_base + sum_primas);
+
+    generar_calculos(std::slice::from_mut(base), ctx.movimientos, 0.0);
+    let pasos = pasos_calculo(base, sum_primas);
+
+    TrazaPasada {
+        tiempo_servicio,
+        directiva,
+        formulas,
+        pasos,
+    }
+}
+
+/// Describe los pasos de `generar_calculos` con los valores ya calculados en la Base.
+fn pasos_calculo(base: &Base, sum_primas: f64) -> Vec<TrazaPaso> {
+    let ts = base.antiguedad as f64;
+    let dias_agui = dias_aguinaldo(base.f_retiro.as_deref().unwrap_or("")) as f64;
+
+    vec![
+        paso(
+            "sueldo_mensual",
+            "sueldo_base + Σ primas",
+            &[("sueldo_base", base.sueldo_base), ("total_primas", sum_primas)],
+            base.sueldo_mensual,
+        ),
+        paso(
+            "aguinaldos",
+            "(sueldo_mensual / 30) * (dias_aguinaldo / 12)",
+            &[("sueldo_mensual", base.sueldo_mensual), ("dias_aguinaldo", dias_agui)],
+            base.aguinaldos,
+        ),
+        paso(
+            "vacaciones",
+            "(sueldo_mensual / 30) * (dias_vacaciones / 12)",
+            &[
+                ("sueldo_mensual", base.sueldo_mensual),
+                ("dias_vacaciones", base.dia_vacaciones as f64),
+            ],
+            base.vacaciones,
+        ),
+        paso(
+            "sueldo_integral",
+            "sueldo_mensual + vacaciones + aguinaldos",
+            &[
+                ("sueldo_mensual", base.sueldo_mensual),
+                ("vacaciones", base.vacaciones),
+                ("aguinaldos", base.aguinaldos),
+            ],
+            base.sueldo_integral,
+        ),
+        paso(
+            "asignacion_antiguedad",
+            "sueldo_integral * tiempo_servicio",
+            &[("sueldo_integral", base.sueldo_integral), ("tiempo_servicio", ts)],
+            base.asignacion_antiguedad,
+        ),
+        paso(
+            "garantias",
+            "(sueldo_integral / 30) * 15",
+            &[("sueldo_integral", base.sueldo_integral)],
+            base.garantias,
+        ),
+        paso(
+            "dias_adicionales",
+            "((sueldo_mensual / 30) * 2) * min(tiempo_servicio, 15)",
+            &[("sueldo_mensual", base.sueldo_mensual), ("tiempo_servicio", ts)],
+            base.dias_adicionales,
+        ),
+        paso(
+            "no_depositado_banco",
+            "max(asignacion_antiguedad - deposito_banco - garantia_original - dias_adicionales, 0)",
+            &[
+                ("asignacion_antiguedad", base.asignacion_antiguedad),
+                ("deposito_banco", base.deposito_banco),
+                ("garantia_original", base.garantia_original),
+                ("dias_adicionales", base.dias_adicionales),
+            ],
+            base.no_depositado_banco,
+        ),
+    ]
+}
+
+fn paso(nombre: &str, formula: &str, entradas: &[(&str, f64)], valor: f64) -> TrazaPaso {
+    TrazaPaso {
+        nombre: nombre.to_string(),
+        formula: formula.to_string(),
+        entradas: entradas.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        valor,
+    }
+}
+
+/// Convierte el resultado de Rhai a f64 con la misma regla del motor (float, int o 0.0).
+pub(crate) fn dinamico_a_f64(val: &Dynamic) -> f64 {
+    if let Ok(f) = val.as_float() {
+        f
+    } else if let Ok(i) = val.as_int() {
+        i as f64
+    } else {
+        0.0
+    }
+}
+
+/// Extrae del scope las variables numéricas que el código Rhai referencia
+/// (coincidencia por identificador completo). Si una variable fue re-declarada
+/// en el scope, prevalece el último valor, igual que en la evaluación.
+pub(crate) fn entradas_referenciadas(scope: &Scope, codigo_rhai: &str) -> BTreeMap<String, f64> {
+    let identificadores: HashSet<&str> = codigo_rhai
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let mut entradas = BTreeMap::new();
+    for (nombre, _, valor) in scope.iter() {
+        if identificadores.contains(nombre) && (valor.is_float() || valor.is_int()) {
+            entradas.insert(nombre.to_string(), dinamico_a_f64(&valor));
+        }
+    }
+    entradas
+}
+
+impl TrazaCalculo {
+    /// Reporte legible de la traza (mismo estilo de banners que el CLI).
+    pub fn a_texto(&self) -> String {
+        let mut s = String::new();
+        let linea = format!("{:=<80}\n", "");
+        let sep = format!("{:-<80}\n", "");
+
+        s.push_str(&linea);
+        s.push_str(&format!("{:^80}\n", "SANDRA SENTINEL - EXPLICACIÓN DE CÁLCULO"));
+        s.push_str(&linea);
+        s.push_str(&format!("  {:<22} : {}\n", "Cédula", self.cedula));
+        s.push_str(&format!("  {:<22} : {}\n", "Ciclo", self.ciclo));
+        s.push_str(&format!(
+            "  {:<22} : {} - {}\n",
+            "Tipo", self.tipo_nomina, self.tipo_nomina.descripcion()
+        ));
+        s.push_str(&format!("  {:<22} : {}\n", "Generado", self.fecha_generacion));
+        s.push_str(&sep);
+
+        s.push_str("ENTRADAS (BASE CRUDA)\n");
+        let b = &self.base_cruda;
+        s.push_str(&format!("  {:<22} : {}\n", "patterns", b.patterns));
+        s.push_str(&format!("  {:<22} : {}\n", "grado_id", b.grado_id));
+        s.push_str(&format!("  {:<22} : {}\n", "componente_id", b.componente_id));
+        s.push_str(&format!("  {:<22} : {:?}\n", "fecha_ingreso", b.fecha_ingreso));
+        s.push_str(&format!("  {:<22} : {:?}\n", "f_ult_ascenso", b.f_ult_ascenso));
+        s.push_str(&format!("  {:<22} : {:?}\n", "f_retiro", b.f_retiro));
+        s.push_str(&format!("  {:<22} : {}\n", "n_hijos", b.n_hijos));
+        s.push_str(&format!("  {:<22} : {}\n", "st_profesion", b.st_profesion));
+        s.push_str(&format!("  {:<22} : {}\n", "st_no_ascenso", b.st_no_ascenso));
+
+        escribir_pasada(&mut s, "PASADA INICIAL", &self.pasada_inicial);
+
+        s.push_str(&sep);
+        s.push_str(&format!("CONCEPTOS ({})\n", self.conceptos.len()));
+        for c in &self.conceptos {
+            s.push_str(&format!(
+                "  [{:?}/{:?}] {} ({}) = {:.2}\n",
+                c.fase, c.tipo, c.codigo, c.descripcion, c.resultado
+            ));
+            s.push_str(&format!("      rhai     : {}\n", c.codigo_rhai));
+            escribir_entradas(&mut s, &c.entradas);
+            if let Some(e) = &c.error {
+                s.push_str(&format!("      ERROR    : {}\n", e));
+            }
+        }
+
+        s.push_str(&sep);
+        s.push_str("MOVIMIENTO (ÚLTIMO)\n");
+        match &self.movimiento {
+            Some(m) => {
+                s.push_str(&format!("  {:<22} : {:.2}\n", "deposito_aa", m.deposito_aa));
+                s.push_str(&format!("  {:<22} : {:.2}\n", "deposito_garantias", m.deposito_de_garantias));
+                s.push_str(&format!("  {:<22} : {:.2}\n", "deposito_dias_adic", m.deposito_de_dias_adicionales));
+                s.push_str(&format!("  {:<22} : {:.2}\n", "anticipo", m.anticipo));
+                s.push_str(&format!("  {:<22} : {:.2}\n", "reverso_anticipo", m.reverso_orden_pago_anticipo));
+            }
+            None => s.push_str("  (sin movimientos)\n"),
+        }
+
+        if let Some(p) = &self.recalculo_retiro {
+            escribir_pasada(&mut s, "RECÁLCULO POR FECHA DE RETIRO", p);
+        }
+
+        s.push_str(&linea);
+        s.push_str("RESULTADO\n");
+        s.push_str(&format!("  {:<22} : {:>14.2}\n", "Total Asignaciones", self.total_asignaciones));
+        s.push_str(&format!("  {:<22} : {:>14.2}\n", "Total Deducciones", self.total_deducciones));
+        s.push_str(&format!("  {:<22} : {:>14.2}\n", "Porcentaje", self.porcentaje));
+        s.push_str(&format!("  {:<22} : {:>14.2}\n", "Neto", self.neto));
+        for r in &self.recortes {
+            s.push_str(&format!(
+                "  - Recorte {} : {:.2} -> {:.2} (límite {:.2} sobre bruto {:.2})\n",
+                r.codigo, r.original, r.aplicado, r.limite, r.bruto
+            ));
+        }
+        for e in &self.embargos {
+            s.push_str(&format!(
+                "  - Embargo {} ({}) : solicitado {:.2}, aplicado {:.2} [{}]\n",
+                e.expediente, e.tribunal, e.solicitado, e.aplicado, e.estado
+            ));
+        }
+        for n in &self.notas {
+            s.push_str(&format!("  * {}\n", n));
+        }
+        s.push_str(&linea);
+        s
+    }
+}
+
+fn escribir_pasada(s: &mut String, titulo: &str, p: &TrazaPasada) {
+    s.push_str(&format!("{:-<80}\n", ""));
+    s.push_str(&format!("{}\n", titulo));
+    let t = &p.tiempo_servicio;
+    s.push_str(&format!(
+        "  {:<22} : {} años, {} meses, {} días (antigüedad={}, en grado={})\n",
+        "Tiempo de servicio", t.anos, t.meses, t.dias, t.antiguedad, t.antiguedad_grados
+    ));
+    match &p.directiva {
+        Some(d) => s.push_str(&format!(
+            "  {:<22} : grado={} ({}) antig={} sueldo_base={:.2} ut={:.2} s_minimo={:.2}\n",
+            "Directiva", d.grado_id, d.grado, d.antiguedad, d.sueldo_base, d.unidad_tributaria, d.salario_minimo
+        )),
+        None => s.push_str(&format!("  {:<22} : NO ENCONTRADA\n", "Directiva")),
+    }
+
+    s.push_str(&format!("  Fórmulas ({})\n", p.formulas.len()));
+    for f in &p.formulas {
+        s.push_str(&format!("    {} ({}) = {:.2}\n", f.codigo, f.nombre, f.resultado));
+        s.push_str(&format!("      rhai     : {}\n", f.codigo_rhai));
+        escribir_entradas(s, &f.entradas);
+        if let Some(e) = &f.error {
+            s.push_str(&format!("      ERROR    : {}\n", e));
+        }
+    }
+
+    s.push_str("  Cálculos\n");
+    for paso in &p.pasos {
+        s.push_str(&format!("    {:<22} = {:>14.2}   {}\n", paso.nombre, paso.valor, paso.formula));
+        escribir_entradas(s, &paso.entradas);
+    }
+}
+
+fn escribir_entradas(s: &mut String, entradas: &BTreeMap<String, f64>) {
+    if entradas.is_empty() {
+        return;
+    }
+    let lista: Vec<String> = entradas.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    s.push_str(&format!("      entradas : {}\n", lista.join(", ")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::simulacion::{calcular_escenario, Escenario, Poblacion};
+    use crate::kernel::logica::memoria::{ConceptoNomina, PrimaFuncion};
+
+    #[test]
+    fn test_explicar_coincide_con_el_ciclo() {
+        let directivas: Vec<Directiva> =
+            serde_json::from_str(r#"[{"grado_id":1,"antiguedad":0,"sueldo_base":1000.0}]"#).unwrap();
+        let motor = SentinelEngine::new(vec![PrimaFuncion {
+            codigo: "prima_descendencia".to_string(),
+            nombre: "P_DESCENDENCIA".to_string(),
+            descripcion: "Prueba".to_string(),
+            formula: "12.50 * numero_hijos;".to_string(),
+            monto_nominal: 0.0,
+        }]);
+        let conceptos: Vec<ConceptoNomina> = serde_json::from_str(
+            r#"[
+                {"codigo":"bono","descripcion":"BONO","formula":"sueldo_base * 0.1","tipo":1},
+                {"codigo":"sso","descripcion":"SSO","formula":"sueldo_base * 0.04","tipo":3,"prioridad":"1"},
+                {"codigo":"prestamo","descripcion":"PRESTAMO","formula":"900.0","tipo":3},
+                {"codigo":"ajuste","descripcion":"AJUSTE","formula":"neto * 0.01","tipo":1,"fase":"post_neto"}
+            ]"#,
+        )
+        .unwrap();
+        let ejecutor = EjecutorConceptos::new(conceptos).unwrap();
+        let base: Base = serde_json::from_str(
+            r#"{"patterns":"1-1-2","grado_id":1,"componente_id":1,"n_hijos":2,
+                "fecha_ingreso":"2010-01-01","f_ult_ascenso":"2020-01-01"}"#,
+        )
+        .unwrap();
+        let beneficiario: Beneficiario =
+            serde_json::from_str(r#"{"cedula":"V-1234567","patterns":"1-1-2"}"#).unwrap();
+
+        let ctx = ContextoExplicacion {
+            ciclo: "2026-03",
+            directivas: &directivas,
+            motor: &motor,
+            ejecutor: &ejecutor,
+            movimientos: &[],
+            monto_aprobado_garantias: 0.0,
+            porcentaje_maximo_deducciones: 50.0,
+            ordenes_embargo: &[],
+            creditos: &[],
+            porcentaje_maximo_embargo: 0.0,
+        };
+        let traza = explicar(&ctx, base.clone(), beneficiario.clone(), TipoNomina::Nact);
+
+        // Los pasos sin red de `ejecutar_ciclo_carga` sobre la misma población
+        let escenario = Escenario {
+            nombre: "ciclo",
+            directivas: &directivas,
+            motor: &motor,
+            ejecutor: &ejecutor,
+        };
+        let poblacion = Poblacion {
+            bases: vec![base],
+            beneficiarios: vec![beneficiario],
+            movimientos: Vec::new(),
+        };
+        let ciclo = calcular_escenario(&escenario, &poblacion, TipoNomina::Nact, 50.0);
+        let b = &ciclo[0];
+
+        // El préstamo excede el límite: ambos caminos recortan lo mismo
+        assert!(!traza.recortes.is_empty());
+        assert_eq!(traza.neto, b.neto);
+        assert_eq!(traza.total_asignaciones, b.total_asignaciones);
+        assert_eq!(traza.total_deducciones, b.total_deducciones);
+
+        let calculados = b.conceptos_calculados.as_ref().unwrap();
+        assert_eq!(traza.conceptos.len(), calculados.len());
+        for t in &traza.conceptos {
+            let recortado: f64 = traza
+                .recortes
+                .iter()
+                .filter(|r| r.codigo == t.codigo)
+                .map(|r| r.recortado)
+                .sum();
+            assert_eq!(
+                redondear_dos(t.resultado - recortado),
+                redondear_dos(calculados[&t.codigo].valor),
+                "{}",
+                t.codigo
+            );
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Snapshot del conjunto de fórmulas (primas y conceptos) usado en una ejecución
+//! y comparación entre dos ejecuciones (`sandra formulas diff`).
+//!
+//! El código Rhai vive en base de datos y puede cambiar entre ciclos sin dejar
+//! rastro; el snapshot se guarda como `formulas.json` en la carpeta de salida y se
+//! embebe en `manifest.json` para poder vincular cambios de pago con cambios de reglas.
+
+use crate::kernel::logica::exportador::{self, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::kernel::logica::memoria::{ConceptoNomina, PrimaFuncion};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const NOMBRE_ARCHIVO_FORMULAS: &str = "formulas.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormulaSnapshot {
+    pub codigo: String,
+    pub nombre: String,
+    pub codigo_rhai: String,
+    #[serde(default)]
+    pub monto_nominal: f64,
+    /// Tipo numérico del concepto (solo conceptos).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tipo: Option<u32>,
+    pub hash: String,
+}
+
+impl FormulaSnapshot {
+    fn nueva(codigo: &str, nombre: &str, codigo_rhai: &str, monto_nominal: f64, tipo: Option<u32>) -> Self {
+        let codigo_rhai = codigo_rhai.trim().trim_end_matches(';').to_string();
+        let firma = format!(
+            "{}|{}|{}|{}|{}",
+            codigo,
+            nombre,
+            codigo_rhai,
+            monto_nominal,
+            tipo.map(|t| t.to_string()).unwrap_or_default()
+        );
+        Self {
+            codigo: codigo.to_string(),
+            nombre: nombre.to_string(),
+            hash: exportador::generar_hash(firma.as_bytes()),
+            codigo_rhai,
+            monto_nominal,
+            tipo,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotFormulas {
+    pub ciclo: String,
+    pub fecha_generacion: String,
+    /// Hash del conjunto completo (cambia si cambia cualquier fórmula).
+    pub hash_conjunto: String,
+    pub primas: Vec<FormulaSnapshot>,
+    pub conceptos: Vec<FormulaSnapshot>,
+}
+
+impl SnapshotFormulas {
+    pub fn desde(ciclo: &str, primas: &[PrimaFuncion], conceptos: &[ConceptoNomina]) -> Self {
+        let primas: Vec<FormulaSnapshot> = primas
+            .iter()
+            .map(|p| FormulaSnapshot::nueva(&p.codigo, &p.nombre, &p.formula, p.monto_nominal, None))
+            .collect();
+        let conceptos: Vec<FormulaSnapshot> = conceptos
+            .iter()
+            .map(|c| FormulaSnapshot::nueva(&c.codigo, &c.descripcion, &c.codigo_rhai, 0.0, Some(c.tipo)))
+            .collect();
+
+        let hashes: Vec<&str> = primas
+            .iter()
+            .chain(conceptos.iter())
+            .map(|f| f.hash.as_str())
+            .collect();
+
+        Self {
+            ciclo: ciclo.to_string(),
+            fecha_generacion: chrono::Utc::now().to_rfc3339(),
+            hash_conjunto: exportador::generar_hash(hashes.join("\n").as_bytes()),
+            primas,
+            conceptos,
+        }
+    }
+
+    /// Escribe `formulas.json` en la carpeta de salida.
+    pub fn guardar(&self, destino: &str) -> Result<ResultadoExport, Box<dyn std::error::Error>> {
+        let ruta = if destino == "." || destino.is_empty() {
+            PathBuf::from(NOMBRE_ARCHIVO_FORMULAS)
+        } else {
+            PathBuf::from(destino).join(NOMBRE_ARCHIVO_FORMULAS)
+        };
+
+        let json = serde_json::to_vec_pretty(self)?;
+        std::fs::write(&ruta, &json)?;
+        logger::log_info(
+            "EXPORT",
+            &format!(
+                "Snapshot de fórmulas: {} primas, {} conceptos (hash {})",
+                self.primas.len(),
+                self.conceptos.len(),
+                &self.hash_conjunto[..12.min(self.hash_conjunto.len())]
+            ),
+        );
+
+        Ok(ResultadoExport {
+            ruta: ruta.to_string_lossy().to_string(),
+            tipo: "formulas".to_string(),
+            tamano_original: json.len() as u64,
+            tamano_comprimido: None,
+            hash_sha256: None,
+            hash_sha256_original: Some(exportador::generar_hash(&json)),
+            compresion_aplicada: false,
+        })
+    }
+
+    /// Carga el snapshot de una ejecución. `ruta` puede ser la carpeta de salida,
+    /// su `formulas.json` o su `manifest.json` (bloque `formulas`).
+    pub fn cargar(ruta: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = Path::new(ruta);
+        let archivo = if path.is_dir() {
+            let f = path.join(NOMBRE_ARCHIVO_FORMULAS);
+            if f.exists() {
+                f
+            } else {
+                path.join("manifest.json")
+            }
+        } else {
+            path.to_path_buf()
+        };
+
+        let contenido = std::fs::read_to_string(&archivo)
+            .map_err(|e| format!("No se pudo leer '{}': {}", archivo.display(), e))?;
+        let mut valor: serde_json::Value = serde_json::from_str(&contenido)?;
+        if let Some(embebido) = valor.get_mut("formulas").filter(|v| v.is_object()) {
+            valor = embebido.take();
+        }
+        serde_json::from_value(valor).map_err(|e| {
+            format!("'{}' no contiene un snapshot de fórmulas: {}", archivo.display(), e).into()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CambioFormula {
+    pub origen: String,
+    pub codigo: String,
+    /// Campos que cambiaron (nombre, codigo_rhai, monto_nominal, tipo).
+    pub campos: Vec<String>,
+    pub antes: FormulaSnapshot,
+    pub despues: FormulaSnapshot,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffFormulas {
+    pub ciclo_a: String,
+    pub ciclo_b: String,
+    pub agregadas: Vec<(String, FormulaSnapshot)>,
+    pub eliminadas: Vec<(String, FormulaSnapshot)>,
+    pub modificadas: Vec<CambioFormula>,
+}
+
+impl DiffFormulas {
+    pub fn sin_cambios(&self) -> bool {
+        self.agregadas.is_empty() && self.eliminadas.is_empty() && self.modificadas.is_empty()
+    }
+}
+
+/// Compara dos snapshots por código de fórmula (primas y conceptos por separado).
+pub fn diff(a: &SnapshotFormulas, b: &SnapshotFormulas) -> DiffFormulas {
+    let mut resultado = DiffFormulas {
+        ciclo_a: a.ciclo.clone(),
+        ciclo_b: b.ciclo.clone(),
+        agregadas: Vec::new(),
+        eliminadas: Vec::new(),
+        modificadas: Vec::new(),
+    };
+
+    for (origen, lista_a, lista_b) in [
+        ("prima", &a.primas, &b.primas),
+        ("concepto", &a.conceptos, &b.conceptos),
+    ] {
+        let mapa_a: BTreeMap<&str, &FormulaSnapshot> =
+            lista_a.iter().map(|f| (f.codigo.as_str(), f)).collect();
+        let mapa_b: BTreeMap<&str, &FormulaSnapshot> =
+            lista_b.iter().map(|f| (f.codigo.as_str(), f)).collect();
+
+        for (codigo, fa) in &mapa_a {
+            match mapa_b.get(codigo) {
+                None => resultado.eliminadas.push((origen.to_string(), (*fa).clone())),
+                Some(fb) if fa.hash != fb.hash => {
+                    let mut campos = Vec::new();
+                    if fa.nombre != fb.nombre {
+                        campos.push("nombre".to_string());
+                    }
+                    if fa.codigo_rhai != fb.codigo_rhai {
+                        campos.push("codigo_rhai".to_string());
+                    }
+                    if fa.monto_nominal != fb.monto_nominal {
+                        campos.push("monto_nominal".to_string());
+                    }
+                    if fa.tipo != fb.tipo {
+                        campos.push("tipo".to_string());
+                    }
+                    resultado.modificadas.push(CambioFormula {
+                        origen: origen.to_string(),
+                        codigo: codigo.to_string(),
+                        campos,
+                        antes: (*fa).clone(),
+                        despues: (*fb).clone(),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        for (codigo, fb) in &mapa_b {
+            if !mapa_a.contains_key(codigo) {
+                resultado.agregadas.push((origen.to_string(), (*fb).clone()));
+            }
+        }
+    }
+
+    resultado
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prima(codigo: &str, formula: &str, monto: f64) -> PrimaFuncion {
+        PrimaFuncion {
+            codigo: codigo.to_string(),
+            nombre: codigo.to_uppercase(),
+            descripcion: String::new(),
+            formula: formula.to_string(),
+            monto_nominal: monto,
+        }
+    }
+
+    #[test]
+    fn test_diff_detecta_agregadas_eliminadas_y_modificadas() {
+        let a = SnapshotFormulas::desde(
+            "2026-01",
+            &[
+                prima("prima_a", "sueldo_base * 0.1;", 0.0),
+                prima("prima_b", "12.5 * numero_hijos", 0.0),
+                prima("prima_c", "10.0", 0.0),
+            ],
+            &[],
+        );
+        let b = SnapshotFormulas::desde(
+            "2026-02",
+            &[
+                // Solo difiere el ';' final: no es un cambio
+                prima("prima_a", "sueldo_base * 0.1", 0.0),
+                prima("prima_b", "15.0 * numero_hijos", 0.0),
+                prima("prima_d", "5.0", 2.0),
+            ],
+            &[],
+        );
+
+        let d = diff(&a, &b);
+        assert_eq!(d.modificadas.len(), 1);
+        assert_eq!(d.modificadas[0].codigo, "prima_b");
+        assert_eq!(d.modificadas[0].campos, vec!["codigo_rhai".to_string()]);
+        assert_eq!(d.eliminadas.len(), 1);
+        assert_eq!(d.eliminadas[0].1.codigo, "prima_c");
+        assert_eq!(d.agregadas.len(), 1);
+        assert_eq!(d.agregadas[0].1.codigo, "prima_d");
+        assert_ne!(a.hash_conjunto, b.hash_conjunto);
+        assert!(diff(&a, &a).sin_cambios());
+    }
+}
@@ -1,9 +1,17 @@
 pub mod calculos;
+pub mod casos_prueba;
+pub mod cobertura;
+pub mod distribucion;
+pub mod explicacion;
+pub mod formulas;
 pub mod motor;
+pub mod proyeccion;
+pub mod simulacion;
 use crate::kernel::logica::memoria::{Base, Directiva};
 use chrono::{Datelike, Local, NaiveDate};
+use serde::Serialize;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TiempoServicio {
     pub anos: u32,
     pub meses: u32,
@@ -22,6 +30,17 @@ pub fn is_debug() -> bool {
 pub fn procesar_registro_base(base: &mut Base, directivas: &Vec<Directiva>) {
     // 1. Calcular Tiempo y actualizar antigüedad decimal
     let tiempo = calcular_tiempo_servicio(base);
+    asignar_tiempo_y_directiva(base, tiempo, directivas);
+}
+
+/// Igual que `procesar_registro_base`, con el tiempo de servicio medido a
+/// `fecha_corte` en lugar de f_retiro/hoy (proyecciones a meses futuros).
+pub fn procesar_registro_base_al(base: &mut Base, directivas: &Vec<Directiva>, fecha_corte: NaiveDate) {
+    let tiempo = calcular_tiempo_servicio_al(base, fecha_corte);
+    asignar_tiempo_y_directiva(base, tiempo, directivas);
+}
+
+fn asignar_tiempo_y_directiva(base: &mut Base, tiempo: TiempoServicio, directivas: &Vec<Directiva>) {
     base.antiguedad = tiempo.antiguedad;
     base.antiguedad_grado = tiempo.antiguedad_grados;
 
@@ -75,7 +94,12 @@ pub fn procesar_registro_base(base: &mut Base, directivas: &Vec<Directiva>) {
 
 /// Equivalente a __fechaReconocida y TiempoServicios
 pub fn calcular_tiempo_servicio(base: &Base) -> TiempoServicio {
-    let f_retiro = parsear_fecha_o_hoy(base.f_retiro.as_deref());
+    calcular_tiempo_servicio_al(base, parsear_fecha_o_hoy(base.f_retiro.as_deref()))
+}
+
+/// Tiempo de servicio y antigüedad en grado medidos hasta `fecha_corte`.
+pub fn calcular_tiempo_servicio_al(base: &Base, fecha_corte: NaiveDate) -> TiempoServicio {
+    let f_retiro = fecha_corte;
 
     let fecha_ing_str = base.fecha_ingreso.as_deref().unwrap_or("");
     // Limpiamos la fecha por si viene con hora (ISO 8601: "YYYY-MM-DDTHH:MM:SSZ")
@@ -1,4 +1,7 @@
-use crate::kernel::logica::memoria::{Base, PrimaFuncion};
+use crate::calc::cobertura::DefinicionFormula;
+use crate::calc::casos_prueba::{comparar, preparar_base, CasoPrueba, FalloCaso};
+use crate::calc::explicacion::{dinamico_a_f64, entradas_referenciadas, TrazaFormula};
+use crate::kernel::logica::memoria::{Base, Directiva, PrimaFuncion};
 use rayon::prelude::*;
 use rhai::{Engine, Scope, AST};
 use std::collections::HashMap;
@@ -28,7 +31,7 @@ pub struct SentinelEngine {
 impl SentinelEngine {
     pub fn new(primas: Vec<PrimaFuncion>) -> Self {
         // println!("[SentinelEngine] Iniciando motor de cálculo...");
-        let engine = Engine::new();
+        let engine = crate::util::script::motor();
         let mut formulas = Vec::new();
 
         // println!(
@@ -76,7 +79,7 @@ impl SentinelEngine {
     }
 
     /// Calcula las primas para un lote de beneficiarios en paralelo
-    pub fn calcular_primas(&self, base: &Vec<Base>) -> Vec<(String, HashMap<String, f64>)> {
+    pub fn calcular_primas(&self, base: &[Base]) -> Vec<(String, HashMap<String, f64>)> {
         // ═══════════════════════════════════════════════════════════════════
         //  [DEBUG-PTS] Bajo demanda: activar con flag --debug (SANDRA_DEBUG=1)
         //  Grepear con: grep "DEBUG-PTS" (redirigir stderr con 2>&1)
@@ -252,6 +255,68 @@ impl SentinelEngine {
             .collect()
     }
 
+    /// Evalúa las fórmulas para un solo beneficiario registrando, por cada una,
+    /// las variables del scope que referencia y su resultado (modo `explain`).
+    /// A diferencia de `calcular_primas`, un error no desactiva la fórmula.
+    pub fn trazar_primas(&self, base: &Base) -> Vec<TrazaFormula> {
+        let mut scope = Scope::new();
+        self.llenar_scope(&mut scope, base);
+
+        let mut trazas = Vec::with_capacity(self.formulas.len());
+        for formula in self.formulas.iter() {
+            scope.push("monto_nominal", formula.monto_nominal);
+            let entradas = entradas_referenciadas(&scope, &formula.codigo_rhai);
+
+            let (resultado, error) = match self
+                .engine
+                .eval_ast_with_scope::<rhai::Dynamic>(&mut scope, &formula.ast)
+            {
+                Ok(val) => (dinamico_a_f64(&val), None),
+                Err(e) => (0.0, Some(e.to_string())),
+            };
+            let resultado = (resultado * 100.0).round() / 100.0;
+
+            scope.push(formula.codigo.clone(), resultado);
+
+            trazas.push(TrazaFormula {
+                codigo: formula.codigo.clone(),
+                nombre: formula.nombre.clone(),
+                codigo_rhai: formula.codigo_rhai.clone(),
+                monto_nominal: formula.monto_nominal,
+                entradas,
+                resultado,
+                error,
+            });
+        }
+
+        trazas
+    }
+
+    /// Verifica los valores esperados de primas de cada caso de prueba, con la
+    /// Base procesada contra la directiva como en la carga del ciclo.
+    pub fn verificar_casos(
+        &self,
+        casos: &[CasoPrueba],
+        directivas: &Vec<Directiva>,
+        tolerancia: f64,
+    ) -> Vec<FalloCaso> {
+        let mut fallos = Vec::new();
+        for caso in casos.iter().filter(|c| !c.primas.is_empty()) {
+            let (_, trazas) = preparar_base(caso, directivas, self);
+            let (obtenidos, errores) = resultados_traza(trazas);
+            comparar(caso, &caso.primas, &obtenidos, &errores, tolerancia, &mut fallos);
+        }
+        fallos
+    }
+
+    /// Código, nombre y estado del circuit breaker de cada fórmula (reporte de cobertura).
+    pub fn definiciones(&self) -> Vec<DefinicionFormula> {
+        self.formulas
+            .iter()
+            .map(|f| (f.codigo.clone(), f.nombre.clone(), !f.activo.load(Ordering::Relaxed)))
+            .collect()
+    }
+
     /// Prepara el Scope de Rhai con los datos del Beneficiario
     fn llenar_scope(&self, scope: &mut Scope, base: &Base) {
         // Mapeo de variables esperadas por las fórmulas SQL legacy
@@ -286,10 +351,22 @@ impl SentinelEngine {
     }
 }
 
+fn resultados_traza(trazas: Vec<TrazaFormula>) -> (HashMap<String, f64>, HashMap<String, String>) {
+    let mut obtenidos = HashMap::with_capacity(trazas.len());
+    let mut errores = HashMap::new();
+    for t in trazas {
+        if let Some(e) = t.error {
+            errores.insert(t.codigo.clone(), e);
+        }
+        obtenidos.insert(t.codigo, t.resultado);
+    }
+    (obtenidos, errores)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::kernel::logica::memoria::{Base, PrimaFuncion};
+    use crate::kernel::logica::memoria::{Base, Directiva, PrimaFuncion};
 
     fn crear_base_test(n_hijos: u32, sueldo_base: f64, unidad_tributaria: f64) -> Base {
         Base {
@@ -353,7 +430,7 @@ mod tests {
         )]);
 
         let base = crear_base_test(2, 500.0, 0.0);
-        let resultados = engine.calcular_primas(&vec![base]);
+        let resultados = engine.calcular_primas(&[base]);
 
         assert_eq!(resultados.len(), 1);
         let (_, calculos) = resultados.into_iter().next().unwrap();
@@ -370,7 +447,7 @@ mod tests {
         )]);
 
         let base = crear_base_test(3, 1000.0, 10.0);
-        let resultados = engine.calcular_primas(&vec![base]);
+        let resultados = engine.calcular_primas(&[base]);
 
         assert_eq!(resultados.len(), 1);
         let (_, calculos) = resultados.into_iter().next().unwrap();
@@ -387,10 +464,82 @@ mod tests {
         )]);
 
         let base = crear_base_test(0, 500.0, 0.0);
-        let resultados = engine.calcular_primas(&vec![base]);
+        let resultados = engine.calcular_primas(&[base]);
 
         assert_eq!(resultados.len(), 1);
         let (_, calculos) = resultados.into_iter().next().unwrap();
         assert_eq!(calculos.get("prima_descendencia"), Some(&0.0));
     }
+
+    #[test]
+    fn test_trazar_primas_registra_entradas_encadenadas() {
+        let engine = SentinelEngine::new(vec![
+            crear_prima("prima_a", "P_A", "sueldo_base * 0.1;", 0.0),
+            crear_prima("prima_b", "P_B", "prima_a + monto_nominal * numero_hijos;", 5.0),
+        ]);
+
+        let base = crear_base_test(2, 500.0, 0.0);
+        let trazas = engine.trazar_primas(&base);
+
+        assert_eq!(trazas.len(), 2);
+        assert_eq!(trazas[0].entradas.get("sueldo_base"), Some(&500.0));
+        assert_eq!(trazas[0].resultado, 50.0);
+
+        assert_eq!(trazas[1].entradas.get("prima_a"), Some(&50.0));
+        assert_eq!(trazas[1].entradas.get("monto_nominal"), Some(&5.0));
+        assert_eq!(trazas[1].entradas.get("numero_hijos"), Some(&2.0));
+        assert!(!trazas[1].entradas.contains_key("sueldo_base"));
+        assert_eq!(trazas[1].resultado, 60.0);
+        assert!(trazas[1].error.is_none());
+    }
+
+    #[test]
+    fn test_verificar_casos_detecta_diferencias() {
+        let engine = SentinelEngine::new(vec![
+            crear_prima(
+                "prima_descendencia",
+                "P_DESCENDECIA",
+                "12.50 * numero_hijos;",
+                0.0,
+            ),
+            crear_prima("prima_sueldo", "P_SUELDO", "sueldo_base * 0.10;", 0.0),
+        ]);
+        let directivas: Vec<Directiva> = serde_json::from_str(
+            r#"[{"grado_id": 1, "antiguedad": 0, "sueldo_base": 800.0}]"#,
+        )
+        .unwrap();
+
+        let caso = |nombre: &str, esperado: f64| CasoPrueba {
+            nombre: nombre.to_string(),
+            base: crear_base_test(2, 500.0, 0.0),
+            primas: HashMap::from([("prima_descendencia".to_string(), esperado)]),
+            conceptos: HashMap::new(),
+            tolerancia: None,
+        };
+
+        assert!(engine
+            .verificar_casos(&[caso("ok", 25.004)], &directivas, 0.01)
+            .is_empty());
+
+        let fallos = engine.verificar_casos(&[caso("mal", 30.0)], &directivas, 0.01);
+        assert_eq!(fallos.len(), 1);
+        assert_eq!(fallos[0].obtenido, Some(25.0));
+
+        let mut inexistente = caso("inexistente", 1.0);
+        inexistente.primas = HashMap::from([("prima_x".to_string(), 1.0)]);
+        let fallos = engine.verificar_casos(&[inexistente], &directivas, 0.01);
+        assert_eq!(fallos[0].obtenido, None);
+
+        // El sueldo sale de la directiva (800), no del valor del caso (500):
+        // un cambio en la directiva hace fallar el caso.
+        let mut por_sueldo = caso("sueldo", 0.0);
+        por_sueldo.primas = HashMap::from([("prima_sueldo".to_string(), 80.0)]);
+        assert!(engine
+            .verificar_casos(std::slice::from_ref(&por_sueldo), &directivas, 0.01)
+            .is_empty());
+        let mut cambiada = directivas.clone();
+        cambiada[0].sueldo_base = 900.0;
+        let fallos = engine.verificar_casos(&[por_sueldo], &cambiada, 0.01);
+        assert_eq!(fallos[0].obtenido, Some(90.0));
+    }
 }
@@ -0,0 +1,384 @@
+//! Modo `proyectar`: costo de la nómina a varios meses sobre la población Base.
+//!
+//! Cada mes la población avanza: el tiempo de servicio y la antigüedad en grado
+//! se miden al último día del mes, se aplican los ascensos previstos y los
+//! retiros programados, y se recalculan directiva, primas (`SentinelEngine`),
+//! `generar_calculos` y conceptos pre-fusión. El costo de un integrante es su
+//! sueldo integral más las asignaciones (NPR: sus garantías). Quien ya tiene
+//! fecha de retiro anterior al primer mes no entra en la proyección, y el retiro
+//! por años de servicio no se aplica a NPR.
+
+use crate::calc::calculos::{generar_calculos, redondear_dos};
+use crate::calc::motor::SentinelEngine;
+use crate::calc::{calcular_tiempo_servicio_al, procesar_registro_base_al};
+use crate::kernel::logica::cargador::aplicar_primas;
+use crate::kernel::logica::memoria::{Base, Directiva, TipoConcepto};
+use crate::model::manifiesto::ProyeccionConfig;
+use crate::nomina::EjecutorConceptos;
+use crate::tipos::TipoNomina;
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Ascenso esperado: al cumplir `anos_en_grado` en `grado_origen` se pasa a
+/// `grado_destino` (salvo `st_no_ascenso`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReglaAscenso {
+    pub grado_origen: u32,
+    pub grado_destino: u32,
+    pub anos_en_grado: u32,
+}
+
+/// Retiro con fecha conocida; prevalece sobre el f_retiro de la Base.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetiroProgramado {
+    #[serde(alias = "cedula")]
+    pub patterns: String,
+    /// YYYY-MM-DD: último día en nómina.
+    pub fecha: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MesProyeccion {
+    /// YYYY-MM
+    pub mes: String,
+    pub activos: usize,
+    /// Integrantes que salen de la nómina este mes.
+    pub retiros: usize,
+    pub ascensos: usize,
+    pub costo_total: f64,
+    pub por_componente: BTreeMap<String, f64>,
+    /// Primas y conceptos (las deducciones se reportan pero no suman al costo).
+    pub por_concepto: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultadoProyeccion {
+    pub ciclo: String,
+    pub tipo_nomina: TipoNomina,
+    pub fecha_generacion: String,
+    pub meses: Vec<MesProyeccion>,
+}
+
+fn parsear(fecha: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(fecha.split('T').next().unwrap_or("").trim(), "%Y-%m-%d").ok()
+}
+
+/// Proyecta `config.meses` meses a partir del mes de `inicio`.
+pub fn proyectar(
+    bases: &[Base],
+    directivas: &Vec<Directiva>,
+    motor: &SentinelEngine,
+    ejecutor: &EjecutorConceptos,
+    config: &ProyeccionConfig,
+    tipo_nomina: TipoNomina,
+    inicio: NaiveDate,
+) -> Vec<MesProyeccion> {
+    let retiros_programados: HashMap<&str, NaiveDate> = config
+        .retiros
+        .iter()
+        .filter_map(|r| Some((r.patterns.trim(), parsear(&r.fecha)?)))
+        .collect();
+    let primero_inicio = inicio.with_day(1).unwrap_or(inicio);
+
+    let fecha_retiro = |base: &Base| {
+        retiros_programados
+            .get(base.patterns.trim())
+            .copied()
+            .or_else(|| base.f_retiro.as_deref().and_then(parsear))
+    };
+
+    // Quien ya se retiró antes del primer mes no forma parte de la proyección
+    // (ni cuenta como retiro)
+    let mut poblacion: Vec<Base> = bases
+        .iter()
+        .filter(|b| fecha_retiro(b).is_none_or(|f| f >= primero_inicio))
+        .cloned()
+        .collect();
+    let mut activos = vec![true; poblacion.len()];
+    let mut meses = Vec::with_capacity(config.meses as usize);
+
+    for m in 0..config.meses {
+        let Some(primero) = primero_inicio.checked_add_months(Months::new(m)) else {
+            break;
+        };
+        let fin = primero
+            .checked_add_months(Months::new(1))
+            .and_then(|d| d.pred_opt())
+            .unwrap_or(primero);
+
+        let mut mes = MesProyeccion {
+            mes: primero.format("%Y-%m").to_string(),
+            ..Default::default()
+        };
+        let mut del_mes = Vec::new();
+
+        for (i, base) in poblacion.iter_mut().enumerate() {
+            if !activos[i] {
+                continue;
+            }
+            let f_retiro = fecha_retiro(base);
+            if f_retiro.is_some_and(|f| f < primero) {
+                activos[i] = false;
+                mes.retiros += 1;
+                continue;
+            }
+            let corte = f_retiro.map_or(fin, |f| f.min(fin));
+
+            // Ascenso previsto: reinicia la antigüedad en grado desde este mes
+            let tiempo = calcular_tiempo_servicio_al(base, corte);
+            if base.st_no_ascenso == 0 {
+                if let Some(regla) = config.ascensos.iter().find(|r| {
+                    r.grado_origen == base.grado_id && tiempo.antiguedad_grados >= r.anos_en_grado
+                }) {
+                    base.grado_id = regla.grado_destino;
+                    base.f_ult_ascenso = Some(primero.format("%Y-%m-%d").to_string());
+                    mes.ascensos += 1;
+                }
+            }
+
+            // Retiro por años de servicio: cobra este mes y sale el siguiente.
+            // No aplica a NPR (prestaciones), cuyo costo no depende del retiro.
+            if config.anos_servicio_retiro > 0
+                && tipo_nomina != TipoNomina::Npr
+                && f_retiro.is_none()
+                && tiempo.antiguedad >= config.anos_servicio_retiro
+            {
+                base.f_retiro = Some(fin.format("%Y-%m-%d").to_string());
+            }
+
+            let mut calculo = base.clone();
+            procesar_registro_base_al(&mut calculo, directivas, corte);
+            del_mes.push(calculo);
+        }
+
+        aplicar_primas(&mut del_mes, motor);
+        generar_calculos(&mut del_mes, &[], 0.0);
+        let conceptos = ejecutor.ejecutar(&del_mes);
+
+        for base in &del_mes {
+            let mut asignaciones = 0.0;
+            for (codigo, valor) in base.calculos.iter().flatten() {
+                *mes.por_concepto.entry(codigo.clone()).or_default() += valor;
+            }
+            for c in conceptos.get(&base.patterns).into_iter().flatten() {
+                *mes.por_concepto.entry(c.codigo.clone()).or_default() += c.valor;
+                if c.tipo == TipoConcepto::Asignacion {
+                    asignaciones += c.valor;
+                }
+            }
+            let costo = match tipo_nomina {
+                TipoNomina::Npr => base.garantias,
+                _ => base.sueldo_integral + asignaciones,
+            };
+            *mes.por_componente
+                .entry(format!("{:02}", base.componente_id))
+                .or_default() += costo;
+            mes.costo_total += costo;
+        }
+
+        mes.activos = del_mes.len();
+        mes.costo_total = redondear_dos(mes.costo_total);
+        for v in mes
+            .por_componente
+            .values_mut()
+            .chain(mes.por_concepto.values_mut())
+        {
+            *v = redondear_dos(*v);
+        }
+        meses.push(mes);
+    }
+
+    meses
+}
+
+impl ResultadoProyeccion {
+    /// Tabla mensual para consola: activos, movimientos y costo por componente.
+    pub fn a_texto(&self) -> String {
+        let mut s = String::new();
+        let linea = format!("{:=<80}\n", "");
+        s.push_str(&linea);
+        s.push_str(&format!(
+            "{:^80}\n",
+            "SANDRA SENTINEL - PROYECCIÓN DE COSTO"
+        ));
+        s.push_str(&linea);
+        s.push_str(&format!("  {:<22} : {}\n", "Ciclo", self.ciclo));
+        s.push_str(&format!(
+            "  {:<22} : {} - {}\n",
+            "Tipo",
+            self.tipo_nomina,
+            self.tipo_nomina.descripcion()
+        ));
+        s.push_str(&format!("{:-<80}\n", ""));
+
+        let componentes: Vec<&String> = {
+            let mut c: Vec<&String> = self
+                .meses
+                .iter()
+                .flat_map(|m| m.por_componente.keys())
+                .collect();
+            c.sort();
+            c.dedup();
+            c
+        };
+        s.push_str(&format!(
+            "  {:<8} {:>8} {:>6} {:>6} {:>18}",
+            "mes", "activos", "ret.", "asc.", "costo"
+        ));
+        for c in &componentes {
+            s.push_str(&format!(" {:>16}", format!("comp {}", c)));
+        }
+        s.push('\n');
+        for m in &self.meses {
+            s.push_str(&format!(
+                "  {:<8} {:>8} {:>6} {:>6} {:>18.2}",
+                m.mes, m.activos, m.retiros, m.ascensos, m.costo_total
+            ));
+            for c in &componentes {
+                s.push_str(&format!(
+                    " {:>16.2}",
+                    m.por_componente.get(*c).copied().unwrap_or(0.0)
+                ));
+            }
+            s.push('\n');
+        }
+        s.push_str(&linea);
+        s
+    }
+
+    /// CSV en formato largo: `mes;dimension;clave;monto`, con dimensiones
+    /// `total` (costo, activos, retiros, ascensos), `componente` y `concepto`.
+    pub fn a_csv(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_writer(Vec::new());
+        wtr.write_record(["mes", "dimension", "clave", "monto"])?;
+        for m in &self.meses {
+            for (clave, valor) in [
+                ("costo", format!("{:.2}", m.costo_total)),
+                ("activos", m.activos.to_string()),
+                ("retiros", m.retiros.to_string()),
+                ("ascensos", m.ascensos.to_string()),
+            ] {
+                wtr.write_record([m.mes.as_str(), "total", clave, valor.as_str()])?;
+            }
+            for (dimension, mapa) in [
+                ("componente", &m.por_componente),
+                ("concepto", &m.por_concepto),
+            ] {
+                for (clave, monto) in mapa {
+                    wtr.write_record([
+                        m.mes.clone(),
+                        dimension.to_string(),
+                        clave.clone(),
+                        format!("{:.2}", monto),
+                    ])?;
+                }
+            }
+        }
+        Ok(wtr.into_inner()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base(patterns: &str, grado: u32, ingreso: &str, ascenso: &str) -> Base {
+        serde_json::from_str(&format!(
+            r#"{{"patterns":"{}","grado_id":{},"componente_id":1,"fecha_ingreso":"{}","f_ult_ascenso":"{}"}}"#,
+            patterns, grado, ingreso, ascenso
+        ))
+        .unwrap()
+    }
+
+    fn directiva(grado: u32, sueldo: f64) -> Directiva {
+        serde_json::from_value(serde_json::json!({
+            "grado_id": grado,
+            "antiguedad": 0,
+            "sueldo_base": sueldo
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_avanza_ascensos_y_retiros() {
+        let directivas = vec![directiva(10, 300.0), directiva(11, 600.0)];
+        let motor = SentinelEngine::new(Vec::new());
+        let ejecutor = EjecutorConceptos::new(Vec::new()).unwrap();
+        let config = ProyeccionConfig {
+            meses: 3,
+            anos_servicio_retiro: 30,
+            retiros: vec![RetiroProgramado {
+                patterns: "C".to_string(),
+                fecha: "2026-01-31".to_string(),
+            }],
+            ascensos: vec![ReglaAscenso {
+                grado_origen: 10,
+                grado_destino: 11,
+                anos_en_grado: 3,
+            }],
+        };
+        let bases = vec![
+            // Cumple 3 años en grado el 2026-02-15: asciende en febrero
+            base("A", 10, "2020-01-01", "2023-02-15"),
+            // Cumple 30 años de servicio en enero: cobra enero y sale en febrero
+            base("B", 10, "1996-01-10", "2025-06-01"),
+            // Retiro programado al 31 de enero
+            base("C", 10, "2015-01-01", "2025-06-01"),
+            // Retirado antes del inicio: no entra ni cuenta como retiro
+            Base {
+                f_retiro: Some("2025-12-31".to_string()),
+                ..base("D", 10, "2000-01-01", "2020-01-01")
+            },
+        ];
+
+        let meses = proyectar(
+            &bases,
+            &directivas,
+            &motor,
+            &ejecutor,
+            &config,
+            TipoNomina::Nact,
+            NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+        );
+
+        let resumen: Vec<(&str, usize, usize, usize)> = meses
+            .iter()
+            .map(|m| (m.mes.as_str(), m.activos, m.retiros, m.ascensos))
+            .collect();
+        assert_eq!(
+            resumen,
+            vec![
+                ("2026-01", 3, 0, 0),
+                ("2026-02", 1, 2, 1),
+                ("2026-03", 1, 0, 0),
+            ]
+        );
+        // Enero: A, B y C en el grado 10 (sueldo 300); febrero y marzo: solo A,
+        // ya en el grado 11 (sueldo 600)
+        let costos: Vec<f64> = meses.iter().map(|m| m.costo_total).collect();
+        assert_eq!(costos, vec![1325.01, 883.33, 883.33]);
+        assert_eq!(meses[0].por_componente["01"], 1325.01);
+
+        // NPR: sin retiro por años de servicio, B sigue en la nómina
+        let npr = proyectar(
+            &bases,
+            &directivas,
+            &motor,
+            &ejecutor,
+            &config,
+            TipoNomina::Npr,
+            NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+        );
+        let resumen: Vec<(usize, usize, f64)> = npr
+            .iter()
+            .map(|m| (m.activos, m.retiros, m.costo_total))
+            .collect();
+        assert_eq!(
+            resumen,
+            vec![(3, 0, 662.52), (2, 1, 662.51), (2, 0, 662.51)]
+        );
+    }
+}
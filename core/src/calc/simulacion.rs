@@ -0,0 +1,493 @@
+//! Modo `simular`: costo de la nómina bajo dos cadenas de cálculo (Directiva,
+//! PrimasFunciones y Conceptos) sobre la misma población cargada.
+//!
+//! Cada escenario repite, sin red, los pasos de `Perceptron::ejecutar_ciclo_carga`
+//! sobre copias de la Base y los Beneficiarios crudos: directiva y tiempo de
+//! servicio, primas, `generar_calculos`, conceptos por fases y límite de
+//! deducciones. No se distribuyen garantías ni se aplican créditos o embargos, y
+//! no se genera ningún archivo bancario.
+
+use crate::calc::calculos::{generar_calculos, redondear_dos};
+use crate::calc::motor::SentinelEngine;
+use crate::calc::procesar_registro_base;
+use crate::kernel::{aplicar_conceptos_por_fases, conceptos_pre_fusion};
+use crate::kernel::logica::cargador::{aplicar_primas, fusionar_movimiento, recalcular_por_retiro};
+use crate::kernel::logica::memoria::{Base, Beneficiario, Directiva, Movimiento, TipoConcepto};
+use crate::model::Componente;
+use crate::nomina::EjecutorConceptos;
+use crate::tipos::TipoNomina;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Población cruda (sin cálculos) común a los dos escenarios.
+#[derive(Debug, Clone, Default)]
+pub struct Poblacion {
+    pub bases: Vec<Base>,
+    pub beneficiarios: Vec<Beneficiario>,
+    pub movimientos: Vec<Movimiento>,
+}
+
+/// Cadena de cálculo de un escenario.
+pub struct Escenario<'a> {
+    pub nombre: &'a str,
+    pub directivas: &'a Vec<Directiva>,
+    pub motor: &'a SentinelEngine,
+    pub ejecutor: &'a EjecutorConceptos,
+}
+
+/// Costo agregado de una clave (componente, grado o concepto) en ambos escenarios.
+#[derive(Debug, Clone, Serialize)]
+pub struct FilaCosto {
+    pub clave: String,
+    pub detalle: String,
+    pub beneficiarios: usize,
+    pub base: f64,
+    pub alternativa: f64,
+    pub diferencia: f64,
+    /// `None` si el costo en el escenario base es cero.
+    pub variacion_pct: Option<f64>,
+}
+
+/// Cambio de neto de un beneficiario entre escenarios.
+#[derive(Debug, Clone, Serialize)]
+pub struct CambioBeneficiario {
+    pub cedula: String,
+    pub componente_id: u32,
+    pub grado_id: u32,
+    pub neto_base: f64,
+    pub neto_alternativa: f64,
+    pub diferencia: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultadoSimulacion {
+    pub ciclo: String,
+    pub tipo_nomina: TipoNomina,
+    pub fecha_generacion: String,
+    pub escenario_base: String,
+    pub escenario_alternativo: String,
+    pub beneficiarios: usize,
+    /// Σ neto por escenario.
+    pub total_base: f64,
+    pub total_alternativa: f64,
+    pub diferencia: f64,
+    pub por_componente: Vec<FilaCosto>,
+    pub por_grado: Vec<FilaCosto>,
+    /// Primas (detalle `prima`) y conceptos (`asignacion` / `deduccion`).
+    pub por_concepto: Vec<FilaCosto>,
+    /// Ordenados por |diferencia| descendente.
+    pub mayores_cambios: Vec<CambioBeneficiario>,
+}
+
+/// Calcula la nómina de un escenario sobre una copia de la población.
+pub fn calcular_escenario(
+    escenario: &Escenario,
+    poblacion: &Poblacion,
+    tipo_nomina: TipoNomina,
+    porcentaje_maximo_deducciones: f64,
+) -> Vec<Beneficiario> {
+    let mut bases = poblacion.bases.clone();
+    for base in bases.iter_mut() {
+        procesar_registro_base(base, escenario.directivas);
+    }
+    aplicar_primas(&mut bases, escenario.motor);
+    generar_calculos(&mut bases, &poblacion.movimientos, 0.0);
+    let conceptos = escenario.ejecutor.ejecutar(&bases);
+
+    let map_base: HashMap<&str, &Base> = bases
+        .iter()
+        .filter(|b| !b.patterns.is_empty())
+        .map(|b| (b.patterns.as_str(), b))
+        .collect();
+    // El último movimiento de cada cédula, como en la fusión del ciclo
+    let map_mov: HashMap<&str, &Movimiento> = poblacion
+        .movimientos
+        .iter()
+        .map(|m| (m.cedula.as_str(), m))
+        .collect();
+
+    let mut beneficiarios = poblacion.beneficiarios.clone();
+    for ben in beneficiarios.iter_mut() {
+        if let Some(base) = map_base.get(ben.patterns.as_str()) {
+            ben.base = (*base).clone();
+        }
+        if let Some(mov) = map_mov.get(ben.cedula.as_str()) {
+            fusionar_movimiento(ben, mov);
+        }
+    }
+    recalcular_por_retiro(
+        &mut beneficiarios,
+        escenario.directivas,
+        escenario.motor,
+        &poblacion.movimientos,
+    );
+
+    let pre = conceptos_pre_fusion(&beneficiarios, &map_base, &conceptos, escenario.ejecutor);
+    aplicar_conceptos_por_fases(
+        &mut beneficiarios,
+        pre,
+        escenario.ejecutor,
+        tipo_nomina,
+        porcentaje_maximo_deducciones,
+    );
+    beneficiarios
+}
+
+#[derive(Default)]
+struct Acumulado {
+    detalle: String,
+    cedulas: HashSet<String>,
+    base: f64,
+    alternativa: f64,
+}
+
+fn acumular(
+    grupos: &mut BTreeMap<String, Acumulado>,
+    clave: String,
+    detalle: impl FnOnce() -> String,
+    cedula: &str,
+    base: f64,
+    alternativa: f64,
+) {
+    let g = grupos.entry(clave).or_insert_with(|| Acumulado {
+        detalle: detalle(),
+        ..Default::default()
+    });
+    g.cedulas.insert(cedula.to_string());
+    g.base += base;
+    g.alternativa += alternativa;
+}
+
+fn a_filas(grupos: BTreeMap<String, Acumulado>) -> Vec<FilaCosto> {
+    grupos
+        .into_iter()
+        .map(|(clave, g)| {
+            let base = redondear_dos(g.base);
+            let alternativa = redondear_dos(g.alternativa);
+            let diferencia = redondear_dos(alternativa - base);
+            FilaCosto {
+                clave,
+                detalle: g.detalle,
+                beneficiarios: g.cedulas.len(),
+                base,
+                alternativa,
+                diferencia,
+                variacion_pct: (base != 0.0).then(|| redondear_dos(diferencia / base * 100.0)),
+            }
+        })
+        .collect()
+}
+
+/// Valores por concepto y prima de un beneficiario: `clave -> (detalle, valor)`.
+fn valores_conceptos(ben: &Beneficiario) -> HashMap<String, (&'static str, f64)> {
+    let mut valores = HashMap::new();
+    for (codigo, valor) in ben.base.calculos.iter().flatten() {
+        valores.insert(codigo.clone(), ("prima", *valor));
+    }
+    for c in ben.conceptos_calculados.iter().flat_map(|m| m.values()) {
+        let detalle = match c.tipo {
+            TipoConcepto::Asignacion => "asignacion",
+            TipoConcepto::Deduccion => "deduccion",
+        };
+        valores.insert(c.codigo.clone(), (detalle, c.valor));
+    }
+    valores
+}
+
+/// Compara los dos escenarios `(base, alternativo)`. `base` y `alternativa`
+/// vienen de la misma población, en el mismo orden; `directivas` da el nombre de
+/// cada grado.
+pub fn comparar(
+    (escenario_base, escenario_alternativo): (&str, &str),
+    ciclo: &str,
+    tipo_nomina: TipoNomina,
+    base: &[Beneficiario],
+    alternativa: &[Beneficiario],
+    directivas: &[Directiva],
+    top: usize,
+) -> ResultadoSimulacion {
+    let nombres_grado: HashMap<u32, &str> = directivas
+        .iter()
+        .map(|d| (d.grado_id, d.grado.as_str()))
+        .collect();
+
+    let mut por_componente = BTreeMap::new();
+    let mut por_grado = BTreeMap::new();
+    let mut por_concepto = BTreeMap::new();
+    let mut cambios = Vec::with_capacity(base.len());
+
+    for (b, a) in base.iter().zip(alternativa) {
+        let componente_id = b.base.componente_id;
+        let grado_id = b.base.grado_id;
+
+        acumular(
+            &mut por_componente,
+            format!("{:02}", componente_id),
+            || {
+                Componente::desde_id(componente_id)
+                    .map(|c| c.description().to_string())
+                    .unwrap_or_default()
+            },
+            &b.cedula,
+            b.neto,
+            a.neto,
+        );
+        acumular(
+            &mut por_grado,
+            format!("{:03}", grado_id),
+            || nombres_grado.get(&grado_id).unwrap_or(&"").to_string(),
+            &b.cedula,
+            b.neto,
+            a.neto,
+        );
+
+        let valores_base = valores_conceptos(b);
+        let valores_alt = valores_conceptos(a);
+        let codigos: HashSet<&String> = valores_base.keys().chain(valores_alt.keys()).collect();
+        for codigo in codigos {
+            let (detalle_b, vb) = valores_base.get(codigo).copied().unwrap_or(("", 0.0));
+            let (detalle_a, va) = valores_alt.get(codigo).copied().unwrap_or(("", 0.0));
+            if vb == 0.0 && va == 0.0 {
+                continue;
+            }
+            let detalle = if detalle_b.is_empty() {
+                detalle_a
+            } else {
+                detalle_b
+            };
+            acumular(
+                &mut por_concepto,
+                codigo.clone(),
+                || detalle.to_string(),
+                &b.cedula,
+                vb,
+                va,
+            );
+        }
+
+        cambios.push(CambioBeneficiario {
+            cedula: b.cedula.clone(),
+            componente_id,
+            grado_id,
+            neto_base: redondear_dos(b.neto),
+            neto_alternativa: redondear_dos(a.neto),
+            diferencia: redondear_dos(a.neto - b.neto),
+        });
+    }
+
+    cambios.retain(|c| c.diferencia != 0.0);
+    cambios.sort_by(|x, y| {
+        y.diferencia
+            .abs()
+            .total_cmp(&x.diferencia.abs())
+            .then_with(|| x.cedula.cmp(&y.cedula))
+    });
+    cambios.truncate(top);
+
+    let total_base = redondear_dos(base.iter().map(|b| b.neto).sum());
+    let total_alternativa = redondear_dos(alternativa.iter().map(|b| b.neto).sum());
+
+    ResultadoSimulacion {
+        ciclo: ciclo.to_string(),
+        tipo_nomina,
+        fecha_generacion: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        escenario_base: escenario_base.to_string(),
+        escenario_alternativo: escenario_alternativo.to_string(),
+        beneficiarios: base.len(),
+        total_base,
+        total_alternativa,
+        diferencia: redondear_dos(total_alternativa - total_base),
+        por_componente: a_filas(por_componente),
+        por_grado: a_filas(por_grado),
+        por_concepto: a_filas(por_concepto),
+        mayores_cambios: cambios,
+    }
+}
+
+impl ResultadoSimulacion {
+    /// Reporte legible para consola y `simulacion_<ciclo>.txt`.
+    pub fn a_texto(&self) -> String {
+        let mut s = String::new();
+        let linea = format!("{:=<80}\n", "");
+        let sep = format!("{:-<80}\n", "");
+
+        s.push_str(&linea);
+        s.push_str(&format!(
+            "{:^80}\n",
+            "SANDRA SENTINEL - SIMULACIÓN DE COSTO"
+        ));
+        s.push_str(&linea);
+        s.push_str(&format!("  {:<22} : {}\n", "Ciclo", self.ciclo));
+        s.push_str(&format!(
+            "  {:<22} : {} - {}\n",
+            "Tipo",
+            self.tipo_nomina,
+            self.tipo_nomina.descripcion()
+        ));
+        s.push_str(&format!(
+            "  {:<22} : {}\n",
+            "Escenario base", self.escenario_base
+        ));
+        s.push_str(&format!(
+            "  {:<22} : {}\n",
+            "Escenario alternativo", self.escenario_alternativo
+        ));
+        s.push_str(&format!(
+            "  {:<22} : {}\n",
+            "Beneficiarios", self.beneficiarios
+        ));
+        s.push_str(&format!(
+            "  {:<22} : {:>18.2}\n",
+            "Costo base", self.total_base
+        ));
+        s.push_str(&format!(
+            "  {:<22} : {:>18.2}\n",
+            "Costo alternativo", self.total_alternativa
+        ));
+        s.push_str(&format!(
+            "  {:<22} : {:>+18.2}\n",
+            "Diferencia", self.diferencia
+        ));
+
+        for (titulo, filas) in [
+            ("POR COMPONENTE", &self.por_componente),
+            ("POR GRADO", &self.por_grado),
+            ("POR CONCEPTO", &self.por_concepto),
+        ] {
+            s.push_str(&sep);
+            s.push_str(&format!("{}\n", titulo));
+            s.push_str(&format!(
+                "  {:<24} {:>7} {:>16} {:>16} {:>15} {:>8}\n",
+                "clave", "benef.", "base", "alternativa", "diferencia", "%"
+            ));
+            for f in filas {
+                let clave = if f.detalle.is_empty() {
+                    f.clave.clone()
+                } else {
+                    format!("{} {}", f.clave, f.detalle)
+                };
+                s.push_str(&format!(
+                    "  {:<24.24} {:>7} {:>16.2} {:>16.2} {:>+15.2} {:>8}\n",
+                    clave,
+                    f.beneficiarios,
+                    f.base,
+                    f.alternativa,
+                    f.diferencia,
+                    f.variacion_pct
+                        .map(|p| format!("{:+.2}", p))
+                        .unwrap_or_else(|| "-".to_string())
+                ));
+            }
+        }
+
+        s.push_str(&sep);
+        s.push_str(&format!(
+            "MAYORES CAMBIOS ({})\n",
+            self.mayores_cambios.len()
+        ));
+        for c in &self.mayores_cambios {
+            s.push_str(&format!(
+                "  {:<12} comp {:>2} grado {:>3} : {:>14.2} -> {:>14.2} ({:+.2})\n",
+                c.cedula,
+                c.componente_id,
+                c.grado_id,
+                c.neto_base,
+                c.neto_alternativa,
+                c.diferencia
+            ));
+        }
+        s.push_str(&linea);
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::logica::memoria::ConceptoCalculado;
+
+    fn ben(cedula: &str, componente: u32, grado: u32, neto: f64, prima: f64) -> Beneficiario {
+        let mut b: Beneficiario =
+            serde_json::from_str(&format!(r#"{{"cedula":"{}"}}"#, cedula)).unwrap();
+        b.base.componente_id = componente;
+        b.base.grado_id = grado;
+        b.base.calculos = Some(
+            [("prima_profesion".to_string(), prima)]
+                .into_iter()
+                .collect(),
+        );
+        b.conceptos_calculados = Some(
+            [(
+                "sso".to_string(),
+                ConceptoCalculado {
+                    codigo: "sso".to_string(),
+                    descripcion: "SSO".to_string(),
+                    tipo: TipoConcepto::Deduccion,
+                    valor: 10.0,
+                    estructura: String::new(),
+                    cuenta: String::new(),
+                    partida: String::new(),
+                },
+            )]
+            .into_iter()
+            .collect(),
+        );
+        b.neto = neto;
+        b
+    }
+
+    #[test]
+    fn test_comparar_agrupa_y_ordena_cambios() {
+        let base = vec![ben("1", 1, 10, 100.0, 20.0), ben("2", 2, 10, 200.0, 0.0)];
+        let alternativa = vec![ben("1", 1, 10, 150.0, 70.0), ben("2", 2, 10, 190.0, 0.0)];
+
+        let r = comparar(
+            ("a", "b"),
+            "2026-03",
+            TipoNomina::Nact,
+            &base,
+            &alternativa,
+            &[],
+            1,
+        );
+
+        assert_eq!(
+            (r.total_base, r.total_alternativa, r.diferencia),
+            (300.0, 340.0, 40.0)
+        );
+        let componentes: Vec<(&str, &str, f64)> = r
+            .por_componente
+            .iter()
+            .map(|f| (f.clave.as_str(), f.detalle.as_str(), f.diferencia))
+            .collect();
+        assert_eq!(
+            componentes,
+            vec![
+                ("01", "Ejército Bolivariano", 50.0),
+                ("02", "Armada Bolivariana", -10.0)
+            ]
+        );
+        assert_eq!(r.por_grado.len(), 1);
+        assert_eq!(r.por_grado[0].variacion_pct, Some(13.33));
+
+        // La prima en cero en ambos escenarios no cuenta al beneficiario 2
+        let prima = r
+            .por_concepto
+            .iter()
+            .find(|f| f.clave == "prima_profesion")
+            .unwrap();
+        assert_eq!((prima.beneficiarios, prima.diferencia), (1, 50.0));
+        let sso = r.por_concepto.iter().find(|f| f.clave == "sso").unwrap();
+        assert_eq!(
+            (sso.detalle.as_str(), sso.beneficiarios, sso.diferencia),
+            ("deduccion", 2, 0.0)
+        );
+
+        let cambios: Vec<(&str, f64)> = r
+            .mayores_cambios
+            .iter()
+            .map(|c| (c.cedula.as_str(), c.diferencia))
+            .collect();
+        assert_eq!(cambios, vec![("1", 50.0)]);
+    }
+}
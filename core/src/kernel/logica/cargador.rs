@@ -14,6 +14,8 @@ fn is_debug() -> bool {
 pub struct Cargador {
     pub client: Option<SentinelDynamicServiceClient<Channel>>,
     pub config: Manifiesto,
+    /// Distribución de garantías aplicada en `cargar_beneficiarios`, si hubo monto aprobado.
+    pub distribucion_garantias: Option<crate::calc::distribucion::ReporteDistribucion>,
 }
 
 impl Cargador {
@@ -21,6 +23,7 @@ impl Cargador {
         Self {
             client: None,
             config,
+            distribucion_garantias: None,
         }
     }
 
@@ -54,12 +57,41 @@ impl Cargador {
         self.fetch_stream("IPSFA_CConceptos").await
     }
 
+    /// Órdenes de embargo judicial activas (el filtro de vigencia va en `sql_filter`).
+    pub async fn cargar_embargos(
+        &mut self,
+    ) -> Result<Vec<OrdenEmbargo>, Box<dyn std::error::Error + Send + Sync>> {
+        self.fetch_stream("IPSFA_CEmbargos").await
+    }
+
+    /// Créditos con saldo pendiente (capital, cuota, saldo, ciclo de inicio).
+    pub async fn cargar_creditos(
+        &mut self,
+    ) -> Result<Vec<CreditoCuota>, Box<dyn std::error::Error + Send + Sync>> {
+        self.fetch_stream("IPSFA_CCreditos").await
+    }
+
     pub async fn cargar_movimientos(
         &mut self,
     ) -> Result<Vec<Movimiento>, Box<dyn std::error::Error + Send + Sync>> {
         self.fetch_stream("IPSFA_CMovimientos").await
     }
 
+    /// Carga IPSFA_CBase tal como llega del servidor, sin tiempo de servicio,
+    /// directiva ni primas (usado por el modo `explain` para mostrar las entradas crudas).
+    pub async fn cargar_base_cruda(
+        &mut self,
+    ) -> Result<Vec<Base>, Box<dyn std::error::Error + Send + Sync>> {
+        self.fetch_stream("IPSFA_CBase").await
+    }
+
+    /// Carga IPSFA_CBeneficiarios sin fusionar con Base ni Movimientos.
+    pub async fn cargar_beneficiarios_crudos(
+        &mut self,
+    ) -> Result<Vec<Beneficiario>, Box<dyn std::error::Error + Send + Sync>> {
+        self.fetch_stream("IPSFA_CBeneficiarios").await
+    }
+
     /// CARGA DE FINIQUITOS PARA NOMINA PATRIA
     /// Función: IPSFA_CFiniquitosNomina
     /// Campos: cedula, apellidos, numero_cuenta, monto, f_contable, observaciones
@@ -161,71 +193,8 @@ impl Cargador {
             );
 
             // El motor usa Rayon internamente para calcular en paralelo
-            let calculos = engine.calcular_primas(&results);
-
-            // 3. FUSIÓN DE RESULTADOS (Map-Reduce: Volcar cálculos al struct Base)
-            // Optimizamos creando un mapa temporal para acceso rápido por patrón/key
-            let mapa_calculos: std::collections::HashMap<_, _> = calculos.into_iter().collect();
-
-            let mut match_count = 0;
-            let mut count_zeros_primas = 0;
-            let mut count_hijos_sin_prima = 0;
-            for base in &mut results {
-                // Usamos patterns como clave de enlace según tu lógica en motor.rs
-                if let Some(valores) = mapa_calculos.get(&base.patterns) {
-                    match_count += 1;
-
-                    // A) ALMACENAMIENTO DINÁMICO (Único y Definitivo)
-                    base.calculos = Some(valores.clone());
-
-                    // 2. Calcular Total Asignaciones
-                    let sum_primas: f64 = valores.values().sum();
-                    base.total_asignaciones = crate::calc::calculos::redondear_dos(base.sueldo_base + sum_primas);
-
-                    // Integridad: Si tiene sueldo pero 0 primas, es sospechoso
-                    if base.sueldo_base > 0.0 && sum_primas == 0.0 {
-                        count_zeros_primas += 1;
-                    }
-
-                    // Integridad: Si tiene hijos pero no hay prima asociada
-                    let calculos = base.calculos.as_ref().unwrap();
-                    if base.n_hijos > 0
-                        && !calculos.contains_key("prima_hijos")
-                        && !calculos.contains_key("prima_descendencia")
-                    {
-                        count_hijos_sin_prima += 1;
-                        if count_hijos_sin_prima <= 5 {
-                            logger::log_warn(
-                                "CALCULO",
-                                &format!(
-                                    "Beneficiario con n_hijos={} pero sin fórmula prima_hijos/prima_descendencia (patterns={}).",
-                                    base.n_hijos, base.patterns
-                                ),
-                            );
-                        }
-                    }
-                }
-            }
-
-            if count_zeros_primas > 0 {
-                logger::log_warn(
-                    "CALCULO",
-                    &format!(
-                        "Atención: {} registros tienen Sueldo Base pero 0.0 en Primas calculadas.",
-                        count_zeros_primas
-                    ),
-                );
-            }
-
-            if count_hijos_sin_prima > 0 {
-                logger::log_warn(
-                    "CALCULO",
-                    &format!(
-                        "Atención: {} registros tienen hijos (n_hijos>0) pero no se encontró fórmula prima_hijos/prima_descendencia.",
-                        count_hijos_sin_prima
-                    ),
-                );
-            }
+            let match_count = aplicar_primas(&mut results, engine);
+            verificar_integridad_primas(&results);
 
             // Salida simplificada, el mod.rs hará el resumen final
             // println!("[DONE] '{}' completado...", funcion);
@@ -376,46 +345,7 @@ impl Cargador {
                             // 2. Unir con Movimiento por cedula
                             if let Some(movs_encontrados) = map_mov.get(&item.cedula) {
                                 if let Some(ultimo_mov) = movs_encontrados.last() {
-                                    item.movimientos = ultimo_mov.clone();
-                                    // El anticipo se netea restando el reverso de orden de pago de anticipo.
-                                    // La columna reverso_orden_pago_anticipo conserva su valor original.
-                                    item.movimientos.anticipo -= item.movimientos.reverso_orden_pago_anticipo;
-                                    // Copiar deposito banco al base (tipo 3: deposito_aa)
-                                    item.base.deposito_banco = ultimo_mov.deposito_aa;
-                                    // depositado_en_banco = capital_banco + garantias (tipo 3 + tipo 32)
-                                    item.base.depositado_en_banco = ultimo_mov.deposito_aa + ultimo_mov.deposito_de_garantias;
-                                    // total_aportados = capital_banco + garantias + dias_adicionales (tipo 3 + tipo 32 + tipo 31)
-                                    let total_aportado = ultimo_mov.deposito_aa
-                                        + ultimo_mov.deposito_de_garantias
-                                        + ultimo_mov.deposito_de_dias_adicionales;
-                                    item.base.total_aportados = total_aportado;
-                                    // porcentaje_cancelado = (total_aportados / asignacion_antiguedad) * 100
-                                    item.base.porcentaje_cancelado = if item.base.asignacion_antiguedad > 0.0 {
-                                        (total_aportado / item.base.asignacion_antiguedad) * 100.0
-                                    } else {
-                                        0.0
-                                    };
-                                    // Calcular saldo_disponible = (deposito_banco - anticipo_neto) + deposito_garantias
-                                    // Ahora item.movimientos.anticipo ya es el valor neto (anticipo - reverso_orden_pago_anticipo).
-                                    let anticipo_neto = item.movimientos.anticipo;
-                                    item.base.saldo_disponible = (item.base.deposito_banco - anticipo_neto) + ultimo_mov.deposito_de_garantias;
-                                    if item.base.saldo_disponible < 0.0 {
-                                        item.base.saldo_disponible = 0.0;
-                                    }
-                                    // Regla de presentación heredada de PHP: en reportes/consultas de
-                                    // beneficiarios con fecha de retiro (fallecido/retirado) el saldo
-                                    // disponible se muestra como 0 para reflejar que no puede disponerse.
-                                    if item.f_retiro.as_deref().unwrap_or("").trim() != "" {
-                                        item.base.saldo_disponible = 0.0;
-                                    }
-                                    item.base.saldo_disponible = crate::calc::calculos::redondear_dos(item.base.saldo_disponible);
-                                    // Calcular diferencia_asignacion = AA - deposito_banco - deposito_dias_adicionales - deposito_garantias
-                                    item.base.diferencia_asignacion = crate::calc::calculos::redondear_dos(
-                                        item.base.asignacion_antiguedad
-                                        - item.base.deposito_banco
-                                        - ultimo_mov.deposito_de_dias_adicionales
-                                        - ultimo_mov.deposito_de_garantias
-                                    );
+                                    fusionar_movimiento(&mut item, ultimo_mov);
                                 }
                             }
 
@@ -450,72 +380,48 @@ impl Cargador {
             );
 
             // --- RECÁLCULO DE ANTIGÜEDAD CON F_RETIRO ---
-            // Si el beneficiario tiene f_retiro, copiarlo a la Base para que
-            // calcular_tiempo_servicio lo use como fecha tope en vez de hoy.
-            // Luego reprocesar antigüedad, antigüedad_grado, sueldo_base
-            // y recalcular primas Rhai y todos los cálculos derivados.
-            let mut affected_indices = Vec::new();
-            let mut affected_bases = Vec::new();
-            for (idx, ben) in results.iter_mut().enumerate() {
-                if let Some(ref fr) = ben.f_retiro {
-                    ben.base.f_retiro = Some(fr.clone());
-                    // Reprocesar: antiguedad, antigüedad_grado, sueldo_base
-                    crate::calc::procesar_registro_base(&mut ben.base, directivas);
-                    affected_indices.push(idx);
-                    affected_bases.push(ben.base.clone());
-                }
-            }
+            recalcular_por_retiro(&mut results, directivas, engine, movimientos);
 
-            if !affected_bases.is_empty() {
-                // Recalcular primas Rhai con la antigüedad corregida (limitada por f_retiro)
-                let nuevos_calculos: std::collections::HashMap<String, std::collections::HashMap<String, f64>> =
-                    engine.calcular_primas(&affected_bases).into_iter().collect();
-
-                // Fusionar nuevos cálculos de primas en las bases afectadas
-                for (i, _idx) in affected_indices.iter().enumerate() {
-                    if let Some(calcs) = nuevos_calculos.get(&affected_bases[i].patterns) {
-                        affected_bases[i].calculos = Some(calcs.clone());
-                        let sum_primas = calcs.values().sum::<f64>();
-                        affected_bases[i].total_asignaciones =
-                            crate::calc::calculos::redondear_dos(affected_bases[i].sueldo_base + sum_primas);
-                    }
-                }
-
-                // Recalcular valores derivados (sueldo_mensual, integral, garantías, etc.)
-                crate::calc::calculos::generar_calculos(&mut affected_bases, movimientos, 0.0);
-
-                // Recalcular campos que dependen de asignacion_antiguedad con el valor corregido
-                // (diferencia_asignacion y porcentaje_cancelado se calcularon en la fase de fusión
-                // con la antigüedad original, por lo que deben refrescarse tras corregir f_retiro).
-                for (i, &idx) in affected_indices.iter().enumerate() {
-                    let mov = &results[idx].movimientos;
-                    let base = &mut affected_bases[i];
-
-                    base.diferencia_asignacion = crate::calc::calculos::redondear_dos(
-                        base.asignacion_antiguedad
-                            - base.deposito_banco
-                            - mov.deposito_de_dias_adicionales
-                            - mov.deposito_de_garantias,
+            // APLICAR DISTRIBUCIÓN DE GARANTÍAS (después de fusión completa)
+            // Ambos montos requieren aportes.habilitar: deshabilitado no se distribuye nada
+            let aportes = &self.config.aportes;
+            if !aportes.habilitar
+                && (!aportes.montos_aprobados_por_particion.is_empty() || monto_aprobado_garantias > 0.0)
+            {
+                logger::log_warn(
+                    "APORTES",
+                    "montos aprobados de garantías ignorados: aportes.habilitar es false",
+                );
+            } else if !aportes.montos_aprobados_por_particion.is_empty() {
+                println!(
+                    "    > Aplicando distribución de garantías por {}...",
+                    aportes.particion_garantias
+                );
+                let reporte = crate::calc::distribucion::distribuir_por_particion(
+                    &mut results,
+                    aportes.particion_garantias,
+                    &aportes.montos_aprobados_por_particion,
+                );
+                for r in &reporte.resumen {
+                    println!(
+                        "    > PARTICION {:<6}: beneficiarios={}, aprobado={:.2}, distribuido={:.2}",
+                        r.particion, r.beneficiarios, r.monto_aprobado, r.distribuido
                     );
-
-                    if base.asignacion_antiguedad > 0.0 {
-                        base.porcentaje_cancelado =
-                            (base.total_aportados / base.asignacion_antiguedad) * 100.0;
-                    } else {
-                        base.porcentaje_cancelado = 0.0;
+                    if r.monto_aprobado <= 0.0 && r.beneficiarios > 0 {
+                        println!(
+                            "      [WARN] Partición '{}' sin monto aprobado: {} beneficiarios sin anticipo",
+                            r.particion, r.beneficiarios
+                        );
                     }
                 }
-
-                // Fusionar bases recalculadas de vuelta a los beneficiarios
-                for (i, &idx) in affected_indices.iter().enumerate() {
-                    results[idx].base = std::mem::take(&mut affected_bases[i]);
-                }
-            }
-
-            // APLICAR DISTRIBUCIÓN DE GARANTÍAS (después de fusión completa)
-            if monto_aprobado_garantias > 0.0 {
+                self.distribucion_garantias = Some(reporte);
+            } else if monto_aprobado_garantias > 0.0 {
                 println!("    > Aplicando distribución de garantías...");
-                crate::calc::calculos::generar_calculos_beneficiarios(&mut results, monto_aprobado_garantias);
+                self.distribucion_garantias = crate::calc::calculos::generar_calculos_beneficiarios(
+                    &mut results,
+                    monto_aprobado_garantias,
+                )
+                .map(crate::calc::distribucion::ReporteDistribucion::global);
             }
 
             Ok(results)
@@ -637,3 +543,192 @@ impl Cargador {
         self.fetch_stream("public.familiar").await
     }
 }
+
+/// Vuelca el último movimiento de la cédula sobre el beneficiario fusionado:
+/// netea el anticipo y deriva los saldos bancarios de la Base
+/// (deposito_banco, total_aportados, saldo_disponible, diferencia_asignacion, ...).
+pub fn fusionar_movimiento(item: &mut Beneficiario, ultimo_mov: &Movimiento) {
+    item.movimientos = ultimo_mov.clone();
+    // El anticipo se netea restando el reverso de orden de pago de anticipo.
+    // La columna reverso_orden_pago_anticipo conserva su valor original.
+    item.movimientos.anticipo -= item.movimientos.reverso_orden_pago_anticipo;
+    // Copiar deposito banco al base (tipo 3: deposito_aa)
+    item.base.deposito_banco = ultimo_mov.deposito_aa;
+    // depositado_en_banco = capital_banco + garantias (tipo 3 + tipo 32)
+    item.base.depositado_en_banco = ultimo_mov.deposito_aa + ultimo_mov.deposito_de_garantias;
+    // total_aportados = capital_banco + garantias + dias_adicionales (tipo 3 + tipo 32 + tipo 31)
+    let total_aportado = ultimo_mov.deposito_aa
+        + ultimo_mov.deposito_de_garantias
+        + ultimo_mov.deposito_de_dias_adicionales;
+    item.base.total_aportados = total_aportado;
+    // porcentaje_cancelado = (total_aportados / asignacion_antiguedad) * 100
+    item.base.porcentaje_cancelado = if item.base.asignacion_antiguedad > 0.0 {
+        (total_aportado / item.base.asignacion_antiguedad) * 100.0
+    } else {
+        0.0
+    };
+    // Calcular saldo_disponible = (deposito_banco - anticipo_neto) + deposito_garantias
+    // Ahora item.movimientos.anticipo ya es el valor neto (anticipo - reverso_orden_pago_anticipo).
+    let anticipo_neto = item.movimientos.anticipo;
+    item.base.saldo_disponible = (item.base.deposito_banco - anticipo_neto) + ultimo_mov.deposito_de_garantias;
+    if item.base.saldo_disponible < 0.0 {
+        item.base.saldo_disponible = 0.0;
+    }
+    // Regla de presentación heredada de PHP: en reportes/consultas de
+    // beneficiarios con fecha de retiro (fallecido/retirado) el saldo
+    // disponible se muestra como 0 para reflejar que no puede disponerse.
+    if item.f_retiro.as_deref().unwrap_or("").trim() != "" {
+        item.base.saldo_disponible = 0.0;
+    }
+    item.base.saldo_disponible = crate::calc::calculos::redondear_dos(item.base.saldo_disponible);
+    // Calcular diferencia_asignacion = AA - deposito_banco - deposito_dias_adicionales - deposito_garantias
+    item.base.diferencia_asignacion = crate::calc::calculos::redondear_dos(
+        item.base.asignacion_antiguedad
+        - item.base.deposito_banco
+        - ultimo_mov.deposito_de_dias_adicionales
+        - ultimo_mov.deposito_de_garantias
+    );
+}
+
+/// Recalcula los campos que dependen de asignacion_antiguedad (diferencia_asignacion y
+/// porcentaje_cancelado) tras corregir la antigüedad por f_retiro.
+pub fn refrescar_dependientes_asignacion(base: &mut Base, mov: &Movimiento) {
+    base.diferencia_asignacion = crate::calc::calculos::redondear_dos(
+        base.asignacion_antiguedad
+            - base.deposito_banco
+            - mov.deposito_de_dias_adicionales
+            - mov.deposito_de_garantias,
+    );
+
+    if base.asignacion_antiguedad > 0.0 {
+        base.porcentaje_cancelado = (base.total_aportados / base.asignacion_antiguedad) * 100.0;
+    } else {
+        base.porcentaje_cancelado = 0.0;
+    }
+}
+
+/// Calcula las primas Rhai de las bases y vuelca los resultados (calculos y
+/// total_asignaciones). Devuelve cuántas bases recibieron cálculos.
+pub fn aplicar_primas(bases: &mut [Base], engine: &crate::calc::motor::SentinelEngine) -> usize {
+    let calculos = engine.calcular_primas(bases);
+
+    // Map-Reduce: volcar cálculos al struct Base por patrón/key
+    let mapa_calculos: std::collections::HashMap<_, _> = calculos.into_iter().collect();
+
+    let mut match_count = 0;
+    for base in bases.iter_mut() {
+        // Usamos patterns como clave de enlace según tu lógica en motor.rs
+        if let Some(valores) = mapa_calculos.get(&base.patterns) {
+            match_count += 1;
+
+            // A) ALMACENAMIENTO DINÁMICO (Único y Definitivo)
+            base.calculos = Some(valores.clone());
+
+            // 2. Calcular Total Asignaciones
+            let sum_primas: f64 = valores.values().sum();
+            base.total_asignaciones = crate::calc::calculos::redondear_dos(base.sueldo_base + sum_primas);
+        }
+    }
+    match_count
+}
+
+/// Controles de integridad sobre las primas ya calculadas de la carga: sueldo
+/// base con todas las primas en cero, e hijos sin fórmula de prima por hijos.
+/// Solo registran advertencias en el log.
+pub fn verificar_integridad_primas(bases: &[Base]) {
+    let mut count_zeros_primas = 0;
+    let mut count_hijos_sin_prima = 0;
+    for base in bases {
+        let Some(calculos) = base.calculos.as_ref() else {
+            continue;
+        };
+        let sum_primas: f64 = calculos.values().sum();
+
+        // Integridad: Si tiene sueldo pero 0 primas, es sospechoso
+        if base.sueldo_base > 0.0 && sum_primas == 0.0 {
+            count_zeros_primas += 1;
+        }
+
+        // Integridad: Si tiene hijos pero no hay prima asociada
+        if base.n_hijos > 0
+            && !calculos.contains_key("prima_hijos")
+            && !calculos.contains_key("prima_descendencia")
+        {
+            count_hijos_sin_prima += 1;
+            if count_hijos_sin_prima <= 5 {
+                logger::log_warn(
+                    "CALCULO",
+                    &format!(
+                        "Beneficiario con n_hijos={} pero sin fórmula prima_hijos/prima_descendencia (patterns={}).",
+                        base.n_hijos, base.patterns
+                    ),
+                );
+            }
+        }
+    }
+
+    if count_zeros_primas > 0 {
+        logger::log_warn(
+            "CALCULO",
+            &format!(
+                "Atención: {} registros tienen Sueldo Base pero 0.0 en Primas calculadas.",
+                count_zeros_primas
+            ),
+        );
+    }
+
+    if count_hijos_sin_prima > 0 {
+        logger::log_warn(
+            "CALCULO",
+            &format!(
+                "Atención: {} registros tienen hijos (n_hijos>0) pero no se encontró fórmula prima_hijos/prima_descendencia.",
+                count_hijos_sin_prima
+            ),
+        );
+    }
+}
+
+/// Si el beneficiario tiene f_retiro, la copia a su Base para que
+/// calcular_tiempo_servicio la use como fecha tope en vez de hoy; luego
+/// reprocesa antigüedad, antigüedad_grado y sueldo_base, y recalcula las primas
+/// Rhai y todos los cálculos derivados.
+pub fn recalcular_por_retiro(
+    beneficiarios: &mut [Beneficiario],
+    directivas: &Vec<Directiva>,
+    engine: &crate::calc::motor::SentinelEngine,
+    movimientos: &[Movimiento],
+) {
+    let mut affected_indices = Vec::new();
+    let mut affected_bases = Vec::new();
+    for (idx, ben) in beneficiarios.iter_mut().enumerate() {
+        if let Some(ref fr) = ben.f_retiro {
+            ben.base.f_retiro = Some(fr.clone());
+            // Reprocesar: antiguedad, antigüedad_grado, sueldo_base
+            crate::calc::procesar_registro_base(&mut ben.base, directivas);
+            affected_indices.push(idx);
+            affected_bases.push(ben.base.clone());
+        }
+    }
+
+    if affected_bases.is_empty() {
+        return;
+    }
+
+    // Recalcular primas Rhai con la antigüedad corregida (limitada por f_retiro)
+    aplicar_primas(&mut affected_bases, engine);
+
+    // Recalcular valores derivados (sueldo_mensual, integral, garantías, etc.)
+    crate::calc::calculos::generar_calculos(&mut affected_bases, movimientos, 0.0);
+
+    // Recalcular campos que dependen de asignacion_antiguedad con el valor corregido
+    // (diferencia_asignacion y porcentaje_cancelado se calcularon en la fase de fusión
+    // con la antigüedad original, por lo que deben refrescarse tras corregir f_retiro).
+    for (i, &idx) in affected_indices.iter().enumerate() {
+        refrescar_dependientes_asignacion(&mut affected_bases[i], &beneficiarios[idx].movimientos);
+    }
+
+    // Fusionar bases recalculadas de vuelta a los beneficiarios
+    for (i, &idx) in affected_indices.iter().enumerate() {
+        beneficiarios[idx].base = std::mem::take(&mut affected_bases[i]);
+    }
+}
@@ -1,6 +1,7 @@
 use super::logger;
 use crate::banco::tipos::TipoArchivo;
 use crate::calc::calculos::truncar_dos;
+use crate::calc::formulas::SnapshotFormulas;
 use crate::kernel::logica::memoria::Beneficiario;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -50,6 +51,8 @@ pub struct ManifestExport {
     pub compresion: String,
     #[serde(rename = "archivos")]
     pub archivos: Vec<InfoArchivo>,
+    #[serde(rename = "formulas", default, skip_serializing_if = "Option::is_none")]
+    pub formulas: Option<SnapshotFormulas>,
 }
 
 #[derive(Debug, Clone)]
@@ -470,13 +473,18 @@ pub fn exportar_aporte_csv(
     Ok(resultado)
 }
 
+/// Aporte en CSV y apertura en TXT del Banco de Venezuela. La apertura pasa por
+/// el enrutamiento: las cuentas de otros bancos, inválidas o rechazadas por el
+/// formato quedan en `no_enrutados_apertura_<ciclo>.csv` (último resultado), y el
+/// APERT se divide en partes según `limite`.
 pub fn exportar_aporte_y_apertura_txt(
     beneficiaries: &Vec<Beneficiario>,
     ciclo: &str,
     destino: &str,
     comprimir: bool,
     nivel_compresion: i32,
-) -> Result<(ResultadoExport, ResultadoExport), Box<dyn std::error::Error>> {
+    limite: &crate::banco::formato::LimiteArchivo,
+) -> Result<(ResultadoExport, Vec<ResultadoExport>, ResultadoExport), Box<dyn std::error::Error>> {
     let mut aporte = Vec::new();
     let mut apertura = Vec::new();
 
@@ -498,17 +506,33 @@ pub fn exportar_aporte_y_apertura_txt(
     );
 
     let res_aporte = exportar_aporte_csv(&mut aporte, ciclo, destino, comprimir, nivel_compresion)?;
-    let res_apertura = crate::banco::venezuela::generar_txt_venezuela(
+    let registro = crate::banco::RegistroBancos::por_defecto();
+    let ctx = crate::banco::ContextoArchivo::new(TipoArchivo::Apertura, ciclo, 100.0);
+    let venezuela = crate::banco::Banco::Venezuela;
+    let enrutamiento = crate::banco::enrutamiento::enrutar(
         &apertura,
-        TipoArchivo::Apertura,
-        ciclo,
+        &registro,
+        &[venezuela.codigo().to_string()],
+        &ctx,
+    );
+    let formato = registro
+        .obtener(venezuela)
+        .ok_or("Banco de Venezuela sin formato de archivo")?;
+    let res_apertura = crate::banco::formato::escribir_archivos(
+        formato,
+        enrutamiento.renglones(venezuela),
+        &ctx,
         destino,
-        100.0,
         comprimir,
         nivel_compresion,
-    )?;
-
-    Ok((res_aporte, res_apertura))
+        limite,
+    )?
+    .into_iter()
+    .map(|archivo| archivo.export)
+    .collect();
+    let res_no_enrutados = enrutamiento.exportar(&format!("apertura_{}", ciclo), destino)?;
+
+    Ok((res_aporte, res_apertura, res_no_enrutados))
 }
 
 pub fn exportar_aporte_y_apertura_csv(
@@ -646,6 +670,7 @@ pub fn generar_manifest(
     id_operacion: &str,
     destino: &str,
     resultados: &[ResultadoExport],
+    formulas: Option<&SnapshotFormulas>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let archivos: Vec<InfoArchivo> = resultados
         .iter()
@@ -692,6 +717,7 @@ pub fn generar_manifest(
             "ninguna".to_string()
         },
         archivos,
+        formulas: formulas.cloned(),
     };
 
     let nombre_manifest = "manifest.json".to_string();
@@ -863,6 +889,15 @@ pub fn exportar_nomina_dinamica(
     Ok(resultado)
 }
 
+/// Forma de la nómina CSV por tipo: columnas propias de NFCP y columnas opcionales.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ColumnasNomina {
+    pub es_nfcp: bool,
+    /// `numero_cuenta` tras `sueldo_neto_porcentaje` (`salida.columna_numero_cuenta`).
+    /// Es opcional para no correr las columnas de quien lee la nómina por posición.
+    pub numero_cuenta: bool,
+}
+
 pub fn exportar_nomina_por_tipo(
     beneficiarios: &Vec<Beneficiario>,
     ciclo: &str,
@@ -870,7 +905,7 @@ pub fn exportar_nomina_por_tipo(
     destino: &str,
     comprimir: bool,
     nivel_compresion: i32,
-    es_nfcp: bool,
+    columnas: ColumnasNomina,
 ) -> Result<Vec<ResultadoExport>, Box<dyn std::error::Error>> {
     let es_npr = tipo == "npr";
     let mut resultados = Vec::new();
@@ -886,7 +921,7 @@ pub fn exportar_nomina_por_tipo(
             destino,
             comprimir,
             nivel_compresion,
-            es_nfcp,
+            columnas,
             es_npr,
             false,
         )?;
@@ -901,7 +936,7 @@ pub fn exportar_nomina_por_tipo(
             destino,
             comprimir,
             nivel_compresion,
-            es_nfcp,
+            columnas,
             es_npr,
             true,
         )?;
@@ -918,7 +953,7 @@ fn generar_csv_nomina(
     destino: &str,
     comprimir: bool,
     nivel_compresion: i32,
-    es_nfcp: bool,
+    columnas: ColumnasNomina,
     es_npr: bool,
     es_paralizado: bool,
 ) -> Result<ResultadoExport, Box<dyn std::error::Error>> {
@@ -952,11 +987,11 @@ fn generar_csv_nomina(
 
     // Para archivos de paralizados, no se incluyen conceptos en headers ni registros
     let incluir_conceptos = !es_npr && !es_paralizado;
-    let headers = generar_headers_nomina(es_npr, es_nfcp, incluir_conceptos, &beneficiarios);
+    let headers = generar_headers_nomina(es_npr, columnas, incluir_conceptos, &beneficiarios);
     wtr.write_record(&headers)?;
 
     for b in &beneficiarios {
-        let record = generar_registro_nomina(b, &headers, es_npr, es_nfcp, incluir_conceptos);
+        let record = generar_registro_nomina(b, &headers, es_npr, columnas, incluir_conceptos);
         wtr.write_record(&record)?;
     }
 
@@ -968,10 +1003,11 @@ fn generar_csv_nomina(
 
 fn generar_headers_nomina(
     es_npr: bool,
-    es_nfcp: bool,
+    columnas: ColumnasNomina,
     incluir_conceptos: bool,
     beneficiarios: &[&Beneficiario],
 ) -> Vec<String> {
+    let es_nfcp = columnas.es_nfcp;
     let mut headers = Vec::new();
 
     headers.push("cedula".to_string());
@@ -1048,6 +1084,9 @@ fn generar_headers_nomina(
     headers.push("patterns".to_string());
     headers.push("porcentaje".to_string());
     headers.push("sueldo_neto_porcentaje".to_string());
+    if columnas.numero_cuenta {
+        headers.push("numero_cuenta".to_string());
+    }
 
     if incluir_conceptos {
         let mut descripciones_conceptos: Vec<String> = Vec::new();
@@ -1082,9 +1121,10 @@ fn generar_registro_nomina(
     b: &Beneficiario,
     headers: &[String],
     es_npr: bool,
-    es_nfcp: bool,
+    columnas: ColumnasNomina,
     incluir_conceptos: bool,
 ) -> Vec<String> {
+    let es_nfcp = columnas.es_nfcp;
     let mut record = Vec::new();
 
     let get_calc = |key: &str| -> String {
@@ -1185,6 +1225,9 @@ fn generar_registro_nomina(
 
     let sueldo_neto_pct = b.base.sueldo_mensual * (b.porcentaje / 100.0);
     record.push(format!("{:.2}", sueldo_neto_pct));
+    if columnas.numero_cuenta {
+        record.push(b.numero_cuenta.clone());
+    }
 
     // Para NACT/NRCP/NFCP (no NPR, no paralizados): agregar columnas de conceptos
     if incluir_conceptos {
@@ -1308,6 +1351,7 @@ fn es_campo_fijo(campo: &str) -> bool {
             | "patterns"
             | "porcentaje"
             | "sueldo_neto_porcentaje"
+            | "numero_cuenta"
             | "total_asignaciones"
             | "total_deducciones"
             | "sueldo_total"
@@ -28,7 +28,8 @@ pub struct FiniquitoPatria {
 
 /// Transformación al formato TXT del Sistema Patria
 impl FiniquitoPatria {
-    /// Convierte la estructura al formato de línea TXT para Patria
+    /// Convierte la estructura al formato de línea TXT para Patria, según el
+    /// diseño `schema/bancos/patria.json`
     ///
     /// Formato detalle (80 columnas):
     /// - Columnas 1-1: Letra de cédula (V o E)
@@ -36,55 +37,77 @@ impl FiniquitoPatria {
     /// - Columnas 10-29: Número de cuenta (20 dígitos)
     /// - Columnas 30-40: Monto (11 dígitos, 9 enteros + 2 decimales)
     /// - Columnas 41-80: Nombre (40 caracteres)
-    pub fn to_line_patria(&self) -> String {
-        // Campo 1: Letra de cédula
-        let letra_cedula = if self.cedula.starts_with('V') || self.cedula.starts_with('E') {
-            &self.cedula[0..1]
-        } else {
-            "V"
-        };
-
-        // Campo 2: Cédula padded a 8 dígitos
-        let cedula_clean: String = self.cedula.chars().filter(|c| c.is_ascii_digit()).collect();
-        let cedula_padded = format!("{:0>8}", cedula_clean);
-
-        // Campo 3: Número de cuenta (20 dígitos)
-        let cuenta_clean: String = self
-            .numero_cuenta
-            .chars()
-            .filter(|c| c.is_ascii_digit())
-            .collect();
-        let cuenta_padded = format!("{:0<20}", cuenta_clean);
-
-        // Campo 4: Monto (11 dígitos: 9 enteros + 2 decimales)
-        let monto_centavos = (self.monto * 100.0).round() as i64;
-        let monto_padded = format!("{:0>11}", monto_centavos);
-
-        // Campo 5: Nombre (40 caracteres, padded con espacios)
-        let nombre_padded = format!("{: <40}", self.apellidos);
-
-        // Línea completa (80 columnas)
-        format!(
-            "{}{}{}{}{}",
-            letra_cedula, cedula_padded, cuenta_padded, monto_padded, nombre_padded
-        )
+    ///
+    /// Error si la cédula no es válida o si algún valor no cabe en su campo
+    /// (cédula o monto demasiado largos).
+    pub fn to_line_patria(&self) -> Result<String, String> {
+        use crate::banco::diseno::{DisenoArchivo, RegistroCompilado, DISENO_PATRIA};
+        use rhai::{Engine, Scope};
+        use std::sync::OnceLock;
+
+        static DETALLE: OnceLock<(Engine, RegistroCompilado)> = OnceLock::new();
+        let (engine, detalle) = DETALLE.get_or_init(|| {
+            let engine = crate::util::script::motor();
+            let detalle = DisenoArchivo::desde_json(DISENO_PATRIA)
+                .and_then(|d| RegistroCompilado::compilar(&engine, &d.detalle))
+                .expect("schema/bancos/patria.json inválido");
+            (engine, detalle)
+        });
+
+        let nacionalidad = crate::util::cedula::Cedula::parse(&self.cedula)?.nacionalidad;
+        let mut scope = Scope::new();
+        scope.push("nacionalidad", nacionalidad.as_str().to_string());
+        scope.push("cedula", self.cedula.clone());
+        scope.push("numero_cuenta", self.numero_cuenta.clone());
+        scope.push("monto", self.monto);
+        scope.push("apellidos", self.apellidos.clone());
+        detalle.renderizar(engine, &mut scope)
     }
 
-    /// Valida que el registro cumpla las reglas para Patria
-    pub fn es_valido(&self) -> bool {
-        // Regla 1: Cuenta debe empezar con 0102
-        let cuenta_ok = self.numero_cuenta.starts_with("0102");
+    /// Valida que el registro cumpla las reglas para Patria con los bancos admitidos
+    pub fn es_valido(&self, bancos: &[String]) -> bool {
+        self.validar(bancos).is_ok()
+    }
 
-        // Regla 2: Monto positivo
-        let monto_ok = self.monto > 0.0;
+    /// Código del banco de la cuenta si el registro cumple las reglas para
+    /// Patria, o el motivo por el que queda fuera del TXT.
+    pub fn validar(&self, bancos: &[String]) -> Result<String, RechazoPatria> {
+        // Regla 1: Monto positivo
+        if self.monto <= 0.0 {
+            return Err(RechazoPatria::Negativo);
+        }
 
-        // Regla 3: Cédula válida
-        let cedula_ok = !self.cedula.is_empty() && self.cedula.len() >= 7;
+        // Regla 2: Cuenta de un banco admitido y con dígitos de control válidos
+        let Some(banco) = bancos.iter().find(|b| self.numero_cuenta.starts_with(b.as_str())) else {
+            return Err(RechazoPatria::SinCuenta);
+        };
+        crate::banco::cuenta::validar_ccc(&self.numero_cuenta)
+            .map_err(RechazoPatria::CuentaInvalida)?;
+
+        // Regla 3: Cédula válida (nacionalidad V/E/J/P y largo)
+        if self.cedula.len() < 7 {
+            return Err(RechazoPatria::CedulaInvalida(format!(
+                "cédula {} con menos de 7 caracteres",
+                self.cedula
+            )));
+        }
+        crate::util::cedula::Cedula::parse(&self.cedula).map_err(RechazoPatria::CedulaInvalida)?;
 
-        cuenta_ok && monto_ok && cedula_ok
+        Ok(banco.clone())
     }
 }
 
+/// Motivo por el que un finiquito no entra en el TXT de Patria.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RechazoPatria {
+    /// Monto cero o negativo.
+    Negativo,
+    /// Cuenta vacía o de un banco no admitido.
+    SinCuenta,
+    CuentaInvalida(String),
+    CedulaInvalida(String),
+}
+
 // =============================================================================
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -766,6 +789,17 @@ pub enum TipoConcepto {
     Deduccion,
 }
 
+impl TipoConcepto {
+    /// Interpreta la clase explícita de un concepto; `None` si no es reconocida.
+    pub fn desde_clase(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "asignacion" | "asignación" | "asig" | "a" => Some(TipoConcepto::Asignacion),
+            "deduccion" | "deducción" | "ded" | "d" => Some(TipoConcepto::Deduccion),
+            _ => None,
+        }
+    }
+}
+
 impl From<String> for TipoConcepto {
     fn from(s: String) -> Self {
         match s.to_lowercase().as_str() {
@@ -776,6 +810,98 @@ impl From<String> for TipoConcepto {
     }
 }
 
+/// Momento del ciclo en que se evalúa un concepto.
+/// - `PreFusion`: sobre la Base con primas (comportamiento histórico).
+/// - `PostFusion`: tras fusionar Beneficiarios; ve porcentaje, totales, neto y
+///   los conceptos de la fase anterior.
+/// - `PostNeto`: tras el neto que incluye los conceptos post-fusión.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FaseConcepto {
+    #[default]
+    PreFusion,
+    PostFusion,
+    PostNeto,
+}
+
+impl FaseConcepto {
+    pub const TODAS: [FaseConcepto; 3] = [
+        FaseConcepto::PreFusion,
+        FaseConcepto::PostFusion,
+        FaseConcepto::PostNeto,
+    ];
+
+    /// Interpreta el valor de la columna `fase` (texto o número). Vacío = pre-fusión.
+    pub fn desde_texto(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+            "" | "1" | "pre_fusion" | "prefusion" | "pre" => Some(FaseConcepto::PreFusion),
+            "2" | "post_fusion" | "postfusion" | "fusion" => Some(FaseConcepto::PostFusion),
+            "3" | "post_neto" | "postneto" | "neto" => Some(FaseConcepto::PostNeto),
+            _ => None,
+        }
+    }
+}
+
+/// Orden de embargo judicial activa (IPSFA_CEmbargos).
+/// Se descuenta `porcentaje` del neto o, si es 0, `monto_fijo`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrdenEmbargo {
+    #[serde(default, alias = "cedula_embargado", deserialize_with = "deserialize_any_to_string")]
+    pub cedula: String,
+
+    #[serde(default, alias = "numero_expediente", alias = "oficio", deserialize_with = "deserialize_any_to_string")]
+    pub expediente: String,
+
+    #[serde(default, alias = "juzgado", deserialize_with = "deserialize_any_to_string")]
+    pub tribunal: String,
+
+    #[serde(default, deserialize_with = "deserialize_string_to_f64")]
+    pub porcentaje: f64,
+
+    #[serde(default, alias = "monto", deserialize_with = "deserialize_string_to_f64")]
+    pub monto_fijo: f64,
+
+    #[serde(default, alias = "cedula_demandante", deserialize_with = "deserialize_any_to_string")]
+    pub cedula_beneficiario: String,
+
+    #[serde(default, alias = "nombre_demandante", deserialize_with = "deserialize_any_to_string")]
+    pub nombre_beneficiario: String,
+
+    #[serde(default, alias = "cuenta_beneficiario", deserialize_with = "deserialize_any_to_string")]
+    pub numero_cuenta: String,
+
+    /// Orden de cobro entre embargos de una misma cédula (1 = primero).
+    #[serde(default, deserialize_with = "deserialize_any_to_u32")]
+    pub prioridad: u32,
+}
+
+/// Crédito otorgado por la institución, descontado en cuotas fijas (IPSFA_CCreditos
+/// o archivo de saldos del ciclo anterior).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreditoCuota {
+    #[serde(default, alias = "numero_credito", alias = "id", deserialize_with = "deserialize_any_to_string")]
+    pub numero: String,
+
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub cedula: String,
+
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub descripcion: String,
+
+    #[serde(default, alias = "monto_credito", deserialize_with = "deserialize_string_to_f64")]
+    pub capital: f64,
+
+    #[serde(default, alias = "monto_cuota", deserialize_with = "deserialize_string_to_f64")]
+    pub cuota: f64,
+
+    #[serde(default, alias = "saldo_pendiente", deserialize_with = "deserialize_string_to_f64")]
+    pub saldo: f64,
+
+    /// Primer ciclo (YYYY-MM) en que se descuenta la cuota. Vacío = ya iniciado.
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub ciclo_inicio: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConceptoCalculado {
     pub codigo: String,
@@ -819,6 +945,54 @@ pub struct ConceptoNomina {
         alias = "TIPO"
     )]
     pub tipo: u32,
+
+    /// Fase de evaluación (`pre_fusion` | `post_fusion` | `post_neto`). Vacío = pre-fusión.
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub fase: String,
+
+    /// Clase explícita (`asignacion` | `deduccion`). Si se omite se usa `tipo` (1-2 / 3-5).
+    #[serde(default, alias = "naturaleza", deserialize_with = "deserialize_any_to_string")]
+    pub clase: String,
+
+    // --- Reglas de aplicabilidad (vacío = aplica a todos) ---
+    /// Nóminas separadas por coma (ej. "nact,nrcp").
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub tipos_nomina: String,
+
+    /// Componentes separados por coma (ej. "1,2,3").
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub componentes: String,
+
+    /// status_id del beneficiario separados por coma (ej. "201,202").
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub status: String,
+
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub grado_min: String,
+
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub grado_max: String,
+
+    /// Ventana de vigencia (YYYY-MM-DD) respecto a la fecha de referencia del ciclo.
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub vigente_desde: String,
+
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub vigente_hasta: String,
+
+    // --- Límites de monto ---
+    /// Piso del resultado (no se aplica a resultados 0.0).
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub monto_minimo: String,
+
+    /// Tope del resultado.
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub monto_maximo: String,
+
+    /// Prioridad de cobro de una deducción (1 = se cobra primero). Ante el límite
+    /// global se recortan primero las de número mayor y las que no declaran prioridad.
+    #[serde(default, deserialize_with = "deserialize_any_to_string")]
+    pub prioridad: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
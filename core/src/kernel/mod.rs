@@ -12,10 +12,14 @@ use sandra::{DynamicRequest, DynamicResponse};
 
 pub mod logica;
 
-use crate::nomina::{calcular_totales_conceptos, EjecutorConceptos};
+use crate::nomina::{
+    calcular_totales_conceptos, limitar_deducciones, EjecutorConceptos, EntradaFase,
+    RecorteDeduccion,
+};
 use crate::tipos::TipoNomina;
 use logica::cargador;
-use logica::memoria::{self, Base, Beneficiario, Movimiento};
+use logica::memoria::{self, Base, Beneficiario, ConceptoCalculado, FaseConcepto, Movimiento};
+use std::collections::HashMap;
 
 // El "Perceptrón" (Cache/Memoization)
 #[derive(Debug)]
@@ -30,6 +34,26 @@ pub struct Perceptron {
     pub base: Vec<memoria::Base>,
     pub movimientos: Vec<memoria::Movimiento>,
     pub beneficiarios: Vec<memoria::Beneficiario>,
+    pub ordenes_embargo: Vec<memoria::OrdenEmbargo>,
+    pub creditos: Vec<memoria::CreditoCuota>,
+
+    // Cobertura y distribución de resultados por fórmula del último ciclo
+    pub cobertura: crate::calc::cobertura::ReporteCobertura,
+
+    // Distribución de garantías del último ciclo, global o por partición (conciliación)
+    pub distribucion_garantias: Option<crate::calc::distribucion::ReporteDistribucion>,
+
+    // Deducciones recortadas por el límite legal en el último ciclo
+    pub recortes: Vec<crate::nomina::RecorteDeduccion>,
+
+    // Ejecución de embargos judiciales del último ciclo
+    pub embargos: crate::nomina::ReporteEmbargos,
+
+    // Cuotas de créditos descontadas y saldos resultantes del último ciclo
+    pub reporte_creditos: crate::nomina::ReporteCreditos,
+
+    // Violaciones de las reglas de validación previas a la exportación
+    pub validacion: crate::nomina::ReporteValidacion,
 
     // Configuración de Ejecución (Manifiesto)
     pub config: crate::model::Manifiesto,
@@ -45,6 +69,14 @@ impl Default for Perceptron {
             base: Vec::new(),
             movimientos: Vec::new(),
             beneficiarios: Vec::new(),
+            ordenes_embargo: Vec::new(),
+            creditos: Vec::new(),
+            cobertura: crate::calc::cobertura::ReporteCobertura::default(),
+            distribucion_garantias: None,
+            recortes: Vec::new(),
+            embargos: crate::nomina::ReporteEmbargos::default(),
+            reporte_creditos: crate::nomina::ReporteCreditos::default(),
+            validacion: crate::nomina::ReporteValidacion::default(),
             config: crate::model::Manifiesto::default(),
         }
     }
@@ -91,6 +123,261 @@ impl Perceptron {
         }
     }
 
+    /// Modo `explain`: carga solo el beneficiario indicado y reconstruye su cálculo
+    /// paso a paso. Las cargas de Base, Beneficiarios y Movimientos se restringen
+    /// por cédula; directiva, primas y conceptos se cargan completos.
+    pub async fn explicar_beneficiario(
+        &mut self,
+        cedula: &str,
+        tipo_nomina: TipoNomina,
+    ) -> Result<crate::calc::explicacion::TrazaCalculo, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.clone().ok_or("Cliente gRPC no conectado")?;
+
+        let mut config = self.config.clone();
+        config.restringir_a_cedula(cedula)?;
+
+        let mut c = cargador::Cargador::new(config.clone());
+        c.client = Some(client);
+
+        self.directiva = c.cargar_directiva().await?;
+        self.primas_funciones = c.cargar_primas_funciones().await?;
+        self.conceptos_nomina = c.cargar_conceptos().await?;
+        self.movimientos = c.cargar_movimientos().await?;
+        self.ordenes_embargo = if config.embargos.habilitar {
+            c.cargar_embargos()
+                .await?
+                .into_iter()
+                .filter(|o| o.cedula.trim() == cedula)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let base_cruda = c
+            .cargar_base_cruda()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("No se encontró registro Base para la cédula {}", cedula))?;
+
+        let beneficiario = c
+            .cargar_beneficiarios_crudos()
+            .await?
+            .into_iter()
+            .find(|b| b.cedula == cedula)
+            .ok_or_else(|| format!("No se encontró beneficiario con cédula {}", cedula))?;
+        if beneficiario.patterns != base_cruda.patterns {
+            logica::logger::log_warn(
+                "EXPLAIN",
+                &format!(
+                    "patterns del beneficiario ({}) difiere del de Base ({})",
+                    beneficiario.patterns, base_cruda.patterns
+                ),
+            );
+        }
+
+        self.creditos = if config.creditos.habilitar {
+            let creditos = match &config.creditos.archivo_saldos {
+                Some(ruta) => crate::nomina::credito::cargar_saldos(ruta)?,
+                None => c.cargar_creditos().await?,
+            };
+            creditos.into_iter().filter(|cr| cr.cedula.trim() == cedula).collect()
+        } else {
+            Vec::new()
+        };
+
+        let motor = crate::calc::motor::SentinelEngine::new(self.primas_funciones.clone());
+        let ejecutor = EjecutorConceptos::new(self.conceptos_nomina.clone())?
+            .para_ciclo(tipo_nomina, config.fecha_referencia());
+
+        let ctx = crate::calc::explicacion::ContextoExplicacion {
+            ciclo: &config.ciclo,
+            directivas: &self.directiva,
+            motor: &motor,
+            ejecutor: &ejecutor,
+            movimientos: &self.movimientos,
+            monto_aprobado_garantias: if config.aportes.habilitar {
+                config.aportes.monto_aprobado_garantias
+            } else {
+                0.0
+            },
+            porcentaje_maximo_deducciones: config.limites.porcentaje_maximo_deducciones,
+            ordenes_embargo: &self.ordenes_embargo,
+            creditos: &self.creditos,
+            porcentaje_maximo_embargo: config.embargos.porcentaje_maximo()?,
+        };
+
+        Ok(crate::calc::explicacion::explicar(
+            &ctx,
+            base_cruda,
+            beneficiario,
+            tipo_nomina,
+        ))
+    }
+
+    /// Modo `simular`: carga una sola vez la población (Base, Beneficiarios y
+    /// Movimientos) con el manifiesto actual y la calcula con las referencias
+    /// (Directiva, PrimasFunciones, Conceptos) del manifiesto actual y de
+    /// `alternativo`. No modifica la memoria de trabajo del ciclo.
+    pub async fn simular(
+        &mut self,
+        alternativo: &crate::model::Manifiesto,
+        tipo_nomina: TipoNomina,
+        top: usize,
+    ) -> Result<crate::calc::simulacion::ResultadoSimulacion, Box<dyn std::error::Error + Send + Sync>> {
+        use crate::calc::simulacion::{calcular_escenario, comparar, Escenario, Poblacion};
+
+        let client = self.client.clone().ok_or("Cliente gRPC no conectado")?;
+
+        println!("PASO 1: CARGA DE POBLACIÓN");
+        println!("{:-<80}", "");
+        let mut c = cargador::Cargador::new(self.config.clone());
+        c.client = Some(client.clone());
+        let poblacion = Poblacion {
+            movimientos: c.cargar_movimientos().await?,
+            bases: c.cargar_base_cruda().await?,
+            beneficiarios: c.cargar_beneficiarios_crudos().await?,
+        };
+        println!(
+            "  • {:<20} : {:>10} registros | OK",
+            "Base (Personal)",
+            poblacion.bases.len()
+        );
+        println!(
+            "  • {:<20} : {:>10} registros | OK",
+            "Beneficiarios",
+            poblacion.beneficiarios.len()
+        );
+        println!(
+            "  • {:<20} : {:>10} registros | OK",
+            "Movimientos",
+            poblacion.movimientos.len()
+        );
+        println!();
+
+        let mut resultados = Vec::with_capacity(2);
+        let mut directivas_base = Vec::new();
+        for (paso, config) in [(2, &self.config), (3, alternativo)] {
+            println!("PASO {}: ESCENARIO '{}'", paso, config.nombre);
+            println!("{:-<80}", "");
+            let mut c = cargador::Cargador::new(config.clone());
+            c.client = Some(client.clone());
+            let directivas = c.cargar_directiva().await?;
+            let primas = c.cargar_primas_funciones().await?;
+            let conceptos = c.cargar_conceptos().await?;
+            println!(
+                "  • {:<20} : {:>10} registros | OK",
+                "Directiva",
+                directivas.len()
+            );
+            println!(
+                "  • {:<20} : {:>10} registros | OK",
+                "Primas Funciones",
+                primas.len()
+            );
+            println!(
+                "  • {:<20} : {:>10} registros | OK",
+                "Conceptos",
+                conceptos.len()
+            );
+
+            let motor = crate::calc::motor::SentinelEngine::new(primas);
+            let ejecutor = EjecutorConceptos::new(conceptos)?
+                .para_ciclo(tipo_nomina, config.fecha_referencia());
+            let escenario = Escenario {
+                nombre: &config.nombre,
+                directivas: &directivas,
+                motor: &motor,
+                ejecutor: &ejecutor,
+            };
+            let beneficiarios = calcular_escenario(
+                &escenario,
+                &poblacion,
+                tipo_nomina,
+                config.limites.porcentaje_maximo_deducciones,
+            );
+            println!(
+                "  • {:<20} : {:>18.2} | OK",
+                "Costo (Σ neto)",
+                beneficiarios.iter().map(|b| b.neto).sum::<f64>()
+            );
+            println!();
+
+            // Los nombres de grado del reporte salen de la directiva base
+            if paso == 2 {
+                directivas_base = directivas;
+            }
+            resultados.push(beneficiarios);
+        }
+
+        Ok(comparar(
+            (&self.config.nombre, &alternativo.nombre),
+            &self.config.ciclo,
+            tipo_nomina,
+            &resultados[0],
+            &resultados[1],
+            &directivas_base,
+            top,
+        ))
+    }
+
+    /// Modo `proyectar`: carga la población Base y sus referencias y la avanza
+    /// mes a mes (`config.proyeccion`) desde la fecha de referencia del ciclo.
+    pub async fn proyectar(
+        &mut self,
+        tipo_nomina: TipoNomina,
+    ) -> Result<crate::calc::proyeccion::ResultadoProyeccion, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.client.clone().ok_or("Cliente gRPC no conectado")?;
+        let config = self.config.clone();
+
+        println!("PASO 1: CARGA DE REFERENCIAS Y POBLACIÓN");
+        println!("{:-<80}", "");
+        let mut c = cargador::Cargador::new(config.clone());
+        c.client = Some(client);
+        self.directiva = c.cargar_directiva().await?;
+        self.primas_funciones = c.cargar_primas_funciones().await?;
+        self.conceptos_nomina = c.cargar_conceptos().await?;
+        self.base = c.cargar_base_cruda().await?;
+        for (nombre, registros) in [
+            ("Directiva", self.directiva.len()),
+            ("Primas Funciones", self.primas_funciones.len()),
+            ("Conceptos", self.conceptos_nomina.len()),
+            ("Base (Personal)", self.base.len()),
+        ] {
+            println!("  • {:<20} : {:>10} registros | OK", nombre, registros);
+        }
+        println!();
+
+        println!("PASO 2: PROYECCIÓN ({} MESES)", config.proyeccion.meses);
+        println!("{:-<80}", "");
+        let t = std::time::Instant::now();
+        let motor = crate::calc::motor::SentinelEngine::new(self.primas_funciones.clone());
+        let ejecutor = EjecutorConceptos::new(self.conceptos_nomina.clone())?
+            .para_ciclo(tipo_nomina, config.fecha_referencia());
+        let meses = crate::calc::proyeccion::proyectar(
+            &self.base,
+            &self.directiva,
+            &motor,
+            &ejecutor,
+            &config.proyeccion,
+            tipo_nomina,
+            config.fecha_referencia(),
+        );
+        println!(
+            "  • {:<20} : {:>10} meses | OK",
+            "Proyección",
+            meses.len()
+        );
+        println!("    (Tiempo Paso 2: {:.2?})", t.elapsed());
+
+        Ok(crate::calc::proyeccion::ResultadoProyeccion {
+            ciclo: config.ciclo.clone(),
+            tipo_nomina,
+            fecha_generacion: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            meses,
+        })
+    }
+
     /// Orquestador Principal del Ciclo de Nómina
     pub async fn ejecutar_ciclo_carga(
         &mut self,
@@ -101,6 +388,7 @@ impl Perceptron {
         // Clonar configuración para pasarla a los hilos
         let config_ref = self.config.clone();
         let client = self.client.clone().ok_or("Cliente gRPC no conectado")?;
+        let porcentaje_maximo_embargo = config_ref.embargos.porcentaje_maximo()?;
 
         // ---------------------------------------------------------------------
         // PASO 1: CARGA DE REFERENCIAS
@@ -167,6 +455,26 @@ impl Perceptron {
             "  • {:<20} : {:>10} | LISTO",
             "Motor de Cálculo", "Inicializado"
         );
+
+        // Casos de prueba de primas: abortar antes de la carga masiva si no cuadran
+        let casos_prueba = config_ref.cargar_casos_prueba()?;
+        if casos_prueba.iter().any(|c| !c.primas.is_empty()) {
+            let fallos = motor_arc.verificar_casos(
+                &casos_prueba,
+                &self.directiva,
+                config_ref.tolerancia_casos,
+            );
+            if !fallos.is_empty() {
+                let msg = crate::calc::casos_prueba::resumen_fallos("primas", &fallos);
+                logica::logger::log_error("CASOS_PRUEBA", &msg);
+                return Err(msg.into());
+            }
+            println!(
+                "  • {:<20} : {:>10} casos | OK",
+                "Casos Primas",
+                casos_prueba.iter().filter(|c| !c.primas.is_empty()).count()
+            );
+        }
         println!();
 
         // ---------------------------------------------------------------------
@@ -230,6 +538,23 @@ impl Perceptron {
         self.conceptos_nomina = res_conc??;
         self.movimientos = movimientos_cargados;
 
+        if config_ref.embargos.habilitar {
+            let mut c_emb = cargador::Cargador::new(config_ref.clone());
+            c_emb.client = Some(client.clone());
+            self.ordenes_embargo = c_emb.cargar_embargos().await?;
+        }
+
+        if config_ref.creditos.habilitar {
+            self.creditos = match &config_ref.creditos.archivo_saldos {
+                Some(ruta) => crate::nomina::credito::cargar_saldos(ruta)?,
+                None => {
+                    let mut c_cred = cargador::Cargador::new(config_ref.clone());
+                    c_cred.client = Some(client.clone());
+                    c_cred.cargar_creditos().await?
+                }
+            };
+        }
+
         println!(
             "  • {:<20} : {:>10} registros | OK",
             "Base (Personal)",
@@ -251,6 +576,28 @@ impl Perceptron {
         );
         print_filtro(&self.config, "IPSFA_CConceptos");
 
+        if config_ref.embargos.habilitar {
+            println!(
+                "  • {:<20} : {:>10} registros | OK",
+                "Órdenes Embargo",
+                self.ordenes_embargo.len()
+            );
+            print_filtro(&self.config, "IPSFA_CEmbargos");
+        }
+
+        if config_ref.creditos.habilitar {
+            println!(
+                "  • {:<20} : {:>10} registros | OK ({})",
+                "Créditos",
+                self.creditos.len(),
+                config_ref
+                    .creditos
+                    .archivo_saldos
+                    .as_deref()
+                    .unwrap_or("IPSFA_CCreditos")
+            );
+        }
+
         println!("    (Tiempo Paso 2: {:.2?})", t_p2.elapsed());
         println!();
 
@@ -264,7 +611,39 @@ impl Perceptron {
         
         let conceptos_nomina = self.conceptos_nomina.clone();
 
-        let ejecutor = EjecutorConceptos::new(conceptos_nomina);
+        let ejecutor = EjecutorConceptos::new(conceptos_nomina)
+            .inspect_err(|e| logica::logger::log_error("CONCEPTOS", e))?
+            .para_ciclo(tipo_nomina, config_ref.fecha_referencia());
+        let conceptos_fuera = self.conceptos_nomina.len() - ejecutor.get_codigos().len();
+        if conceptos_fuera > 0 {
+            println!(
+                "  • {:<20} : {:>10} conceptos | no aplican a {} / {}",
+                "Conceptos Excluidos",
+                conceptos_fuera,
+                tipo_nomina,
+                config_ref.fecha_referencia()
+            );
+        }
+
+        if casos_prueba.iter().any(|c| !c.conceptos.is_empty()) {
+            let fallos = ejecutor.verificar_casos(
+                &casos_prueba,
+                &self.directiva,
+                motor_arc.as_ref(),
+                config_ref.tolerancia_casos,
+            );
+            if !fallos.is_empty() {
+                let msg = crate::calc::casos_prueba::resumen_fallos("conceptos", &fallos);
+                logica::logger::log_error("CASOS_PRUEBA", &msg);
+                return Err(msg.into());
+            }
+            println!(
+                "  • {:<20} : {:>10} casos | OK",
+                "Casos Conceptos",
+                casos_prueba.iter().filter(|c| !c.conceptos.is_empty()).count()
+            );
+        }
+
         let conceptos_calculados_map = ejecutor.ejecutar(&self.base);
 
         let total_conceptos_cargados = self.conceptos_nomina.len();
@@ -313,6 +692,7 @@ impl Perceptron {
         self.beneficiarios = c_ben
             .cargar_beneficiarios(&self.base, &self.movimientos, monto_aprobado, &self.directiva, motor_arc.as_ref())
             .await?;
+        self.distribucion_garantias = c_ben.distribucion_garantias.take();
 
         // ---------------------------------------------------------------------
         // PASO 3.5: APLICAR CONCEPTOS Y CALCULAR NETO
@@ -320,65 +700,38 @@ impl Perceptron {
         println!("PASO 3.5: APLICAR CONCEPTOS Y CALCULAR NETO");
         println!("{:-<80}", "");
 
-        for beneficiario in &mut self.beneficiarios {
-            // Buscar conceptos calculados para este beneficiario
-            if let Some(conceptos) = conceptos_calculados_map.get(&beneficiario.base.patterns) {
-                let (total_asig, total_ded) = calcular_totales_conceptos(conceptos);
-                
-                beneficiario.conceptos_calculados = Some(
-                    conceptos.iter()
-                        .map(|c| (c.codigo.clone(), c.clone()))
-                        .collect()
-                );
-                beneficiario.total_asignaciones = total_asig;
-                beneficiario.total_deducciones = total_ded;
-
-                // Calcular neto según tipo de nómina
-                match tipo_nomina {
-                    TipoNomina::Npr => {
-                        // NPR: neto = garantías (lógica actual de prestaciones)
-                        beneficiario.neto = beneficiario.base.garantias;
-                        beneficiario.porcentaje = 100.0;
-                    }
-                    TipoNomina::Nact => {
-                        // NACT: neto = sueldo integral (100%)
-                        beneficiario.porcentaje = 100.0;
-                        let base_neto = beneficiario.base.sueldo_integral;
-                        beneficiario.neto = base_neto + total_asig - total_ded;
-                    }
-                    TipoNomina::Nrcp => {
-                        // NRCP: neto = (integral × porcentaje/100) + asig - deduc
-                        let pct = beneficiario.porcentaje / 100.0;
-                        let base_neto = beneficiario.base.sueldo_integral * pct;
-                        beneficiario.neto = base_neto + total_asig - total_ded;
-                    }
-                    TipoNomina::Nfcp => {
-                        // NFCP: se calcula después con lógica especial de familiares
-                        beneficiario.neto = 0.0;
-                    }
-                    TipoNomina::Npat => {
-                        // NPAT: Nómina Patria - no aplica aquí (usa proceso separado)
-                        beneficiario.neto = 0.0;
-                    }
-                }
-            } else {
-                // Si no hay conceptos, inicializar en 0
-                beneficiario.conceptos_calculados = None;
-                beneficiario.total_asignaciones = 0.0;
-                beneficiario.total_deducciones = 0.0;
-                beneficiario.neto = match tipo_nomina {
-                    TipoNomina::Npr => beneficiario.base.garantias,
-                    TipoNomina::Nact => beneficiario.base.sueldo_integral,
-                    TipoNomina::Nrcp => {
-                        let pct = beneficiario.porcentaje / 100.0;
-                        beneficiario.base.sueldo_integral * pct
-                    }
-                    TipoNomina::Nfcp => 0.0,
-                    TipoNomina::Npat => 0.0,
-                };
-                beneficiario.porcentaje = 100.0;
+        // Conceptos pre-fusión por beneficiario (buscados por patterns) más las
+        // cuotas de créditos del ciclo (una sola vez por cédula)
+        let mut cuotas_creditos =
+            crate::nomina::credito::cuotas_del_ciclo(
+                &self.creditos,
+                &config_ref.ciclo,
+                tipo_nomina,
+            );
+        let bases_pre: HashMap<&str, &Base> = self
+            .base
+            .iter()
+            .filter(|b| !b.patterns.is_empty())
+            .map(|b| (b.patterns.as_str(), b))
+            .collect();
+        let mut conceptos_pre = conceptos_pre_fusion(
+            &self.beneficiarios,
+            &bases_pre,
+            &conceptos_calculados_map,
+            &ejecutor,
+        );
+        for (b, pre) in self.beneficiarios.iter().zip(conceptos_pre.iter_mut()) {
+            if let Some(cuotas) = cuotas_creditos.remove(&b.cedula) {
+                pre.get_or_insert_with(Vec::new).extend(cuotas);
             }
         }
+        self.recortes = aplicar_conceptos_por_fases(
+            &mut self.beneficiarios,
+            conceptos_pre,
+            &ejecutor,
+            tipo_nomina,
+            config_ref.limites.porcentaje_maximo_deducciones,
+        );
 
         println!(
             "  • {:<20} : {:>10} registros | OK",
@@ -387,6 +740,104 @@ impl Perceptron {
         );
         print_filtro(&self.config, "IPSFA_CBeneficiarios");
 
+        if config_ref.limites.porcentaje_maximo_deducciones > 0.0 {
+            let afectados: std::collections::HashSet<&str> =
+                self.recortes.iter().map(|r| r.cedula.as_str()).collect();
+            println!(
+                "  • {:<20} : {:>10} beneficiarios | {:.2} recortado (límite {}%)",
+                "Límite Deducciones",
+                afectados.len(),
+                self.recortes.iter().map(|r| r.recortado).sum::<f64>(),
+                config_ref.limites.porcentaje_maximo_deducciones
+            );
+        }
+
+        // Embargos judiciales sobre el neto final
+        if config_ref.embargos.habilitar {
+            self.embargos = crate::nomina::aplicar_embargos(
+                &mut self.beneficiarios,
+                &self.ordenes_embargo,
+                porcentaje_maximo_embargo,
+            );
+            println!(
+                "  • {:<20} : {:>10} órdenes | {} pagos, {:.2} retenido",
+                "Embargos",
+                self.embargos.ejecuciones.len(),
+                self.embargos.pagos.len(),
+                self.embargos.total_aplicado()
+            );
+        }
+
+        // Reglas de validación del manifiesto sobre el neto final (después de
+        // créditos y embargos): los excluidos no llegan al exportador, no
+        // reducen saldos de créditos y sus embargos no se pagan
+        if !config_ref.validaciones.is_empty() {
+            self.validacion = crate::nomina::validacion::validar(
+                &mut self.beneficiarios,
+                &self.directiva,
+                &config_ref.validaciones,
+            );
+            for ((regla, severidad), n) in self.validacion.resumen() {
+                logica::logger::log_warn(
+                    "VALIDACION",
+                    &format!("{}: {} registros ({})", regla, n, severidad),
+                );
+            }
+            println!(
+                "  • {:<20} : {:>10} violaciones | {} excluidos",
+                "Validación",
+                self.validacion.violaciones.len(),
+                self.validacion.excluidos.len()
+            );
+            if self.validacion.abortar() {
+                return Err(format!(
+                    "Validación abortó el ciclo: {} violaciones de reglas con severidad 'abortar' (ver {})",
+                    self.validacion
+                        .violaciones
+                        .iter()
+                        .filter(|v| v.severidad == crate::nomina::validacion::Severidad::Abortar)
+                        .count(),
+                    crate::nomina::validacion::NOMBRE_ARCHIVO_VALIDACION
+                )
+                .into());
+            }
+            if !self.validacion.excluidos.is_empty() {
+                self.embargos.excluir(&self.validacion.excluidos);
+            }
+        }
+
+        if config_ref.creditos.habilitar {
+            self.reporte_creditos = crate::nomina::credito::actualizar_saldos(
+                &self.creditos,
+                &self.beneficiarios,
+                &config_ref.ciclo,
+                tipo_nomina,
+            );
+            println!(
+                "  • {:<20} : {:>10} créditos | {:.2} descontado",
+                "Cuotas Créditos",
+                self.reporte_creditos.movimientos.len(),
+                self.reporte_creditos.total_descontado()
+            );
+        }
+
+        // Cobertura de fórmulas (primas y conceptos) sobre los beneficiarios finales
+        self.cobertura = crate::calc::cobertura::ReporteCobertura::desde(
+            &self.beneficiarios,
+            &motor_arc.definiciones(),
+            &ejecutor.definiciones(),
+        );
+        let alertas = self.cobertura.alertas();
+        for alerta in &alertas {
+            logica::logger::log_warn("COBERTURA", alerta);
+        }
+        println!(
+            "  • {:<20} : {:>10} fórmulas | {} alertas",
+            "Cobertura",
+            self.cobertura.primas.len() + self.cobertura.conceptos.len(),
+            alertas.len()
+        );
+
         println!("    (Tiempo Paso 3: {:.2?})", t_p3.elapsed());
         Ok(())
     }
@@ -484,6 +935,221 @@ impl Perceptron {
     }
 }
 
+/// Conceptos pre-fusión de cada beneficiario, buscados por patterns. Se evalúan
+/// sin conocer el status; si alguno no admite el del beneficiario se reevalúan
+/// sobre su Base pre-fusión con el status, de modo que los conceptos que
+/// referencian a uno excluido lo vean en 0.0.
+pub fn conceptos_pre_fusion(
+    beneficiarios: &[Beneficiario],
+    bases: &HashMap<&str, &Base>,
+    calculados: &HashMap<String, Vec<ConceptoCalculado>>,
+    ejecutor: &EjecutorConceptos,
+) -> Vec<Option<Vec<ConceptoCalculado>>> {
+    beneficiarios
+        .iter()
+        .map(|b| {
+            let previos = calculados.get(&b.base.patterns)?;
+            if !ejecutor.excluye_status(b.status_id) {
+                return Some(previos.clone());
+            }
+            let base = bases.get(b.base.patterns.as_str()).copied().unwrap_or(&b.base);
+            Some(ejecutor.ejecutar_con_status(base, b.status_id))
+        })
+        .collect()
+}
+
+/// Aplica los conceptos pre-fusión y calcula el neto; luego evalúa las fases
+/// post-fusión y post-neto (si hay conceptos declarados) recalculando el neto
+/// tras cada una con todos los conceptos acumulados. El límite legal de
+/// deducciones se aplica antes de post-neto, que ve el neto ya recortado; las
+/// deducciones que agrega post-neto se limitan después sin tocar las anteriores.
+pub fn aplicar_conceptos_por_fases(
+    beneficiarios: &mut [Beneficiario],
+    mut acumulados: Vec<Option<Vec<ConceptoCalculado>>>,
+    ejecutor: &EjecutorConceptos,
+    tipo_nomina: TipoNomina,
+    porcentaje_maximo_deducciones: f64,
+) -> Vec<RecorteDeduccion> {
+    // El cálculo del neto normaliza el porcentaje; las fases posteriores ven el original
+    let porcentajes: Vec<f64> = beneficiarios.iter().map(|b| b.porcentaje).collect();
+
+    for (beneficiario, conceptos) in beneficiarios.iter_mut().zip(&acumulados) {
+        aplicar_conceptos_y_neto(beneficiario, conceptos.as_deref(), tipo_nomina);
+    }
+    aplicar_fase(
+        beneficiarios,
+        &mut acumulados,
+        &porcentajes,
+        ejecutor,
+        FaseConcepto::PostFusion,
+        tipo_nomina,
+    );
+
+    // Límite legal sobre las deducciones hasta post-fusión
+    let mut recortes = Vec::new();
+    for (i, conceptos) in acumulados.iter_mut().enumerate() {
+        let Some(conceptos) = conceptos else { continue };
+        recortes.extend(limitar_y_recalcular(
+            &mut beneficiarios[i],
+            conceptos,
+            0,
+            porcentajes[i],
+            ejecutor,
+            tipo_nomina,
+            porcentaje_maximo_deducciones,
+        ));
+    }
+
+    // Post-neto ve el neto ya limitado; sus deducciones se limitan aparte
+    let desde: Vec<usize> = acumulados.iter().map(|c| c.as_ref().map_or(0, Vec::len)).collect();
+    aplicar_fase(
+        beneficiarios,
+        &mut acumulados,
+        &porcentajes,
+        ejecutor,
+        FaseConcepto::PostNeto,
+        tipo_nomina,
+    );
+    for (i, conceptos) in acumulados.iter_mut().enumerate() {
+        let Some(conceptos) = conceptos else { continue };
+        recortes.extend(limitar_y_recalcular(
+            &mut beneficiarios[i],
+            conceptos,
+            desde[i],
+            porcentajes[i],
+            ejecutor,
+            tipo_nomina,
+            porcentaje_maximo_deducciones,
+        ));
+    }
+    recortes
+}
+
+/// Evalúa una fase post-fusión (si tiene conceptos) y recalcula el neto con todos
+/// los conceptos acumulados.
+fn aplicar_fase(
+    beneficiarios: &mut [Beneficiario],
+    acumulados: &mut [Option<Vec<ConceptoCalculado>>],
+    porcentajes: &[f64],
+    ejecutor: &EjecutorConceptos,
+    fase: FaseConcepto,
+    tipo_nomina: TipoNomina,
+) {
+    if !ejecutor.tiene_fase(fase) {
+        return;
+    }
+
+    let nuevos = {
+        let entradas: Vec<EntradaFase> = beneficiarios
+            .iter()
+            .zip(acumulados.iter())
+            .zip(porcentajes)
+            .map(|((b, previos), &porcentaje)| EntradaFase {
+                beneficiario: b,
+                porcentaje,
+                previos: previos.as_deref().unwrap_or(&[]),
+            })
+            .collect();
+        ejecutor.ejecutar_fase(fase, &entradas)
+    };
+
+    for (i, calculados) in nuevos.into_iter().enumerate() {
+        acumulados[i].get_or_insert_with(Vec::new).extend(calculados);
+        beneficiarios[i].porcentaje = porcentajes[i];
+        aplicar_conceptos_y_neto(&mut beneficiarios[i], acumulados[i].as_deref(), tipo_nomina);
+    }
+}
+
+/// Aplica el límite legal de deducciones recortando solo los conceptos desde
+/// `desde` y, si hubo recortes, recalcula totales y neto con el porcentaje original.
+pub fn limitar_y_recalcular(
+    beneficiario: &mut Beneficiario,
+    conceptos: &mut [ConceptoCalculado],
+    desde: usize,
+    porcentaje: f64,
+    ejecutor: &EjecutorConceptos,
+    tipo_nomina: TipoNomina,
+    porcentaje_maximo_deducciones: f64,
+) -> Vec<RecorteDeduccion> {
+    let recortes = limitar_deducciones(
+        beneficiario,
+        &mut conceptos[desde..],
+        porcentaje_maximo_deducciones,
+        tipo_nomina,
+        |codigo| ejecutor.prioridad(codigo),
+    );
+    if !recortes.is_empty() {
+        beneficiario.porcentaje = porcentaje;
+        aplicar_conceptos_y_neto(beneficiario, Some(conceptos), tipo_nomina);
+    }
+    recortes
+}
+
+/// Aplica los conceptos calculados de un beneficiario (totales de asignaciones y
+/// deducciones) y calcula su neto según el tipo de nómina.
+pub fn aplicar_conceptos_y_neto(
+    beneficiario: &mut Beneficiario,
+    conceptos: Option<&[ConceptoCalculado]>,
+    tipo_nomina: TipoNomina,
+) {
+    if let Some(conceptos) = conceptos {
+        let (total_asig, total_ded) = calcular_totales_conceptos(conceptos);
+
+        beneficiario.conceptos_calculados = Some(
+            conceptos.iter()
+                .map(|c| (c.codigo.clone(), c.clone()))
+                .collect()
+        );
+        beneficiario.total_asignaciones = total_asig;
+        beneficiario.total_deducciones = total_ded;
+
+        // Calcular neto según tipo de nómina
+        match tipo_nomina {
+            TipoNomina::Npr => {
+                // NPR: neto = garantías (lógica actual de prestaciones)
+                beneficiario.neto = beneficiario.base.garantias;
+                beneficiario.porcentaje = 100.0;
+            }
+            TipoNomina::Nact => {
+                // NACT: neto = sueldo integral (100%)
+                beneficiario.porcentaje = 100.0;
+                let base_neto = beneficiario.base.sueldo_integral;
+                beneficiario.neto = base_neto + total_asig - total_ded;
+            }
+            TipoNomina::Nrcp => {
+                // NRCP: neto = (integral × porcentaje/100) + asig - deduc
+                let pct = beneficiario.porcentaje / 100.0;
+                let base_neto = beneficiario.base.sueldo_integral * pct;
+                beneficiario.neto = base_neto + total_asig - total_ded;
+            }
+            TipoNomina::Nfcp => {
+                // NFCP: se calcula después con lógica especial de familiares
+                beneficiario.neto = 0.0;
+            }
+            TipoNomina::Npat => {
+                // NPAT: Nómina Patria - no aplica aquí (usa proceso separado)
+                beneficiario.neto = 0.0;
+            }
+        }
+    } else {
+        // Si no hay conceptos, inicializar en 0
+        beneficiario.conceptos_calculados = None;
+        beneficiario.total_asignaciones = 0.0;
+        beneficiario.total_deducciones = 0.0;
+        beneficiario.neto = match tipo_nomina {
+            TipoNomina::Npr => beneficiario.base.garantias,
+            TipoNomina::Nact => beneficiario.base.sueldo_integral,
+            TipoNomina::Nrcp => {
+                let pct = beneficiario.porcentaje / 100.0;
+                beneficiario.base.sueldo_integral * pct
+            }
+            TipoNomina::Nfcp => 0.0,
+            TipoNomina::Npat => 0.0,
+        };
+        beneficiario.porcentaje = 100.0;
+    }
+}
+
 // --- Helpers para conversión Protobuf -> JSON ---
 
 pub fn proto_value_to_json(val: prost_types::Value) -> serde_json::Value {
@@ -10,6 +10,17 @@ pub enum Componente {
 }
 
 impl Componente {
+    pub fn desde_id(id: u32) -> Option<Self> {
+        match id {
+            1 => Some(Componente::Ejercito),
+            2 => Some(Componente::Armada),
+            3 => Some(Componente::Aviacion),
+            4 => Some(Componente::GuardiaNacional),
+            5 => Some(Componente::Milicia),
+            _ => None,
+        }
+    }
+
     pub fn description(&self) -> &str {
         match self {
             Componente::Ejercito => "Ejército Bolivariano",
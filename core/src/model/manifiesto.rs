@@ -1,3 +1,8 @@
+use crate::banco::formato::LimiteArchivo;
+use crate::calc::casos_prueba::CasoPrueba;
+use crate::calc::distribucion::CriterioParticion;
+use crate::calc::proyeccion::{ReglaAscenso, RetiroProgramado};
+use crate::nomina::validacion::ReglaValidacion;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -10,10 +15,142 @@ pub struct AporteConfig {
     #[serde(default)]
     pub monto_aprobado_garantias: f64,
 
+    /// Criterio de partición de `montos_aprobados_por_particion`.
+    #[serde(default)]
+    pub particion_garantias: CriterioParticion,
+
+    /// Monto aprobado por partición (id de componente o código de banco). Si no
+    /// está vacío, reemplaza a `monto_aprobado_garantias` y la distribución se
+    /// hace por separado dentro de cada partición.
+    #[serde(default)]
+    pub montos_aprobados_por_particion: HashMap<String, f64>,
+
     #[serde(default)]
     pub generar_apertura_con_aporte: bool,
 }
 
+/// Límites legales sobre las deducciones de cada beneficiario.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LimitesConfig {
+    /// Máximo de deducciones como porcentaje del bruto (0 = sin límite).
+    #[serde(default)]
+    pub porcentaje_maximo_deducciones: f64,
+}
+
+/// Embargos judiciales aplicados sobre el neto (carga IPSFA_CEmbargos).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbargoConfig {
+    #[serde(default)]
+    pub habilitar: bool,
+
+    /// Máximo embargable como porcentaje del neto, sumando todas las órdenes.
+    /// Obligatorio con `habilitar`: no hay un tope por defecto.
+    #[serde(default)]
+    pub porcentaje_maximo_neto: Option<f64>,
+}
+
+impl EmbargoConfig {
+    /// Máximo embargable del ciclo (0 si los embargos no están habilitados).
+    pub fn porcentaje_maximo(&self) -> Result<f64, String> {
+        if !self.habilitar {
+            return Ok(0.0);
+        }
+        match self.porcentaje_maximo_neto {
+            None => Err(
+                "embargos.habilitar requiere embargos.porcentaje_maximo_neto (máximo embargable del neto)"
+                    .to_string(),
+            ),
+            Some(p) if p <= 0.0 || p > 100.0 => Err(format!(
+                "embargos.porcentaje_maximo_neto {} fuera de rango (0, 100]",
+                p
+            )),
+            Some(p) => Ok(p),
+        }
+    }
+}
+
+/// Créditos descontados por cuotas.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CreditoConfig {
+    #[serde(default)]
+    pub habilitar: bool,
+
+    /// `saldos_creditos.json` de un ciclo anterior. Si se omite se usa IPSFA_CCreditos.
+    #[serde(default)]
+    pub archivo_saldos: Option<String>,
+}
+
+/// Proyección del costo de la nómina a varios meses (`sandra proyectar`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProyeccionConfig {
+    #[serde(default = "default_meses_proyeccion")]
+    pub meses: u32,
+
+    /// Retiro al cumplir estos años de servicio (0 = sin retiro automático).
+    #[serde(default)]
+    pub anos_servicio_retiro: u32,
+
+    #[serde(default)]
+    pub retiros: Vec<RetiroProgramado>,
+
+    #[serde(default)]
+    pub ascensos: Vec<ReglaAscenso>,
+}
+
+fn default_meses_proyeccion() -> u32 {
+    12
+}
+
+impl Default for ProyeccionConfig {
+    fn default() -> Self {
+        Self {
+            meses: default_meses_proyeccion(),
+            anos_servicio_retiro: 0,
+            retiros: Vec::new(),
+            ascensos: Vec::new(),
+        }
+    }
+}
+
+/// Umbrales de anomalías de `sandra diff` entre la nómina de dos ciclos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiferenciasConfig {
+    /// Variación del neto (`sueldo_total`), en porcentaje, a partir de la cual se marca.
+    #[serde(default = "default_variacion_neto_pct")]
+    pub variacion_neto_pct: f64,
+
+    /// Neto mínimo para marcar un cambio de cuenta bancaria (0 = todo cambio de cuenta).
+    #[serde(default)]
+    pub monto_cambio_cuenta: f64,
+
+    /// Marca cambios de `sueldo_base` sin cambio de `grado_id`.
+    #[serde(default = "default_true")]
+    pub sueldo_base_sin_ascenso: bool,
+
+    /// Columnas que no se reportan como campos modificados (p. ej. antigüedades).
+    #[serde(default)]
+    pub ignorar_campos: Vec<String>,
+}
+
+fn default_variacion_neto_pct() -> f64 {
+    20.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for DiferenciasConfig {
+    fn default() -> Self {
+        Self {
+            variacion_neto_pct: default_variacion_neto_pct(),
+            monto_cambio_cuenta: 0.0,
+            sueldo_base_sin_ascenso: true,
+            ignorar_campos: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SalidaConfig {
     #[serde(default = "default_carpeta_destino")]
@@ -30,6 +167,37 @@ pub struct SalidaConfig {
 
     #[serde(default)]
     pub bancos: Vec<String>, // Códigos de banco: ["0102", "0177", "0175", ...]
+
+    /// Diseño de archivo por código de banco (JSON como los de `schema/bancos/`);
+    /// reemplaza el formato incorporado de ese banco.
+    #[serde(default)]
+    pub disenos: HashMap<String, String>,
+
+    /// Máximo de registros y de total por archivo, por código de banco; el
+    /// archivo que los supera se divide en `_001`, `_002`, ...
+    #[serde(default)]
+    pub limites: HashMap<String, LimiteArchivo>,
+
+    /// Agrega `numero_cuenta` a la nómina CSV (la usa `sandra diff` para
+    /// detectar cambios de cuenta). Desactivada por defecto.
+    #[serde(default)]
+    pub columna_numero_cuenta: bool,
+
+    /// RIF de la empresa ordenante para las cabeceras de los TXT bancarios.
+    #[serde(default)]
+    pub rif: Option<String>,
+}
+
+impl SalidaConfig {
+    /// RIF de la empresa ordenante (`salida.rif`), sin separadores. Los diseños
+    /// que lo llevan en la cabecera (Mercantil, Banco del Tesoro) no se generan
+    /// sin él.
+    pub fn rif_empresa(&self) -> Result<Option<String>, String> {
+        self.rif
+            .as_deref()
+            .map(|r| crate::util::cedula::rif(r).map_err(|e| format!("salida.rif: {}", e)))
+            .transpose()
+    }
 }
 
 fn default_carpeta_destino() -> String {
@@ -40,6 +208,53 @@ fn default_nivel_compresion() -> i32 {
     3
 }
 
+/// Nómina Patria (`sandra patria`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatriaConfig {
+    /// Códigos de banco admitidos; se genera un TXT por banco.
+    #[serde(default = "default_bancos_patria")]
+    pub bancos: Vec<String>,
+
+    /// RIF del ordenante en la cabecera; obligatorio para generar la nómina.
+    #[serde(default)]
+    pub rif: Option<String>,
+
+    #[serde(default = "default_moneda_patria")]
+    pub moneda: String,
+
+    /// Escribe en el TXT también las cédulas repetidas. Por defecto solo van a
+    /// `patria_duplicados.csv`, para no pagarlas dos veces.
+    #[serde(default)]
+    pub incluir_duplicados: bool,
+}
+
+impl PatriaConfig {
+    /// RIF del ordenante (`patria.rif`), sin separadores.
+    pub fn rif_ordenante(&self) -> Result<String, String> {
+        let rif = self.rif.as_deref().ok_or("patria.rif es obligatorio")?;
+        crate::util::cedula::rif(rif).map_err(|e| format!("patria.rif: {}", e))
+    }
+}
+
+impl Default for PatriaConfig {
+    fn default() -> Self {
+        Self {
+            bancos: default_bancos_patria(),
+            rif: None,
+            moneda: default_moneda_patria(),
+            incluir_duplicados: false,
+        }
+    }
+}
+
+fn default_bancos_patria() -> Vec<String> {
+    vec!["0102".to_string()]
+}
+
+fn default_moneda_patria() -> String {
+    "VES".to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Manifiesto {
     pub nombre: String,
@@ -68,6 +283,44 @@ pub struct Manifiesto {
 
     #[serde(default)]
     pub salida: SalidaConfig,
+
+    #[serde(default)]
+    pub limites: LimitesConfig,
+
+    #[serde(default)]
+    pub embargos: EmbargoConfig,
+
+    #[serde(default)]
+    pub creditos: CreditoConfig,
+
+    #[serde(default)]
+    pub proyeccion: ProyeccionConfig,
+
+    #[serde(default)]
+    pub diferencias: DiferenciasConfig,
+
+    #[serde(default)]
+    pub patria: PatriaConfig,
+
+    /// Reglas de validación evaluadas antes de exportar (ver `nomina::validacion`).
+    #[serde(default = "crate::nomina::validacion::reglas_por_defecto")]
+    pub validaciones: Vec<ReglaValidacion>,
+
+    /// Casos de prueba de fórmulas (primas/conceptos) verificados antes del ciclo.
+    #[serde(default)]
+    pub casos_prueba: Vec<CasoPrueba>,
+
+    /// Archivo JSON acompañante con más casos de prueba (arreglo de casos).
+    #[serde(default)]
+    pub archivo_casos_prueba: Option<String>,
+
+    /// Diferencia absoluta máxima aceptada en los casos de prueba.
+    #[serde(default = "default_tolerancia_casos")]
+    pub tolerancia_casos: f64,
+}
+
+fn default_tolerancia_casos() -> f64 {
+    0.01
 }
 
 fn default_autor() -> String {
@@ -103,6 +356,55 @@ impl Manifiesto {
         Ok(manifiesto)
     }
 
+    /// Casos de prueba del manifiesto más los del archivo acompañante, si existe.
+    pub fn cargar_casos_prueba(&self) -> Result<Vec<CasoPrueba>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut casos = self.casos_prueba.clone();
+        if let Some(ruta) = &self.archivo_casos_prueba {
+            let contenido = fs::read_to_string(ruta)
+                .map_err(|e| format!("No se pudo leer archivo de casos '{}': {}", ruta, e))?;
+            let extra: Vec<CasoPrueba> = serde_json::from_str(&contenido)
+                .map_err(|e| format!("Archivo de casos '{}' inválido: {}", ruta, e))?;
+            casos.extend(extra);
+        }
+        Ok(casos)
+    }
+
+    /// Fecha contra la que se evalúa la vigencia de los conceptos:
+    /// `parametros_globales.fecha_referencia` (YYYY-MM-DD), el primer día del
+    /// ciclo si este es `YYYY-MM`, o la fecha actual.
+    pub fn fecha_referencia(&self) -> chrono::NaiveDate {
+        self.parametros_globales
+            .get("fecha_referencia")
+            .and_then(|f| chrono::NaiveDate::parse_from_str(f.trim(), "%Y-%m-%d").ok())
+            .or_else(|| chrono::NaiveDate::parse_from_str(&format!("{}-01", self.ciclo.trim()), "%Y-%m-%d").ok())
+            .unwrap_or_else(|| chrono::Local::now().date_naive())
+    }
+
+    /// Restringe las cargas por cédula (Base, Beneficiarios y Movimientos) añadiendo
+    /// la condición al `sql_filter` existente. Solo acepta cédulas numéricas para no
+    /// inyectar texto arbitrario en el filtro.
+    pub fn restringir_a_cedula(&mut self, cedula: &str) -> Result<(), String> {
+        if cedula.is_empty() || !cedula.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("Cédula inválida: '{}' (solo dígitos)", cedula));
+        }
+
+        for (clave, columna) in [
+            ("IPSFA_CBase", "cedula"),
+            ("IPSFA_CMovimientos", "cedula"),
+            ("IPSFA_CBeneficiarios", "bnf.cedula"),
+        ] {
+            let condicion = format!("{} = '{}'", columna, cedula);
+            let cfg = self.cargas.entry(clave.to_string()).or_default();
+            cfg.sql_filter = Some(match cfg.sql_filter.as_deref().map(str::trim) {
+                Some(f) if !f.is_empty() && f != "\"%\"" && f != "%" => {
+                    format!("({}) and {}", f, condicion)
+                }
+                _ => condicion,
+            });
+        }
+        Ok(())
+    }
+
     pub fn default_mock() -> Self {
         let mut cargas: HashMap<String, CargaConfig> = HashMap::new();
 
@@ -175,6 +477,16 @@ impl Manifiesto {
             cargas,
             aportes: AporteConfig::default(),
             salida: SalidaConfig::default(),
+            limites: LimitesConfig::default(),
+            embargos: EmbargoConfig::default(),
+            creditos: CreditoConfig::default(),
+            proyeccion: ProyeccionConfig::default(),
+            diferencias: DiferenciasConfig::default(),
+            patria: PatriaConfig::default(),
+            validaciones: crate::nomina::validacion::reglas_por_defecto(),
+            casos_prueba: Vec::new(),
+            archivo_casos_prueba: None,
+            tolerancia_casos: default_tolerancia_casos(),
         }
     }
 }
@@ -1,4 +1,13 @@
-use crate::kernel::logica::memoria::{Base, ConceptoCalculado, ConceptoNomina, TipoConcepto};
+use crate::calc::cobertura::DefinicionFormula;
+use crate::calc::casos_prueba::{comparar, preparar_base, CasoPrueba, FalloCaso};
+use crate::calc::motor::SentinelEngine;
+use crate::calc::explicacion::{dinamico_a_f64, entradas_referenciadas, TrazaConcepto};
+use crate::kernel::logica::memoria::{
+    Base, Beneficiario, ConceptoCalculado, ConceptoNomina, Directiva, FaseConcepto,
+    TipoConcepto,
+};
+use crate::tipos::TipoNomina;
+use chrono::NaiveDate;
 use rayon::prelude::*;
 use rhai::{Engine, Scope, AST};
 use std::collections::HashMap;
@@ -16,21 +25,220 @@ pub struct ConceptoFnx {
     pub estructura: String,
     pub cuenta: String,
     pub partida: String,
+    pub fase: FaseConcepto,
+    pub clase: TipoConcepto,
+    pub reglas: ReglasAplicacion,
+    pub piso: Option<f64>,
+    pub tope: Option<f64>,
+    pub prioridad: Option<u32>,
+}
+
+impl ConceptoFnx {
+    /// Ajusta el resultado al tope y al piso declarados. Un 0.0 significa que el
+    /// concepto no corresponde y no se eleva al piso.
+    pub fn acotar(&self, valor: f64) -> f64 {
+        let mut v = valor;
+        if let Some(tope) = self.tope {
+            v = v.min(tope);
+        }
+        if let Some(piso) = self.piso {
+            if v != 0.0 {
+                v = v.max(piso);
+            }
+        }
+        v
+    }
+}
+
+/// Condiciones bajo las que un concepto aplica. Una lista vacía o un límite
+/// ausente no restringe.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReglasAplicacion {
+    pub tipos_nomina: Vec<TipoNomina>,
+    pub componentes: Vec<u32>,
+    pub status: Vec<u32>,
+    pub grado_min: Option<u32>,
+    pub grado_max: Option<u32>,
+    pub vigente_desde: Option<NaiveDate>,
+    pub vigente_hasta: Option<NaiveDate>,
+}
+
+/// Datos del registro evaluado contra las reglas por beneficiario. `status_id`
+/// es `None` en pre-fusión (la Base no lo trae); si luego el status del
+/// beneficiario excluye algún concepto, se reevalúa con `Sujeto::con_status`.
+#[derive(Debug, Clone, Copy)]
+struct Sujeto {
+    componente_id: u32,
+    grado_id: u32,
+    status_id: Option<u32>,
+}
+
+impl ReglasAplicacion {
+    pub fn desde(concepto: &ConceptoNomina) -> Result<Self, String> {
+        let tipos_nomina = lista(&concepto.tipos_nomina, "tipos_nomina", |t| {
+            TipoNomina::desde_codigo(t)
+        })?;
+        let componentes = lista(&concepto.componentes, "componentes", |t| t.parse().ok())?;
+        let status = lista(&concepto.status, "status", |t| t.parse().ok())?;
+        let grado_min = opcional(&concepto.grado_min, "grado_min", |t| t.parse().ok())?;
+        let grado_max = opcional(&concepto.grado_max, "grado_max", |t| t.parse().ok())?;
+        let fecha = |t: &str| NaiveDate::parse_from_str(t, "%Y-%m-%d").ok();
+        let vigente_desde = opcional(&concepto.vigente_desde, "vigente_desde", fecha)?;
+        let vigente_hasta = opcional(&concepto.vigente_hasta, "vigente_hasta", fecha)?;
+
+        if let (Some(min), Some(max)) = (grado_min, grado_max) {
+            if min > max {
+                return Err(format!("grado_min {} mayor que grado_max {}", min, max));
+            }
+        }
+        if let (Some(desde), Some(hasta)) = (vigente_desde, vigente_hasta) {
+            if desde > hasta {
+                return Err(format!("vigente_desde {} posterior a vigente_hasta {}", desde, hasta));
+            }
+        }
+
+        Ok(Self {
+            tipos_nomina,
+            componentes,
+            status,
+            grado_min,
+            grado_max,
+            vigente_desde,
+            vigente_hasta,
+        })
+    }
+
+    /// Condiciones que dependen solo del ciclo: tipo de nómina y vigencia.
+    pub fn aplica_ciclo(&self, tipo_nomina: TipoNomina, fecha_referencia: NaiveDate) -> bool {
+        (self.tipos_nomina.is_empty() || self.tipos_nomina.contains(&tipo_nomina))
+            && self.vigente_desde.is_none_or(|d| fecha_referencia >= d)
+            && self.vigente_hasta.is_none_or(|h| fecha_referencia <= h)
+    }
+
+    fn aplica_sujeto(&self, sujeto: Sujeto) -> bool {
+        (self.componentes.is_empty() || self.componentes.contains(&sujeto.componente_id))
+            && self.grado_min.is_none_or(|g| sujeto.grado_id >= g)
+            && self.grado_max.is_none_or(|g| sujeto.grado_id <= g)
+            && sujeto
+                .status_id
+                .is_none_or(|s| self.status.is_empty() || self.status.contains(&s))
+    }
+}
+
+fn lista<T>(texto: &str, campo: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, String> {
+    texto
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| parse(t).ok_or_else(|| format!("valor '{}' inválido en {}", t, campo)))
+        .collect()
+}
+
+fn opcional<T>(texto: &str, campo: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Option<T>, String> {
+    let t = texto.trim();
+    if t.is_empty() {
+        return Ok(None);
+    }
+    parse(t)
+        .map(Some)
+        .ok_or_else(|| format!("valor '{}' inválido en {}", t, campo))
+}
+
+/// Piso, tope y prioridad de cobro declarados en un concepto.
+type LimitesMonto = (Option<f64>, Option<f64>, Option<u32>);
+
+fn limites_monto(concepto: &ConceptoNomina) -> Result<LimitesMonto, String> {
+    let piso = opcional(&concepto.monto_minimo, "monto_minimo", |t| t.parse().ok())?;
+    let tope = opcional(&concepto.monto_maximo, "monto_maximo", |t| t.parse().ok())?;
+    let prioridad = opcional(&concepto.prioridad, "prioridad", |t| t.parse().ok())?;
+    if let (Some(p), Some(t)) = (piso, tope) {
+        if p > t {
+            return Err(format!("monto_minimo {} mayor que monto_maximo {}", p, t));
+        }
+    }
+    Ok((piso, tope, prioridad))
+}
+
+/// Clase del concepto: la declarada en `clase` o, en su defecto, la de `tipo` (1-2
+/// asignación, 3-5 deducción). Cualquier otro caso es un error de validación.
+fn resolver_clase(concepto: &ConceptoNomina) -> Result<TipoConcepto, String> {
+    if !concepto.clase.trim().is_empty() {
+        return TipoConcepto::desde_clase(&concepto.clase).ok_or_else(|| {
+            format!("clase '{}' desconocida (asignacion | deduccion)", concepto.clase)
+        });
+    }
+    match concepto.tipo {
+        1 | 2 => Ok(TipoConcepto::Asignacion),
+        3..=5 => Ok(TipoConcepto::Deduccion),
+        t => Err(format!("tipo {} sin clase explícita (asignacion | deduccion)", t)),
+    }
+}
+
+impl Sujeto {
+    fn de_base(base: &Base) -> Self {
+        Self {
+            componente_id: base.componente_id,
+            grado_id: base.grado_id,
+            status_id: None,
+        }
+    }
+
+    fn con_status(base: &Base, status_id: u32) -> Self {
+        Self {
+            status_id: Some(status_id),
+            ..Self::de_base(base)
+        }
+    }
+
+    fn de_beneficiario(ben: &Beneficiario) -> Self {
+        Self {
+            componente_id: ben.base.componente_id,
+            grado_id: ben.base.grado_id,
+            status_id: Some(ben.status_id),
+        }
+    }
+}
+
+/// Datos de un beneficiario visibles para los conceptos post-fusión y post-neto.
+pub struct EntradaFase<'a> {
+    pub beneficiario: &'a Beneficiario,
+    /// Porcentaje de pensión original (antes de que el cálculo del neto lo normalice).
+    pub porcentaje: f64,
+    /// Conceptos calculados en las fases anteriores, en orden de evaluación.
+    pub previos: &'a [ConceptoCalculado],
 }
 
 pub struct EjecutorConceptos {
     engine: Engine,
     conceptos: Arc<Vec<ConceptoFnx>>,
+    /// Códigos descartados por `para_ciclo`; valen 0.0 para los que los referencian.
+    excluidos: Arc<Vec<String>>,
 }
 
 impl EjecutorConceptos {
-    pub fn new(conceptos_nomina: Vec<ConceptoNomina>) -> Self {
-        let engine = Engine::new();
+    /// Compila los conceptos. Clase, fase o reglas de aplicabilidad inválidas
+    /// impiden continuar: se devuelven todos los errores juntos.
+    pub fn new(conceptos_nomina: Vec<ConceptoNomina>) -> Result<Self, String> {
+        let engine = crate::util::script::motor();
         let mut conceptos = Vec::new();
+        let mut errores = Vec::new();
 
         for concepto in conceptos_nomina {
             let raw = concepto.codigo_rhai.trim().trim_end_matches(';').to_string();
 
+            let fase = FaseConcepto::desde_texto(&concepto.fase)
+                .ok_or_else(|| format!("fase '{}' desconocida", concepto.fase));
+            let validado = resolver_clase(&concepto).and_then(|clase| {
+                Ok((clase, fase?, ReglasAplicacion::desde(&concepto)?, limites_monto(&concepto)?))
+            });
+            let (clase, fase, reglas, (piso, tope, prioridad)) = match validado {
+                Ok(v) => v,
+                Err(e) => {
+                    errores.push(format!("[{}] {}", concepto.codigo, e));
+                    continue;
+                }
+            };
+
             match engine.compile(&raw) {
                 Ok(ast) => {
                     conceptos.push(ConceptoFnx {
@@ -43,6 +251,12 @@ impl EjecutorConceptos {
                         estructura: concepto.estructura.clone(),
                         cuenta: concepto.cuenta.clone(),
                         partida: concepto.partida.clone(),
+                        fase,
+                        clase,
+                        reglas,
+                        piso,
+                        tope,
+                        prioridad,
                     });
                 }
                 Err(e) => {
@@ -54,78 +268,217 @@ impl EjecutorConceptos {
             }
         }
 
-        Self {
+        if !errores.is_empty() {
+            return Err(format!(
+                "{} concepto(s) con definición inválida:\n  - {}",
+                errores.len(),
+                errores.join("\n  - ")
+            ));
+        }
+
+        Ok(Self {
             engine,
             conceptos: Arc::new(conceptos),
+            excluidos: Arc::new(Vec::new()),
+        })
+    }
+
+    /// Descarta los conceptos que no aplican al tipo de nómina o cuya vigencia no
+    /// cubre la fecha de referencia del ciclo. Sus códigos siguen en el scope con
+    /// 0.0, de modo que los conceptos que los referencian se calculan igual.
+    pub fn para_ciclo(self, tipo_nomina: TipoNomina, fecha_referencia: NaiveDate) -> Self {
+        let (conceptos, descartados): (Vec<ConceptoFnx>, Vec<ConceptoFnx>) = self
+            .conceptos
+            .iter()
+            .cloned()
+            .partition(|c| c.reglas.aplica_ciclo(tipo_nomina, fecha_referencia));
+        let mut excluidos = (*self.excluidos).clone();
+        excluidos.extend(descartados.into_iter().map(|c| c.codigo));
+        Self {
+            engine: self.engine,
+            conceptos: Arc::new(conceptos),
+            excluidos: Arc::new(excluidos),
         }
     }
 
+    /// Indica si algún concepto pre-fusión tiene reglas de status que no admiten
+    /// `status_id`. Los conceptos pre-fusión se evalúan sin conocer el status; en
+    /// ese caso hay que reevaluarlos con `ejecutar_con_status`.
+    pub fn excluye_status(&self, status_id: u32) -> bool {
+        self.conceptos.iter().any(|c| {
+            c.fase == FaseConcepto::PreFusion
+                && !c.reglas.status.is_empty()
+                && !c.reglas.status.contains(&status_id)
+        })
+    }
+
+    /// Evalúa los conceptos pre-fusión de una Base con el status del beneficiario:
+    /// los que no lo admiten valen 0.0 para los que los referencian.
+    pub fn ejecutar_con_status(&self, base: &Base, status_id: u32) -> Vec<ConceptoCalculado> {
+        let mut scope = self.crear_scope(base);
+        self.evaluar(&mut scope, FaseConcepto::PreFusion, Sujeto::con_status(base, status_id))
+    }
+
+    /// Evalúa los conceptos de fase pre-fusión sobre cada Base (con primas).
     pub fn ejecutar(&self, bases: &[Base]) -> HashMap<String, Vec<ConceptoCalculado>> {
         bases
             .par_iter()
             .map(|base| {
                 let mut scope = self.crear_scope(base);
-                let mut calculados = Vec::new();
+                let calculados =
+                    self.evaluar(&mut scope, FaseConcepto::PreFusion, Sujeto::de_base(base));
+                (base.patterns.clone(), calculados)
+            })
+            .collect()
+    }
 
-                for concepto in self.conceptos.iter() {
-                    if !concepto.activo.load(Ordering::Relaxed) {
-                        continue;
-                    }
+    /// Evalúa los conceptos de una fase posterior a la fusión. El resultado queda
+    /// alineado por índice con `entradas`.
+    pub fn ejecutar_fase(
+        &self,
+        fase: FaseConcepto,
+        entradas: &[EntradaFase],
+    ) -> Vec<Vec<ConceptoCalculado>> {
+        entradas
+            .par_iter()
+            .map(|entrada| {
+                let mut scope = self.crear_scope_fase(entrada);
+                self.evaluar(&mut scope, fase, Sujeto::de_beneficiario(entrada.beneficiario))
+            })
+            .collect()
+    }
 
-                    let resultado: f64 = match self
-                        .engine
-                        .eval_ast_with_scope::<rhai::Dynamic>(&mut scope, &concepto.ast)
-                    {
-                        Ok(val) => {
-                            if let Ok(f) = val.as_float() {
-                                f
-                            } else if let Ok(i) = val.as_int() {
-                                i as f64
-                            } else {
-                                0.0
-                            }
-                        }
-                        Err(e) => {
-                            if concepto.activo.swap(false, Ordering::Relaxed) {
-                                let msg = format!(
-                                    "Concepto '{}' DESACTIVADO por error: {}",
-                                    concepto.codigo, e
-                                );
-                                eprintln!("[ERROR] [EjecutorConceptos] {}", msg);
-                            }
-                            0.0
-                        }
-                    };
-
-                    scope.push(concepto.codigo.clone(), resultado);
-
-                    calculados.push(ConceptoCalculado {
-                        codigo: concepto.codigo.clone(),
-                        descripcion: concepto.descripcion.clone(),
-                        tipo: match concepto.tipo {
-                            1 | 2 => TipoConcepto::Asignacion,
-                            3 | 4 | 5 => TipoConcepto::Deduccion,
-                            _ => {
-                                if concepto.codigo.to_lowercase().contains("ded")
-                                    || concepto.codigo.to_lowercase().starts_with("desc")
-                                    || concepto.codigo.to_lowercase().starts_with("ret")
-                                {
-                                    TipoConcepto::Deduccion
-                                } else {
-                                    TipoConcepto::Asignacion
-                                }
-                            }
-                        },
-                        valor: resultado,
-                        estructura: concepto.estructura.clone(),
-                        cuenta: concepto.cuenta.clone(),
-                        partida: concepto.partida.clone(),
-                    });
+    /// Indica si hay conceptos declarados para la fase.
+    pub fn tiene_fase(&self, fase: FaseConcepto) -> bool {
+        self.conceptos.iter().any(|c| c.fase == fase)
+    }
+
+    fn evaluar(&self, scope: &mut Scope, fase: FaseConcepto, sujeto: Sujeto) -> Vec<ConceptoCalculado> {
+        let mut calculados = Vec::new();
+
+        for concepto in self.conceptos.iter().filter(|c| c.fase == fase) {
+            if !concepto.activo.load(Ordering::Relaxed) {
+                continue;
+            }
+            // Un concepto que no aplica vale 0.0 para los que lo referencian
+            if !concepto.reglas.aplica_sujeto(sujeto) {
+                scope.push(concepto.codigo.clone(), 0.0_f64);
+                continue;
+            }
+
+            let resultado: f64 = match self
+                .engine
+                .eval_ast_with_scope::<rhai::Dynamic>(scope, &concepto.ast)
+            {
+                Ok(val) => concepto.acotar(dinamico_a_f64(&val)),
+                Err(e) => {
+                    if concepto.activo.swap(false, Ordering::Relaxed) {
+                        let msg = format!(
+                            "Concepto '{}' DESACTIVADO por error: {}",
+                            concepto.codigo, e
+                        );
+                        eprintln!("[ERROR] [EjecutorConceptos] {}", msg);
+                    }
+                    0.0
                 }
+            };
 
-                (base.patterns.clone(), calculados)
-            })
-            .collect()
+            scope.push(concepto.codigo.clone(), resultado);
+
+            calculados.push(ConceptoCalculado {
+                codigo: concepto.codigo.clone(),
+                descripcion: concepto.descripcion.clone(),
+                tipo: concepto.clase.clone(),
+                valor: resultado,
+                estructura: concepto.estructura.clone(),
+                cuenta: concepto.cuenta.clone(),
+                partida: concepto.partida.clone(),
+            });
+        }
+
+        calculados
+    }
+
+    /// Evalúa los conceptos pre-fusión de una sola Base registrando entradas y
+    /// resultado de cada uno (modo `explain`). No activa el circuit breaker.
+    pub fn trazar(&self, base: &Base) -> Vec<TrazaConcepto> {
+        let mut scope = self.crear_scope(base);
+        self.trazar_scope(&mut scope, FaseConcepto::PreFusion, Sujeto::de_base(base))
+    }
+
+    /// Igual que `trazar` con el status del beneficiario (ver `excluye_status`).
+    pub fn trazar_con_status(&self, base: &Base, status_id: u32) -> Vec<TrazaConcepto> {
+        let mut scope = self.crear_scope(base);
+        self.trazar_scope(&mut scope, FaseConcepto::PreFusion, Sujeto::con_status(base, status_id))
+    }
+
+    /// Igual que `trazar` para una fase posterior a la fusión.
+    pub fn trazar_fase(&self, fase: FaseConcepto, entrada: &EntradaFase) -> Vec<TrazaConcepto> {
+        let mut scope = self.crear_scope_fase(entrada);
+        self.trazar_scope(&mut scope, fase, Sujeto::de_beneficiario(entrada.beneficiario))
+    }
+
+    fn trazar_scope(&self, scope: &mut Scope, fase: FaseConcepto, sujeto: Sujeto) -> Vec<TrazaConcepto> {
+        let mut trazas = Vec::new();
+
+        for concepto in self.conceptos.iter().filter(|c| c.fase == fase) {
+            if !concepto.reglas.aplica_sujeto(sujeto) {
+                scope.push(concepto.codigo.clone(), 0.0_f64);
+                continue;
+            }
+            let entradas = entradas_referenciadas(scope, &concepto.codigo_rhai);
+
+            let (resultado, error) = match self
+                .engine
+                .eval_ast_with_scope::<rhai::Dynamic>(scope, &concepto.ast)
+            {
+                Ok(val) => (concepto.acotar(dinamico_a_f64(&val)), None),
+                Err(e) => (0.0, Some(e.to_string())),
+            };
+
+            scope.push(concepto.codigo.clone(), resultado);
+
+            trazas.push(TrazaConcepto {
+                codigo: concepto.codigo.clone(),
+                descripcion: concepto.descripcion.clone(),
+                tipo: concepto.clase.clone(),
+                fase,
+                codigo_rhai: concepto.codigo_rhai.clone(),
+                entradas,
+                resultado,
+                error,
+            });
+        }
+
+        trazas
+    }
+
+    /// Verifica los valores esperados de conceptos de cada caso de prueba. La Base
+    /// del caso pasa antes por la directiva, las primas y `generar_calculos`,
+    /// igual que en el ciclo.
+    /// Solo cubre conceptos pre-fusión (las fases posteriores requieren el beneficiario).
+    pub fn verificar_casos(
+        &self,
+        casos: &[CasoPrueba],
+        directivas: &Vec<Directiva>,
+        motor: &SentinelEngine,
+        tolerancia: f64,
+    ) -> Vec<FalloCaso> {
+        let mut fallos = Vec::new();
+        for caso in casos.iter().filter(|c| !c.conceptos.is_empty()) {
+            let (base, _) = preparar_base(caso, directivas, motor);
+
+            let mut obtenidos = HashMap::new();
+            let mut errores = HashMap::new();
+            for t in self.trazar(&base) {
+                if let Some(e) = t.error {
+                    errores.insert(t.codigo.clone(), e);
+                }
+                obtenidos.insert(t.codigo, t.resultado);
+            }
+            comparar(caso, &caso.conceptos, &obtenidos, &errores, tolerancia, &mut fallos);
+        }
+        fallos
     }
 
     fn crear_scope(&self, base: &Base) -> Scope<'_> {
@@ -152,6 +505,11 @@ impl EjecutorConceptos {
         scope.push("st_profesion", base.st_profesion as i64);
         scope.push("st_profesionalizacion", base.st_profesion as i64);
 
+        // Conceptos fuera del ciclo: 0.0, igual que los que no aplican al sujeto
+        for codigo in self.excluidos.iter() {
+            scope.push(codigo.clone(), 0.0_f64);
+        }
+
         if let Some(calculos) = &base.calculos {
             for (key, value) in calculos {
                 scope.push(key.clone(), *value);
@@ -161,6 +519,38 @@ impl EjecutorConceptos {
         scope
     }
 
+    /// Scope de fases post-fusión: Base fusionada + primas (y los conceptos fuera
+    /// del ciclo en 0.0), datos del beneficiario (porcentaje, totales y neto
+    /// vigentes) y resultados de las fases anteriores.
+    fn crear_scope_fase(&self, entrada: &EntradaFase) -> Scope<'_> {
+        let ben = entrada.beneficiario;
+        let mut scope = self.crear_scope(&ben.base);
+
+        scope.push("porcentaje", entrada.porcentaje);
+        scope.push("total_asignaciones", ben.total_asignaciones);
+        scope.push("total_deducciones", ben.total_deducciones);
+        scope.push("neto", ben.neto);
+
+        for c in entrada.previos {
+            scope.push(c.codigo.clone(), c.valor);
+        }
+
+        scope
+    }
+
+    /// Prioridad de cobro declarada para el concepto, si la tiene.
+    pub fn prioridad(&self, codigo: &str) -> Option<u32> {
+        self.conceptos.iter().find(|c| c.codigo == codigo).and_then(|c| c.prioridad)
+    }
+
+    /// Código, descripción y estado del circuit breaker de cada concepto (reporte de cobertura).
+    pub fn definiciones(&self) -> Vec<DefinicionFormula> {
+        self.conceptos
+            .iter()
+            .map(|c| (c.codigo.clone(), c.descripcion.clone(), !c.activo.load(Ordering::Relaxed)))
+            .collect()
+    }
+
     pub fn get_codigos(&self) -> Vec<String> {
         self.conceptos.iter().map(|c| c.codigo.clone()).collect()
     }
@@ -181,3 +571,187 @@ pub fn calcular_totales_conceptos(conceptos: &[ConceptoCalculado]) -> (f64, f64)
 
     (asignaciones, deducciones)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::{aplicar_conceptos_por_fases, conceptos_pre_fusion};
+
+    fn concepto(codigo: &str, formula: &str, tipo: u32, fase: &str) -> ConceptoNomina {
+        ConceptoNomina {
+            codigo: codigo.to_string(),
+            descripcion: codigo.to_uppercase(),
+            codigo_rhai: formula.to_string(),
+            estructura: String::new(),
+            cuenta: String::new(),
+            partida: String::new(),
+            tipo,
+            fase: fase.to_string(),
+            clase: String::new(),
+            tipos_nomina: String::new(),
+            componentes: String::new(),
+            status: String::new(),
+            grado_min: String::new(),
+            grado_max: String::new(),
+            vigente_desde: String::new(),
+            vigente_hasta: String::new(),
+            monto_minimo: String::new(),
+            monto_maximo: String::new(),
+            prioridad: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_fase_post_neto_ve_neto_y_conceptos_previos() {
+        let ejecutor = EjecutorConceptos::new(vec![
+            concepto("bono", "100.0", 1, ""),
+            concepto("ded_neto", "neto * 0.1", 3, "post_neto"),
+            concepto("ajuste", "if bono > 0.0 { 5.0 } else { 0.0 }", 1, "post_fusion"),
+        ])
+        .unwrap();
+
+        let mut ben: Beneficiario = serde_json::from_str(r#"{"cedula":"1"}"#).unwrap();
+        ben.base.sueldo_integral = 1000.0;
+
+        let pre = ejecutor.ejecutar(std::slice::from_ref(&ben.base));
+        let pre = vec![pre.get(&ben.base.patterns).cloned()];
+        let mut bens = vec![ben];
+        aplicar_conceptos_por_fases(&mut bens, pre, &ejecutor, TipoNomina::Nact, 0.0);
+
+        // pre: 1000 + 100 = 1100; post_fusion: +5 = 1105; post_neto: -110.5
+        let b = &bens[0];
+        assert_eq!(b.total_asignaciones, 105.0);
+        assert!((b.total_deducciones - 110.5).abs() < 1e-9);
+        assert!((b.neto - 994.5).abs() < 1e-9);
+        assert_eq!(b.conceptos_calculados.as_ref().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_post_neto_ve_el_neto_con_deducciones_limitadas() {
+        let ejecutor = EjecutorConceptos::new(vec![
+            concepto("prestamo", "800.0", 3, ""),
+            concepto("bono_neto", "neto * 0.1", 1, "post_neto"),
+        ])
+        .unwrap();
+
+        let mut ben: Beneficiario = serde_json::from_str(r#"{"cedula":"1"}"#).unwrap();
+        ben.base.sueldo_integral = 1000.0;
+
+        let pre = ejecutor.ejecutar(std::slice::from_ref(&ben.base));
+        let pre = vec![pre.get(&ben.base.patterns).cloned()];
+        let mut bens = vec![ben];
+        let recortes = aplicar_conceptos_por_fases(&mut bens, pre, &ejecutor, TipoNomina::Nact, 50.0);
+
+        // bruto 1000, límite 500: el préstamo baja a 500 y post-neto lee neto = 500
+        assert_eq!(recortes.len(), 1);
+        assert_eq!(recortes[0].recortado, 300.0);
+        let b = &bens[0];
+        assert_eq!(b.conceptos_calculados.as_ref().unwrap()["bono_neto"].valor, 50.0);
+        assert_eq!(b.total_deducciones, 500.0);
+        assert_eq!(b.neto, 550.0);
+    }
+
+    #[test]
+    fn test_status_excluido_vale_cero_para_dependientes() {
+        let mut solo_activos = concepto("bono_activo", "100.0", 1, "");
+        solo_activos.status = "201".to_string();
+        let ejecutor = EjecutorConceptos::new(vec![
+            solo_activos,
+            concepto("derivado", "bono_activo * 0.5", 1, ""),
+        ])
+        .unwrap();
+
+        let mut activo: Beneficiario = serde_json::from_str(r#"{"cedula":"1"}"#).unwrap();
+        activo.status_id = 201;
+        let mut retirado = activo.clone();
+        retirado.status_id = 202;
+        assert!(!ejecutor.excluye_status(201));
+        assert!(ejecutor.excluye_status(202));
+
+        let calculados = ejecutor.ejecutar(std::slice::from_ref(&activo.base));
+        let bases = HashMap::from([(activo.base.patterns.as_str(), &activo.base)]);
+        let bens = vec![activo.clone(), retirado];
+        let pre = conceptos_pre_fusion(&bens, &bases, &calculados, &ejecutor);
+
+        let valores = |pre: &Option<Vec<ConceptoCalculado>>| -> Vec<(String, f64)> {
+            pre.iter().flatten().map(|c| (c.codigo.clone(), c.valor)).collect()
+        };
+        assert_eq!(
+            valores(&pre[0]),
+            vec![("bono_activo".to_string(), 100.0), ("derivado".to_string(), 50.0)]
+        );
+        // El excluido no aparece y su dependiente lo ve en 0.0
+        assert_eq!(valores(&pre[1]), vec![("derivado".to_string(), 0.0)]);
+    }
+
+    #[test]
+    fn test_clase_explicita_obligatoria_y_reglas_de_aplicabilidad() {
+        // tipo desconocido sin clase: ya no se adivina por el prefijo del código
+        let err = EjecutorConceptos::new(vec![concepto("ded_x", "1.0", 9, "")]).err().unwrap();
+        assert!(err.contains("[ded_x]") && err.contains("sin clase"));
+
+        let mut ded = concepto("ded_x", "10.0", 9, "");
+        ded.clase = "deduccion".to_string();
+        let mut solo_nrcp = concepto("bono_nrcp", "50.0", 1, "");
+        solo_nrcp.tipos_nomina = "nrcp".to_string();
+        let mut oficiales = concepto("bono_oficial", "20.0", 1, "");
+        oficiales.grado_min = "10".to_string();
+        oficiales.componentes = "1,2".to_string();
+        let mut vencido = concepto("bono_2025", "5.0", 1, "");
+        vencido.vigente_hasta = "2025-12-31".to_string();
+        let usa_oficial = concepto("derivado", "bono_oficial * 2.0", 1, "");
+
+        let fecha = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let ejecutor = EjecutorConceptos::new(vec![ded, solo_nrcp, oficiales, vencido, usa_oficial])
+            .unwrap()
+            .para_ciclo(TipoNomina::Nact, fecha);
+        assert_eq!(ejecutor.get_codigos(), vec!["ded_x", "bono_oficial", "derivado"]);
+
+        let mut base: Base = serde_json::from_str(r#"{"cedula":"1"}"#).unwrap();
+        base.grado_id = 5;
+        base.componente_id = 1;
+        let calculados = &ejecutor.ejecutar(std::slice::from_ref(&base))[&base.patterns];
+        let valores: Vec<(&str, f64)> = calculados.iter().map(|c| (c.codigo.as_str(), c.valor)).collect();
+        assert_eq!(valores, vec![("ded_x", 10.0), ("derivado", 0.0)]);
+        assert!(matches!(calculados[0].tipo, TipoConcepto::Deduccion));
+
+        let mut invalido = concepto("malo", "1.0", 1, "");
+        invalido.tipos_nomina = "nact,xyz".to_string();
+        let err = EjecutorConceptos::new(vec![invalido]).err().unwrap();
+        assert!(err.contains("'xyz' inválido en tipos_nomina"));
+    }
+
+    #[test]
+    fn test_concepto_fuera_del_ciclo_vale_cero_para_dependientes() {
+        let mut solo_nrcp = concepto("bono_nrcp", "50.0", 1, "");
+        solo_nrcp.tipos_nomina = "nrcp".to_string();
+        let fecha = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let ejecutor = EjecutorConceptos::new(vec![
+            solo_nrcp,
+            concepto("derivado", "bono_nrcp + 7.0", 1, ""),
+            concepto("derivado_neto", "bono_nrcp + neto * 0.0 + 1.0", 1, "post_neto"),
+        ])
+        .unwrap()
+        .para_ciclo(TipoNomina::Nact, fecha);
+
+        let mut ben: Beneficiario = serde_json::from_str(r#"{"cedula":"1"}"#).unwrap();
+        ben.base.sueldo_integral = 100.0;
+        let pre = ejecutor.ejecutar(std::slice::from_ref(&ben.base));
+        let valores: Vec<(&str, f64)> = pre[&ben.base.patterns]
+            .iter()
+            .map(|c| (c.codigo.as_str(), c.valor))
+            .collect();
+        assert_eq!(valores, vec![("derivado", 7.0)]);
+
+        let post = ejecutor.ejecutar_fase(
+            FaseConcepto::PostNeto,
+            &[EntradaFase {
+                beneficiario: &ben,
+                porcentaje: 100.0,
+                previos: &pre[&ben.base.patterns],
+            }],
+        );
+        assert_eq!(post[0][0].valor, 1.0);
+        assert!(ejecutor.definiciones().iter().all(|(_, _, desactivado)| !desactivado));
+    }
+}
@@ -0,0 +1,385 @@
+//! Créditos descontados por cuotas fijas hasta cancelar el saldo.
+//!
+//! Cada crédito activo genera en el ciclo una deducción pre-fusión
+//! `credito_<numero>` por el menor entre la cuota y el saldo, de modo que pasa por
+//! las fases y el límite de deducciones como cualquier otro concepto. Al final
+//! del ciclo el saldo se reduce por lo efectivamente descontado y se escribe
+//! `saldos_creditos.json`, que sirve de entrada al ciclo siguiente.
+//!
+//! Solo las nóminas que descuentan deducciones del neto (NACT y NRCP) reciben
+//! cuotas; en las demás el saldo no cambia y el crédito queda `no_descontado`.
+
+use crate::calc::calculos::redondear_dos;
+use crate::kernel::logica::exportador::{self, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::kernel::logica::memoria::{Beneficiario, ConceptoCalculado, CreditoCuota, TipoConcepto};
+use crate::tipos::TipoNomina;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+pub const PREFIJO_CONCEPTO_CREDITO: &str = "credito_";
+pub const NOMBRE_ARCHIVO_SALDOS: &str = "saldos_creditos.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstadoCredito {
+    /// Se descontó la cuota completa y queda saldo.
+    Activo,
+    /// El saldo quedó en cero en este ciclo.
+    Cancelado,
+    /// Se descontó menos de la cuota (límite de deducciones o sin conceptos).
+    Parcial,
+    /// Aún no llega su ciclo de inicio.
+    Pendiente,
+    /// Sin saldo desde antes del ciclo.
+    Pagado,
+    /// La cédula no está en la nómina del ciclo.
+    SinBeneficiario,
+    /// El tipo de nómina no descuenta deducciones del neto.
+    NoDescontado,
+}
+
+impl std::fmt::Display for EstadoCredito {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            EstadoCredito::Activo => "activo",
+            EstadoCredito::Cancelado => "cancelado",
+            EstadoCredito::Parcial => "parcial",
+            EstadoCredito::Pendiente => "pendiente",
+            EstadoCredito::Pagado => "pagado",
+            EstadoCredito::SinBeneficiario => "sin_beneficiario",
+            EstadoCredito::NoDescontado => "no_descontado",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MovimientoCredito {
+    pub numero: String,
+    pub cedula: String,
+    pub saldo_anterior: f64,
+    pub cuota: f64,
+    pub descontado: f64,
+    pub saldo_nuevo: f64,
+    pub estado: EstadoCredito,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReporteCreditos {
+    pub ciclo: String,
+    pub movimientos: Vec<MovimientoCredito>,
+    /// Créditos con el saldo actualizado (entrada del ciclo siguiente).
+    pub saldos: Vec<CreditoCuota>,
+}
+
+pub fn codigo_concepto(credito: &CreditoCuota) -> String {
+    format!("{}{}", PREFIJO_CONCEPTO_CREDITO, credito.numero.trim())
+}
+
+fn en_curso(credito: &CreditoCuota, ciclo: &str) -> bool {
+    let inicio = credito.ciclo_inicio.trim();
+    inicio.is_empty() || inicio <= ciclo.trim()
+}
+
+/// Cuota a descontar en el ciclo: la cuota pactada sin exceder el saldo.
+fn cuota_del_ciclo(credito: &CreditoCuota, ciclo: &str) -> f64 {
+    if credito.saldo <= 0.0 || !en_curso(credito, ciclo) {
+        return 0.0;
+    }
+    redondear_dos(credito.cuota.min(credito.saldo).max(0.0))
+}
+
+/// Deducciones del ciclo por cédula, a agregar a los conceptos pre-fusión.
+/// Vacío si el tipo de nómina no descuenta deducciones.
+pub fn cuotas_del_ciclo(
+    creditos: &[CreditoCuota],
+    ciclo: &str,
+    tipo_nomina: TipoNomina,
+) -> HashMap<String, Vec<ConceptoCalculado>> {
+    let mut cuotas: HashMap<String, Vec<ConceptoCalculado>> = HashMap::new();
+    if !tipo_nomina.descuenta_deducciones() {
+        return cuotas;
+    }
+    for credito in creditos {
+        let valor = cuota_del_ciclo(credito, ciclo);
+        if valor <= 0.0 {
+            continue;
+        }
+        let descripcion = if credito.descripcion.trim().is_empty() {
+            format!("CUOTA CREDITO {}", credito.numero.trim())
+        } else {
+            credito.descripcion.clone()
+        };
+        cuotas
+            .entry(credito.cedula.trim().to_string())
+            .or_default()
+            .push(ConceptoCalculado {
+                codigo: codigo_concepto(credito),
+                descripcion,
+                tipo: TipoConcepto::Deduccion,
+                valor,
+                estructura: String::new(),
+                cuenta: String::new(),
+                partida: String::new(),
+            });
+    }
+    cuotas
+}
+
+/// Reduce los saldos por lo efectivamente descontado a cada beneficiario (tras
+/// fases y límite de deducciones). Si el tipo de nómina no descuenta
+/// deducciones los saldos no cambian.
+pub fn actualizar_saldos(
+    creditos: &[CreditoCuota],
+    beneficiarios: &[Beneficiario],
+    ciclo: &str,
+    tipo_nomina: TipoNomina,
+) -> ReporteCreditos {
+    let descuenta = tipo_nomina.descuenta_deducciones();
+    let por_cedula: HashMap<&str, &Beneficiario> = beneficiarios
+        .iter()
+        .map(|b| (b.cedula.as_str(), b))
+        .collect();
+
+    let mut reporte = ReporteCreditos {
+        ciclo: ciclo.to_string(),
+        ..Default::default()
+    };
+
+    for credito in creditos {
+        let cuota = cuota_del_ciclo(credito, ciclo);
+        let ben = por_cedula.get(credito.cedula.trim());
+        let descontado = ben
+            .filter(|_| descuenta)
+            .and_then(|b| b.conceptos_calculados.as_ref())
+            .and_then(|c| c.get(&codigo_concepto(credito)))
+            .map(|c| redondear_dos(c.valor))
+            .unwrap_or(0.0);
+        let saldo_nuevo = redondear_dos((credito.saldo - descontado).max(0.0));
+
+        let estado = if credito.saldo <= 0.0 {
+            EstadoCredito::Pagado
+        } else if !en_curso(credito, ciclo) {
+            EstadoCredito::Pendiente
+        } else if !descuenta {
+            EstadoCredito::NoDescontado
+        } else if ben.is_none() {
+            EstadoCredito::SinBeneficiario
+        } else if saldo_nuevo <= 0.0 {
+            EstadoCredito::Cancelado
+        } else if descontado < cuota {
+            EstadoCredito::Parcial
+        } else {
+            EstadoCredito::Activo
+        };
+
+        reporte.movimientos.push(MovimientoCredito {
+            numero: credito.numero.clone(),
+            cedula: credito.cedula.trim().to_string(),
+            saldo_anterior: credito.saldo,
+            cuota,
+            descontado,
+            saldo_nuevo,
+            estado,
+        });
+        reporte.saldos.push(CreditoCuota {
+            saldo: saldo_nuevo,
+            ..credito.clone()
+        });
+    }
+
+    reporte
+}
+
+/// Lee un archivo de saldos (arreglo JSON de créditos) de un ciclo anterior.
+pub fn cargar_saldos(
+    ruta: &str,
+) -> Result<Vec<CreditoCuota>, Box<dyn std::error::Error + Send + Sync>> {
+    let contenido = std::fs::read_to_string(ruta)
+        .map_err(|e| format!("No se pudo leer el archivo de saldos '{}': {}", ruta, e))?;
+    let creditos: Vec<CreditoCuota> = serde_json::from_str(&contenido)
+        .map_err(|e| format!("Archivo de saldos '{}' inválido: {}", ruta, e))?;
+    Ok(creditos)
+}
+
+impl ReporteCreditos {
+    pub fn total_descontado(&self) -> f64 {
+        redondear_dos(self.movimientos.iter().map(|m| m.descontado).sum())
+    }
+
+    /// Escribe `saldos_creditos.json` (entrada del próximo ciclo) y
+    /// `creditos_ejecucion.csv` (movimiento del ciclo por crédito).
+    pub fn exportar(
+        &self,
+        destino: &str,
+    ) -> Result<Vec<ResultadoExport>, Box<dyn std::error::Error>> {
+        let ruta_base = |nombre: &str| {
+            if destino == "." || destino.is_empty() {
+                PathBuf::from(nombre)
+            } else {
+                PathBuf::from(destino).join(nombre)
+            }
+        };
+
+        let saldos_bytes = serde_json::to_vec_pretty(&self.saldos)?;
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_writer(Vec::new());
+        wtr.write_record([
+            "numero",
+            "cedula",
+            "saldo_anterior",
+            "cuota",
+            "descontado",
+            "saldo_nuevo",
+            "estado",
+        ])?;
+        for m in &self.movimientos {
+            wtr.write_record([
+                m.numero.clone(),
+                m.cedula.clone(),
+                format!("{:.2}", m.saldo_anterior),
+                format!("{:.2}", m.cuota),
+                format!("{:.2}", m.descontado),
+                format!("{:.2}", m.saldo_nuevo),
+                m.estado.to_string(),
+            ])?;
+        }
+        let ejecucion_bytes = wtr.into_inner()?;
+
+        let mut resultados = Vec::new();
+        for (nombre, tipo, datos) in [
+            (NOMBRE_ARCHIVO_SALDOS, "saldos_creditos", saldos_bytes),
+            (
+                "creditos_ejecucion.csv",
+                "creditos_ejecucion",
+                ejecucion_bytes,
+            ),
+        ] {
+            let ruta = ruta_base(nombre);
+            std::fs::write(&ruta, &datos)?;
+            resultados.push(ResultadoExport {
+                ruta: ruta.to_string_lossy().to_string(),
+                tipo: tipo.to_string(),
+                tamano_original: datos.len() as u64,
+                tamano_comprimido: None,
+                hash_sha256: None,
+                hash_sha256_original: Some(exportador::generar_hash(&datos)),
+                compresion_aplicada: false,
+            });
+        }
+
+        logger::log_info(
+            "EXPORT",
+            &format!(
+                "Créditos: {} registros, {:.2} descontado, {} cancelados",
+                self.movimientos.len(),
+                self.total_descontado(),
+                self.movimientos
+                    .iter()
+                    .filter(|m| m.estado == EstadoCredito::Cancelado)
+                    .count()
+            ),
+        );
+        Ok(resultados)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credito(numero: &str, cuota: f64, saldo: f64, ciclo_inicio: &str) -> CreditoCuota {
+        CreditoCuota {
+            numero: numero.to_string(),
+            cedula: "1".to_string(),
+            descripcion: String::new(),
+            capital: 1000.0,
+            cuota,
+            saldo,
+            ciclo_inicio: ciclo_inicio.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cuotas_hasta_cancelar_saldo() {
+        let creditos = vec![
+            credito("A", 100.0, 250.0, "2026-01"),
+            credito("B", 100.0, 60.0, ""),
+            credito("C", 100.0, 500.0, "2026-03"),
+            credito("D", 100.0, 0.0, ""),
+        ];
+
+        let cuotas = cuotas_del_ciclo(&creditos, "2026-02", TipoNomina::Nact);
+        let valores: Vec<(&str, f64)> = cuotas["1"]
+            .iter()
+            .map(|c| (c.codigo.as_str(), c.valor))
+            .collect();
+        assert_eq!(valores, vec![("credito_A", 100.0), ("credito_B", 60.0)]);
+
+        let mut ben: Beneficiario = serde_json::from_str(r#"{"cedula":"1"}"#).unwrap();
+        ben.conceptos_calculados = Some(
+            cuotas["1"]
+                .iter()
+                .map(|c| (c.codigo.clone(), c.clone()))
+                .collect(),
+        );
+        let reporte = actualizar_saldos(&creditos, &[ben], "2026-02", TipoNomina::Nact);
+
+        let estados: Vec<(f64, EstadoCredito)> = reporte
+            .movimientos
+            .iter()
+            .map(|m| (m.saldo_nuevo, m.estado))
+            .collect();
+        assert_eq!(
+            estados,
+            vec![
+                (150.0, EstadoCredito::Activo),
+                (0.0, EstadoCredito::Cancelado),
+                (500.0, EstadoCredito::Pendiente),
+                (0.0, EstadoCredito::Pagado),
+            ]
+        );
+        assert_eq!(reporte.total_descontado(), 160.0);
+
+        // El saldo cancelado ya no genera cuota en el ciclo siguiente
+        let siguiente = cuotas_del_ciclo(&reporte.saldos, "2026-03", TipoNomina::Nact);
+        let codigos: Vec<&str> = siguiente["1"].iter().map(|c| c.codigo.as_str()).collect();
+        assert_eq!(codigos, vec!["credito_A", "credito_C"]);
+    }
+
+    #[test]
+    fn test_npr_no_descuenta_cuotas_ni_saldo() {
+        let creditos = vec![credito("A", 100.0, 250.0, "")];
+        let cuotas = cuotas_del_ciclo(&creditos, "2026-02", TipoNomina::Npr);
+        assert!(cuotas.is_empty());
+
+        // Nómina NPR completa: sin cuotas inyectadas el neto son las garantías
+        let mut ben: Beneficiario = serde_json::from_str(r#"{"cedula":"1"}"#).unwrap();
+        ben.base.garantias = 800.0;
+        let mut bens = vec![ben];
+        let pre = bens.iter().map(|b| cuotas.get(&b.cedula).cloned()).collect();
+        crate::kernel::aplicar_conceptos_por_fases(
+            &mut bens,
+            pre,
+            &ejecutor_vacio(),
+            TipoNomina::Npr,
+            0.0,
+        );
+        assert_eq!(bens[0].neto, 800.0);
+        assert_eq!(bens[0].total_deducciones, 0.0);
+
+        let reporte = actualizar_saldos(&creditos, &bens, "2026-02", TipoNomina::Npr);
+        assert_eq!(reporte.saldos[0].saldo, 250.0);
+        assert_eq!(reporte.movimientos[0].descontado, 0.0);
+        assert_eq!(reporte.movimientos[0].estado, EstadoCredito::NoDescontado);
+        assert_eq!(reporte.total_descontado(), 0.0);
+    }
+
+    fn ejecutor_vacio() -> crate::nomina::EjecutorConceptos {
+        crate::nomina::EjecutorConceptos::new(Vec::new()).unwrap()
+    }
+}
@@ -0,0 +1,530 @@
+//! Diferencias entre la nómina exportada de dos ciclos (`sandra diff`).
+//!
+//! Cruza por cédula los `nomina_<tipo>_<ciclo>.csv` de dos carpetas de salida y
+//! reporta altas, bajas y campos modificados. Sobre los registros presentes en
+//! ambos ciclos marca anomalías según los umbrales de `DiferenciasConfig`.
+
+use crate::calc::calculos::redondear_dos;
+use crate::kernel::logica::exportador::{self, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::model::manifiesto::DiferenciasConfig;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+const CAMPO_NETO: &str = "sueldo_total";
+const CAMPO_CUENTA: &str = "numero_cuenta";
+const CAMPO_SUELDO_BASE: &str = "sueldo_base";
+const CAMPO_GRADO: &str = "grado_id";
+
+/// Nómina de un ciclo leída del CSV exportado, indexada por cédula.
+#[derive(Debug, Clone, Default)]
+pub struct NominaExportada {
+    pub archivo: String,
+    pub columnas: Vec<String>,
+    pub registros: BTreeMap<String, HashMap<String, String>>,
+}
+
+impl NominaExportada {
+    /// Acepta el archivo (`.csv` o `.csv.zst`) o la carpeta de salida del ciclo.
+    /// En una carpeta busca `nomina_<tipo>_*.csv`; sin tipo debe haber uno solo.
+    pub fn cargar(ruta: &str, tipo: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let archivo = resolver_archivo(Path::new(ruta), tipo)?;
+        let bytes = std::fs::read(&archivo)
+            .map_err(|e| format!("No se pudo leer '{}': {}", archivo.display(), e))?;
+        let datos = if archivo.extension().is_some_and(|e| e == "zst") {
+            zstd::stream::decode_all(bytes.as_slice())
+                .map_err(|e| format!("No se pudo descomprimir '{}': {}", archivo.display(), e))?
+        } else {
+            bytes
+        };
+        let mut nomina = Self::desde_csv(&datos)
+            .map_err(|e| format!("Nómina '{}' inválida: {}", archivo.display(), e))?;
+        nomina.archivo = archivo.to_string_lossy().to_string();
+        Ok(nomina)
+    }
+
+    pub fn desde_csv(datos: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut rdr = csv::Reader::from_reader(datos);
+        let columnas: Vec<String> = rdr
+            .headers()?
+            .iter()
+            .map(|h| h.trim().to_string())
+            .collect();
+        if !columnas.iter().any(|c| c == "cedula") {
+            return Err("no tiene columna 'cedula'".into());
+        }
+
+        let mut registros = BTreeMap::new();
+        for fila in rdr.records() {
+            let fila = fila?;
+            let mut registro: HashMap<String, String> = HashMap::new();
+            for (columna, valor) in columnas.iter().zip(fila.iter()) {
+                // Si una columna se repite se conserva la primera
+                registro
+                    .entry(columna.clone())
+                    .or_insert_with(|| valor.trim().to_string());
+            }
+            let cedula = registro.get("cedula").cloned().unwrap_or_default();
+            if !cedula.is_empty() {
+                registros.insert(cedula, registro);
+            }
+        }
+
+        Ok(Self {
+            archivo: String::new(),
+            columnas,
+            registros,
+        })
+    }
+
+    pub fn total_neto(&self) -> f64 {
+        redondear_dos(self.registros.values().map(|r| numero(r, CAMPO_NETO)).sum())
+    }
+}
+
+fn resolver_archivo(
+    path: &Path,
+    tipo: Option<&str>,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if !path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+
+    let prefijo = match tipo {
+        Some(t) => format!("nomina_{}_", t.to_lowercase()),
+        None => "nomina_".to_string(),
+    };
+    let mut candidatos: Vec<PathBuf> = std::fs::read_dir(path)
+        .map_err(|e| format!("No se pudo leer la carpeta '{}': {}", path.display(), e))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            let nombre = p
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            nombre.starts_with(&prefijo)
+                && !nombre.contains("_paralizado_")
+                && (nombre.ends_with(".csv") || nombre.ends_with(".csv.zst"))
+        })
+        .collect();
+    candidatos.sort();
+
+    match candidatos.len() {
+        0 => Err(format!("No hay archivos '{}*.csv' en '{}'", prefijo, path.display()).into()),
+        1 => Ok(candidatos.remove(0)),
+        _ => Err(format!(
+            "Hay varias nóminas en '{}' ({}); indique el tipo con --tipo",
+            path.display(),
+            candidatos
+                .iter()
+                .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .into()),
+    }
+}
+
+fn numero(registro: &HashMap<String, String>, campo: &str) -> f64 {
+    registro
+        .get(campo)
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.0)
+}
+
+fn iguales(antes: &str, despues: &str) -> bool {
+    match (antes.parse::<f64>(), despues.parse::<f64>()) {
+        (Ok(a), Ok(b)) => (a - b).abs() < 0.005,
+        _ => antes == despues,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TipoAnomalia {
+    VariacionNeto,
+    CambioCuenta,
+    SueldoBaseSinAscenso,
+}
+
+impl std::fmt::Display for TipoAnomalia {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TipoAnomalia::VariacionNeto => "variacion_neto",
+            TipoAnomalia::CambioCuenta => "cambio_cuenta",
+            TipoAnomalia::SueldoBaseSinAscenso => "sueldo_base_sin_ascenso",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Anomalia {
+    pub cedula: String,
+    pub tipo: TipoAnomalia,
+    pub detalle: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegistroNomina {
+    pub cedula: String,
+    pub nombre: String,
+    pub neto: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CambioCampo {
+    pub campo: String,
+    pub antes: String,
+    pub despues: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegistroModificado {
+    pub cedula: String,
+    pub cambios: Vec<CambioCampo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffNomina {
+    pub archivo_prev: String,
+    pub archivo_curr: String,
+    pub total_prev: f64,
+    pub total_curr: f64,
+    pub altas: Vec<RegistroNomina>,
+    pub bajas: Vec<RegistroNomina>,
+    pub modificados: Vec<RegistroModificado>,
+    pub anomalias: Vec<Anomalia>,
+}
+
+fn resumen(cedula: &str, registro: &HashMap<String, String>) -> RegistroNomina {
+    let campo = |c: &str| registro.get(c).cloned().unwrap_or_default();
+    RegistroNomina {
+        cedula: cedula.to_string(),
+        nombre: format!("{} {}", campo("nombres"), campo("apellidos"))
+            .trim()
+            .to_string(),
+        neto: numero(registro, CAMPO_NETO),
+    }
+}
+
+fn detectar_anomalias(
+    cedula: &str,
+    prev: &HashMap<String, String>,
+    curr: &HashMap<String, String>,
+    config: &DiferenciasConfig,
+) -> Vec<Anomalia> {
+    let mut anomalias = Vec::new();
+    let anomalia = |tipo, detalle| Anomalia {
+        cedula: cedula.to_string(),
+        tipo,
+        detalle,
+    };
+
+    let neto_prev = numero(prev, CAMPO_NETO);
+    let neto_curr = numero(curr, CAMPO_NETO);
+    if (neto_curr - neto_prev).abs() >= 0.005 {
+        if neto_prev.abs() < 0.005 {
+            anomalias.push(anomalia(
+                TipoAnomalia::VariacionNeto,
+                format!("neto 0.00 -> {:.2} (sin neto previo)", neto_curr),
+            ));
+        } else {
+            let variacion = (neto_curr - neto_prev) / neto_prev.abs() * 100.0;
+            if variacion.abs() > config.variacion_neto_pct {
+                anomalias.push(anomalia(
+                    TipoAnomalia::VariacionNeto,
+                    format!(
+                        "neto {:.2} -> {:.2} ({:+.2}%)",
+                        neto_prev, neto_curr, variacion
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Nóminas exportadas antes de incluir la cuenta no tienen la columna
+    if let (Some(cuenta_prev), Some(cuenta_curr)) = (prev.get(CAMPO_CUENTA), curr.get(CAMPO_CUENTA))
+    {
+        if cuenta_prev != cuenta_curr && neto_curr >= config.monto_cambio_cuenta {
+            anomalias.push(anomalia(
+                TipoAnomalia::CambioCuenta,
+                format!(
+                    "cuenta {} -> {} con neto {:.2}",
+                    cuenta_prev, cuenta_curr, neto_curr
+                ),
+            ));
+        }
+    }
+
+    if config.sueldo_base_sin_ascenso {
+        let base_prev = numero(prev, CAMPO_SUELDO_BASE);
+        let base_curr = numero(curr, CAMPO_SUELDO_BASE);
+        let grado_prev = prev
+            .get(CAMPO_GRADO)
+            .map(String::as_str)
+            .unwrap_or_default();
+        let grado_curr = curr
+            .get(CAMPO_GRADO)
+            .map(String::as_str)
+            .unwrap_or_default();
+        if (base_prev - base_curr).abs() >= 0.005 && grado_prev == grado_curr {
+            anomalias.push(anomalia(
+                TipoAnomalia::SueldoBaseSinAscenso,
+                format!(
+                    "sueldo_base {:.2} -> {:.2} con grado {}",
+                    base_prev, base_curr, grado_curr
+                ),
+            ));
+        }
+    }
+
+    anomalias
+}
+
+/// Compara la nómina del ciclo anterior con la del actual.
+pub fn comparar(
+    prev: &NominaExportada,
+    curr: &NominaExportada,
+    config: &DiferenciasConfig,
+) -> DiffNomina {
+    let mut diff = DiffNomina {
+        archivo_prev: prev.archivo.clone(),
+        archivo_curr: curr.archivo.clone(),
+        total_prev: prev.total_neto(),
+        total_curr: curr.total_neto(),
+        altas: Vec::new(),
+        bajas: Vec::new(),
+        modificados: Vec::new(),
+        anomalias: Vec::new(),
+    };
+
+    let campos: Vec<&String> = curr
+        .columnas
+        .iter()
+        .filter(|c| c.as_str() != "cedula" && prev.columnas.contains(c))
+        .filter(|c| !config.ignorar_campos.contains(c))
+        .collect();
+
+    for (cedula, registro) in &curr.registros {
+        let Some(anterior) = prev.registros.get(cedula) else {
+            diff.altas.push(resumen(cedula, registro));
+            continue;
+        };
+
+        let mut cambios: Vec<CambioCampo> = Vec::new();
+        for campo in &campos {
+            let antes = anterior.get(*campo).cloned().unwrap_or_default();
+            let despues = registro.get(*campo).cloned().unwrap_or_default();
+            if !iguales(&antes, &despues) && !cambios.iter().any(|c| &c.campo == *campo) {
+                cambios.push(CambioCampo {
+                    campo: campo.to_string(),
+                    antes,
+                    despues,
+                });
+            }
+        }
+        if !cambios.is_empty() {
+            diff.modificados.push(RegistroModificado {
+                cedula: cedula.clone(),
+                cambios,
+            });
+        }
+        diff.anomalias
+            .extend(detectar_anomalias(cedula, anterior, registro, config));
+    }
+
+    for (cedula, registro) in &prev.registros {
+        if !curr.registros.contains_key(cedula) {
+            diff.bajas.push(resumen(cedula, registro));
+        }
+    }
+
+    diff
+}
+
+impl DiffNomina {
+    /// Registros modificados por campo, de mayor a menor.
+    pub fn cambios_por_campo(&self) -> Vec<(String, usize)> {
+        let mut conteo: BTreeMap<&str, usize> = BTreeMap::new();
+        for m in &self.modificados {
+            for c in &m.cambios {
+                *conteo.entry(c.campo.as_str()).or_default() += 1;
+            }
+        }
+        let mut conteo: Vec<(String, usize)> = conteo
+            .into_iter()
+            .map(|(c, n)| (c.to_string(), n))
+            .collect();
+        conteo.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        conteo
+    }
+
+    /// Escribe `diferencias_nomina.csv` (altas, bajas y campos modificados) y
+    /// `anomalias_nomina.csv`.
+    pub fn exportar(
+        &self,
+        destino: &str,
+    ) -> Result<Vec<ResultadoExport>, Box<dyn std::error::Error>> {
+        let ruta_base = |nombre: &str| {
+            if destino == "." || destino.is_empty() {
+                PathBuf::from(nombre)
+            } else {
+                PathBuf::from(destino).join(nombre)
+            }
+        };
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_writer(Vec::new());
+        wtr.write_record(["cedula", "cambio", "campo", "antes", "despues"])?;
+        for a in &self.altas {
+            wtr.write_record([
+                a.cedula.as_str(),
+                "alta",
+                CAMPO_NETO,
+                "",
+                &format!("{:.2}", a.neto),
+            ])?;
+        }
+        for b in &self.bajas {
+            wtr.write_record([
+                b.cedula.as_str(),
+                "baja",
+                CAMPO_NETO,
+                &format!("{:.2}", b.neto),
+                "",
+            ])?;
+        }
+        for m in &self.modificados {
+            for c in &m.cambios {
+                wtr.write_record([
+                    m.cedula.as_str(),
+                    "modificado",
+                    &c.campo,
+                    &c.antes,
+                    &c.despues,
+                ])?;
+            }
+        }
+        let diferencias_bytes = wtr.into_inner()?;
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_writer(Vec::new());
+        wtr.write_record(["cedula", "tipo", "detalle"])?;
+        for a in &self.anomalias {
+            wtr.write_record([a.cedula.clone(), a.tipo.to_string(), a.detalle.clone()])?;
+        }
+        let anomalias_bytes = wtr.into_inner()?;
+
+        let mut resultados = Vec::new();
+        for (nombre, tipo, datos) in [
+            (
+                "diferencias_nomina.csv",
+                "diferencias_nomina",
+                diferencias_bytes,
+            ),
+            ("anomalias_nomina.csv", "anomalias_nomina", anomalias_bytes),
+        ] {
+            let ruta = ruta_base(nombre);
+            std::fs::write(&ruta, &datos)?;
+            resultados.push(ResultadoExport {
+                ruta: ruta.to_string_lossy().to_string(),
+                tipo: tipo.to_string(),
+                tamano_original: datos.len() as u64,
+                tamano_comprimido: None,
+                hash_sha256: None,
+                hash_sha256_original: Some(exportador::generar_hash(&datos)),
+                compresion_aplicada: false,
+            });
+        }
+
+        logger::log_info(
+            "EXPORT",
+            &format!(
+                "Diferencias de nómina: {} altas, {} bajas, {} modificados, {} anomalías",
+                self.altas.len(),
+                self.bajas.len(),
+                self.modificados.len(),
+                self.anomalias.len()
+            ),
+        );
+        Ok(resultados)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNAS: &str =
+        "cedula,nombres,apellidos,grado_id,sueldo_base,numero_cuenta,sueldo_total";
+
+    fn nomina(filas: &[&str]) -> NominaExportada {
+        let csv = format!("{}\n{}\n", COLUMNAS, filas.join("\n"));
+        NominaExportada::desde_csv(csv.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_altas_bajas_y_anomalias() {
+        let prev = nomina(&[
+            "1,ANA,PEREZ,10,100.00,01020001,1000.00",
+            "2,LUIS,DIAZ,10,100.00,01020002,1000.00",
+            "3,EVA,ROJAS,11,200.00,01020003,2000.00",
+            "4,JOSE,MORA,12,300.00,01020004,3000.00",
+        ]);
+        let curr = nomina(&[
+            "1,ANA,PEREZ,10,100.00,01020001,1000.00",
+            "2,LUIS,DIAZ,10,100.00,01020002,1500.00",
+            "3,EVA,ROJAS,11,250.00,01750099,2100.00",
+            "5,RAUL,SOSA,10,100.00,01020005,900.00",
+        ]);
+        let config = DiferenciasConfig {
+            monto_cambio_cuenta: 2000.0,
+            ..Default::default()
+        };
+
+        let d = comparar(&prev, &curr, &config);
+
+        let altas: Vec<&str> = d.altas.iter().map(|a| a.cedula.as_str()).collect();
+        let bajas: Vec<&str> = d.bajas.iter().map(|b| b.cedula.as_str()).collect();
+        assert_eq!(altas, vec!["5"]);
+        assert_eq!(bajas, vec!["4"]);
+
+        let campos: Vec<(&str, Vec<&str>)> = d
+            .modificados
+            .iter()
+            .map(|m| {
+                (
+                    m.cedula.as_str(),
+                    m.cambios.iter().map(|c| c.campo.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            campos,
+            vec![
+                ("2", vec!["sueldo_total"]),
+                ("3", vec!["sueldo_base", "numero_cuenta", "sueldo_total"]),
+            ]
+        );
+
+        let anomalias: Vec<(&str, TipoAnomalia)> = d
+            .anomalias
+            .iter()
+            .map(|a| (a.cedula.as_str(), a.tipo))
+            .collect();
+        assert_eq!(
+            anomalias,
+            vec![
+                ("2", TipoAnomalia::VariacionNeto),
+                ("3", TipoAnomalia::CambioCuenta),
+                ("3", TipoAnomalia::SueldoBaseSinAscenso),
+            ]
+        );
+        assert_eq!(d.total_prev, 7000.0);
+        assert_eq!(d.total_curr, 5500.0);
+    }
+}
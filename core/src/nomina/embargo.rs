@@ -0,0 +1,436 @@
+//! Embargos judiciales sobre el neto de la nómina.
+//!
+//! Las órdenes activas de cada cédula se aplican por prioridad después del neto
+//! (conceptos, fases y límite de deducciones), sin superar el máximo embargable.
+//! Lo retenido se registra como la deducción `embargo_judicial` del beneficiario
+//! y genera una línea de pago a la cuenta del beneficiario del embargo.
+//!
+//! Si la cédula aparece en más de un registro de la nómina no se elige uno: las
+//! órdenes quedan como `cedula_repetida` para revisión manual.
+
+use crate::calc::calculos::redondear_dos;
+use crate::kernel::logica::exportador::{self, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::kernel::logica::memoria::{Beneficiario, ConceptoCalculado, OrdenEmbargo, TipoConcepto};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+pub const CODIGO_CONCEPTO_EMBARGO: &str = "embargo_judicial";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstadoEmbargo {
+    /// Se retuvo el monto completo.
+    Aplicado,
+    /// Se retuvo una parte por falta de neto o por el máximo embargable.
+    Parcial,
+    /// No quedó neto embargable.
+    SinSaldo,
+    /// La cédula no está en la nómina del ciclo.
+    SinBeneficiario,
+    /// La cédula está en más de un registro de la nómina; no se aplica.
+    CedulaRepetida,
+    /// La orden no indica monto/porcentaje o cuenta de destino.
+    Invalida,
+    /// El embargado fue excluido de la nómina por una regla de validación.
+    Excluido,
+}
+
+impl std::fmt::Display for EstadoEmbargo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            EstadoEmbargo::Aplicado => "aplicado",
+            EstadoEmbargo::Parcial => "parcial",
+            EstadoEmbargo::SinSaldo => "sin_saldo",
+            EstadoEmbargo::SinBeneficiario => "sin_beneficiario",
+            EstadoEmbargo::CedulaRepetida => "cedula_repetida",
+            EstadoEmbargo::Invalida => "invalida",
+            EstadoEmbargo::Excluido => "excluido",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Resultado de una orden en el ciclo.
+#[derive(Debug, Clone, Serialize)]
+pub struct EjecucionEmbargo {
+    pub expediente: String,
+    pub tribunal: String,
+    pub cedula: String,
+    pub prioridad: u32,
+    pub neto_previo: f64,
+    pub solicitado: f64,
+    pub aplicado: f64,
+    pub estado: EstadoEmbargo,
+}
+
+/// Línea de pago al beneficiario de un embargo.
+#[derive(Debug, Clone, Serialize)]
+pub struct PagoEmbargo {
+    pub expediente: String,
+    pub tribunal: String,
+    pub cedula_embargado: String,
+    pub cedula_beneficiario: String,
+    pub nombre_beneficiario: String,
+    pub numero_cuenta: String,
+    pub monto: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReporteEmbargos {
+    pub ejecuciones: Vec<EjecucionEmbargo>,
+    pub pagos: Vec<PagoEmbargo>,
+}
+
+/// Aplica las órdenes sobre el neto de los beneficiarios. `porcentaje_maximo` es
+/// el tope embargable sobre el neto previo, sumando todas las órdenes de la cédula.
+pub fn aplicar_embargos(
+    beneficiarios: &mut [Beneficiario],
+    ordenes: &[OrdenEmbargo],
+    porcentaje_maximo: f64,
+) -> ReporteEmbargos {
+    let mut reporte = ReporteEmbargos::default();
+
+    let mut por_cedula: HashMap<&str, Vec<&OrdenEmbargo>> = HashMap::new();
+    for orden in ordenes {
+        por_cedula
+            .entry(orden.cedula.trim())
+            .or_default()
+            .push(orden);
+    }
+    let mut cedulas: Vec<&str> = por_cedula.keys().copied().collect();
+    cedulas.sort();
+
+    // Índices de la nómina por cédula, construidos una sola vez
+    let mut indices: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, b) in beneficiarios.iter().enumerate() {
+        indices.entry(b.cedula.trim().to_string()).or_default().push(i);
+    }
+
+    for cedula in cedulas {
+        let mut ordenes_cedula = por_cedula.remove(cedula).unwrap_or_default();
+        ordenes_cedula
+            .sort_by(|a, b| (a.prioridad, &a.expediente).cmp(&(b.prioridad, &b.expediente)));
+
+        let idx = match indices.get(cedula).map(Vec::as_slice) {
+            Some([idx]) => *idx,
+            registros => {
+                let estado = if registros.is_some() {
+                    EstadoEmbargo::CedulaRepetida
+                } else {
+                    EstadoEmbargo::SinBeneficiario
+                };
+                for orden in ordenes_cedula {
+                    reporte.ejecuciones.push(ejecucion(
+                        orden,
+                        0.0,
+                        solicitado(orden, 0.0),
+                        0.0,
+                        estado,
+                    ));
+                }
+                continue;
+            }
+        };
+        let ben = &mut beneficiarios[idx];
+
+        let neto_previo = ben.neto;
+        let mut disponible =
+            redondear_dos(neto_previo.max(0.0) * porcentaje_maximo.clamp(0.0, 100.0) / 100.0);
+        let mut retenido = 0.0;
+
+        for orden in ordenes_cedula {
+            let monto = solicitado(orden, neto_previo);
+            if monto <= 0.0 || orden.numero_cuenta.trim().is_empty() {
+                reporte.ejecuciones.push(ejecucion(
+                    orden,
+                    neto_previo,
+                    monto,
+                    0.0,
+                    EstadoEmbargo::Invalida,
+                ));
+                continue;
+            }
+
+            let aplicado = redondear_dos(monto.min(disponible));
+            let estado = if aplicado <= 0.0 {
+                EstadoEmbargo::SinSaldo
+            } else if aplicado < monto {
+                EstadoEmbargo::Parcial
+            } else {
+                EstadoEmbargo::Aplicado
+            };
+            disponible = redondear_dos(disponible - aplicado);
+            retenido += aplicado;
+
+            if aplicado > 0.0 {
+                reporte.pagos.push(PagoEmbargo {
+                    expediente: orden.expediente.clone(),
+                    tribunal: orden.tribunal.clone(),
+                    cedula_embargado: ben.cedula.clone(),
+                    cedula_beneficiario: orden.cedula_beneficiario.clone(),
+                    nombre_beneficiario: orden.nombre_beneficiario.clone(),
+                    numero_cuenta: orden.numero_cuenta.trim().to_string(),
+                    monto: aplicado,
+                });
+            }
+            reporte
+                .ejecuciones
+                .push(ejecucion(orden, neto_previo, monto, aplicado, estado));
+        }
+
+        if retenido > 0.0 {
+            registrar_retencion(ben, redondear_dos(retenido));
+        }
+    }
+
+    reporte
+}
+
+fn solicitado(orden: &OrdenEmbargo, neto: f64) -> f64 {
+    if orden.porcentaje > 0.0 {
+        redondear_dos(neto.max(0.0) * orden.porcentaje / 100.0)
+    } else {
+        redondear_dos(orden.monto_fijo)
+    }
+}
+
+fn ejecucion(
+    orden: &OrdenEmbargo,
+    neto_previo: f64,
+    solicitado: f64,
+    aplicado: f64,
+    estado: EstadoEmbargo,
+) -> EjecucionEmbargo {
+    EjecucionEmbargo {
+        expediente: orden.expediente.clone(),
+        tribunal: orden.tribunal.clone(),
+        cedula: orden.cedula.trim().to_string(),
+        prioridad: orden.prioridad,
+        neto_previo,
+        solicitado,
+        aplicado,
+        estado,
+    }
+}
+
+/// Suma lo retenido como deducción del beneficiario y lo descuenta del neto.
+fn registrar_retencion(ben: &mut Beneficiario, monto: f64) {
+    let conceptos = ben.conceptos_calculados.get_or_insert_with(HashMap::new);
+    conceptos
+        .entry(CODIGO_CONCEPTO_EMBARGO.to_string())
+        .or_insert_with(|| ConceptoCalculado {
+            codigo: CODIGO_CONCEPTO_EMBARGO.to_string(),
+            descripcion: "EMBARGO JUDICIAL".to_string(),
+            tipo: TipoConcepto::Deduccion,
+            valor: 0.0,
+            estructura: String::new(),
+            cuenta: String::new(),
+            partida: String::new(),
+        })
+        .valor += monto;
+    ben.total_deducciones = redondear_dos(ben.total_deducciones + monto);
+    ben.neto = redondear_dos(ben.neto - monto);
+}
+
+impl ReporteEmbargos {
+    pub fn total_aplicado(&self) -> f64 {
+        redondear_dos(self.pagos.iter().map(|p| p.monto).sum())
+    }
+
+    /// Anula las retenciones de las cédulas excluidas de la nómina: sus
+    /// órdenes quedan como `excluido` y sus pagos no se emiten.
+    pub fn excluir(&mut self, cedulas: &[String]) {
+        let excluidas: HashSet<&str> = cedulas.iter().map(|c| c.trim()).collect();
+        for e in self.ejecuciones.iter_mut() {
+            if excluidas.contains(e.cedula.trim()) && e.aplicado > 0.0 {
+                e.aplicado = 0.0;
+                e.estado = EstadoEmbargo::Excluido;
+            }
+        }
+        self.pagos
+            .retain(|p| !excluidas.contains(p.cedula_embargado.trim()));
+    }
+
+    /// Escribe `embargos_ejecucion.csv` (una fila por orden) y `pagos_embargos.csv`
+    /// (una línea de pago por orden con retención).
+    pub fn exportar(
+        &self,
+        destino: &str,
+    ) -> Result<Vec<ResultadoExport>, Box<dyn std::error::Error>> {
+        let ruta_base = |nombre: &str| {
+            if destino == "." || destino.is_empty() {
+                PathBuf::from(nombre)
+            } else {
+                PathBuf::from(destino).join(nombre)
+            }
+        };
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_writer(Vec::new());
+        wtr.write_record([
+            "expediente",
+            "tribunal",
+            "cedula",
+            "prioridad",
+            "neto_previo",
+            "solicitado",
+            "aplicado",
+            "estado",
+        ])?;
+        for e in &self.ejecuciones {
+            wtr.write_record([
+                e.expediente.clone(),
+                e.tribunal.clone(),
+                e.cedula.clone(),
+                e.prioridad.to_string(),
+                format!("{:.2}", e.neto_previo),
+                format!("{:.2}", e.solicitado),
+                format!("{:.2}", e.aplicado),
+                e.estado.to_string(),
+            ])?;
+        }
+        let ejecucion_bytes = wtr.into_inner()?;
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_writer(Vec::new());
+        wtr.write_record([
+            "expediente",
+            "tribunal",
+            "cedula_embargado",
+            "cedula_beneficiario",
+            "nombre_beneficiario",
+            "numero_cuenta",
+            "monto",
+        ])?;
+        for p in &self.pagos {
+            wtr.write_record([
+                p.expediente.clone(),
+                p.tribunal.clone(),
+                p.cedula_embargado.clone(),
+                p.cedula_beneficiario.clone(),
+                p.nombre_beneficiario.clone(),
+                p.numero_cuenta.clone(),
+                format!("{:.2}", p.monto),
+            ])?;
+        }
+        let pagos_bytes = wtr.into_inner()?;
+
+        let mut resultados = Vec::new();
+        for (nombre, tipo, datos) in [
+            (
+                "embargos_ejecucion.csv",
+                "embargos_ejecucion",
+                ejecucion_bytes,
+            ),
+            ("pagos_embargos.csv", "pagos_embargos", pagos_bytes),
+        ] {
+            let ruta = ruta_base(nombre);
+            std::fs::write(&ruta, &datos)?;
+            resultados.push(ResultadoExport {
+                ruta: ruta.to_string_lossy().to_string(),
+                tipo: tipo.to_string(),
+                tamano_original: datos.len() as u64,
+                tamano_comprimido: None,
+                hash_sha256: None,
+                hash_sha256_original: Some(exportador::generar_hash(&datos)),
+                compresion_aplicada: false,
+            });
+        }
+
+        logger::log_info(
+            "EXPORT",
+            &format!(
+                "Embargos: {} órdenes, {} pagos, {:.2} retenido",
+                self.ejecuciones.len(),
+                self.pagos.len(),
+                self.total_aplicado()
+            ),
+        );
+        Ok(resultados)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orden(expediente: &str, porcentaje: f64, monto_fijo: f64, prioridad: u32) -> OrdenEmbargo {
+        OrdenEmbargo {
+            cedula: "1".to_string(),
+            expediente: expediente.to_string(),
+            tribunal: "TRIBUNAL 1".to_string(),
+            porcentaje,
+            monto_fijo,
+            cedula_beneficiario: "2".to_string(),
+            nombre_beneficiario: "DEMANDANTE".to_string(),
+            numero_cuenta: "01020000000000000001".to_string(),
+            prioridad,
+        }
+    }
+
+    #[test]
+    fn test_embargos_por_prioridad_con_maximo_embargable() {
+        let mut ben: Beneficiario = serde_json::from_str(r#"{"cedula":"1"}"#).unwrap();
+        ben.neto = 1000.0;
+        let mut bens = vec![ben];
+        let mut sin_cuenta = orden("C", 0.0, 50.0, 3);
+        sin_cuenta.numero_cuenta.clear();
+        let mut ajeno = orden("D", 10.0, 0.0, 1);
+        ajeno.cedula = "9".to_string();
+
+        // Máximo embargable 40% = 400: B (30% = 300) primero, luego A (fijo 200) parcial
+        let reporte = aplicar_embargos(
+            &mut bens,
+            &[
+                orden("A", 0.0, 200.0, 2),
+                orden("B", 30.0, 0.0, 1),
+                sin_cuenta,
+                ajeno,
+            ],
+            40.0,
+        );
+
+        let estados: Vec<(&str, f64, EstadoEmbargo)> = reporte
+            .ejecuciones
+            .iter()
+            .map(|e| (e.expediente.as_str(), e.aplicado, e.estado))
+            .collect();
+        assert_eq!(
+            estados,
+            vec![
+                ("B", 300.0, EstadoEmbargo::Aplicado),
+                ("A", 100.0, EstadoEmbargo::Parcial),
+                ("C", 0.0, EstadoEmbargo::Invalida),
+                ("D", 0.0, EstadoEmbargo::SinBeneficiario),
+            ]
+        );
+        assert_eq!(reporte.pagos.len(), 2);
+        assert_eq!(reporte.total_aplicado(), 400.0);
+        assert_eq!(bens[0].neto, 600.0);
+        assert_eq!(bens[0].total_deducciones, 400.0);
+        assert_eq!(
+            bens[0].conceptos_calculados.as_ref().unwrap()[CODIGO_CONCEPTO_EMBARGO].valor,
+            400.0
+        );
+
+        // Embargado excluido por validación: no se emiten sus pagos
+        let mut excluido = reporte.clone();
+        excluido.excluir(&["1".to_string()]);
+        assert!(excluido.pagos.is_empty());
+        assert_eq!(excluido.total_aplicado(), 0.0);
+        assert_eq!(excluido.ejecuciones[0].estado, EstadoEmbargo::Excluido);
+        assert_eq!(excluido.ejecuciones[2].estado, EstadoEmbargo::Invalida);
+
+        // Cédula en dos registros: no se elige ninguno
+        let mut repetidos = vec![bens[0].clone(), bens[0].clone()];
+        let reporte = aplicar_embargos(&mut repetidos, &[orden("E", 0.0, 10.0, 1)], 40.0);
+        assert_eq!(reporte.ejecuciones[0].estado, EstadoEmbargo::CedulaRepetida);
+        assert!(reporte.pagos.is_empty());
+        assert_eq!(repetidos[0].neto, 600.0);
+        assert_eq!(repetidos[1].neto, 600.0);
+    }
+}
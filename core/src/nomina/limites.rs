@@ -0,0 +1,204 @@
+//! Límite legal de deducciones sobre el bruto de cada beneficiario.
+//!
+//! Cuando el total de deducciones supera `porcentaje_maximo` del bruto (neto +
+//! deducciones), el exceso se recorta empezando por las deducciones de menor
+//! prioridad de cobro: primero las que no declaran prioridad, luego las de número
+//! mayor; a igual prioridad, la evaluada más tarde.
+
+use crate::calc::calculos::redondear_dos;
+use crate::kernel::logica::exportador::{self, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::kernel::logica::memoria::{Beneficiario, ConceptoCalculado, TipoConcepto};
+use crate::tipos::TipoNomina;
+use serde::Serialize;
+use std::path::PathBuf;
+
+pub const NOMBRE_ARCHIVO_RECORTES: &str = "recortes_deducciones.csv";
+
+/// Recorte aplicado a una deducción de un beneficiario.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecorteDeduccion {
+    pub cedula: String,
+    pub codigo: String,
+    pub descripcion: String,
+    pub prioridad: Option<u32>,
+    pub bruto: f64,
+    pub limite: f64,
+    pub original: f64,
+    pub aplicado: f64,
+    pub recortado: f64,
+}
+
+/// Recorta las deducciones de `conceptos` para que no superen el límite.
+/// `beneficiario` debe tener totales y neto ya calculados con esos conceptos.
+/// Solo NACT y NRCP descuentan deducciones del neto; el resto no se limita.
+pub fn limitar_deducciones(
+    beneficiario: &Beneficiario,
+    conceptos: &mut [ConceptoCalculado],
+    porcentaje_maximo: f64,
+    tipo_nomina: TipoNomina,
+    prioridad: impl Fn(&str) -> Option<u32>,
+) -> Vec<RecorteDeduccion> {
+    if porcentaje_maximo <= 0.0 || !matches!(tipo_nomina, TipoNomina::Nact | TipoNomina::Nrcp) {
+        return Vec::new();
+    }
+
+    let bruto = beneficiario.neto + beneficiario.total_deducciones;
+    let limite = redondear_dos(bruto.max(0.0) * porcentaje_maximo / 100.0);
+    let mut exceso = redondear_dos(beneficiario.total_deducciones - limite);
+    if exceso <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut orden: Vec<usize> = (0..conceptos.len())
+        .filter(|&i| {
+            matches!(conceptos[i].tipo, TipoConcepto::Deduccion) && conceptos[i].valor > 0.0
+        })
+        .collect();
+    orden.sort_by_key(|&i| {
+        (
+            std::cmp::Reverse(prioridad(&conceptos[i].codigo).unwrap_or(u32::MAX)),
+            std::cmp::Reverse(i),
+        )
+    });
+
+    let mut recortes = Vec::new();
+    for i in orden {
+        if exceso <= 0.0 {
+            break;
+        }
+        let c = &mut conceptos[i];
+        let recorte = redondear_dos(c.valor.min(exceso));
+        let original = c.valor;
+        c.valor = redondear_dos(original - recorte);
+        exceso = redondear_dos(exceso - recorte);
+
+        recortes.push(RecorteDeduccion {
+            cedula: beneficiario.cedula.clone(),
+            codigo: c.codigo.clone(),
+            descripcion: c.descripcion.clone(),
+            prioridad: prioridad(&c.codigo),
+            bruto,
+            limite,
+            original,
+            aplicado: c.valor,
+            recortado: recorte,
+        });
+    }
+    recortes
+}
+
+/// Escribe `recortes_deducciones.csv` (una fila por deducción recortada).
+pub fn exportar_recortes(
+    recortes: &[RecorteDeduccion],
+    destino: &str,
+) -> Result<ResultadoExport, Box<dyn std::error::Error>> {
+    let ruta = if destino == "." || destino.is_empty() {
+        PathBuf::from(NOMBRE_ARCHIVO_RECORTES)
+    } else {
+        PathBuf::from(destino).join(NOMBRE_ARCHIVO_RECORTES)
+    };
+
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(b';')
+        .from_writer(Vec::new());
+    wtr.write_record([
+        "cedula",
+        "codigo",
+        "descripcion",
+        "prioridad",
+        "bruto",
+        "limite",
+        "original",
+        "aplicado",
+        "recortado",
+    ])?;
+    for r in recortes {
+        wtr.write_record([
+            r.cedula.clone(),
+            r.codigo.clone(),
+            r.descripcion.clone(),
+            r.prioridad.map(|p| p.to_string()).unwrap_or_default(),
+            format!("{:.2}", r.bruto),
+            format!("{:.2}", r.limite),
+            format!("{:.2}", r.original),
+            format!("{:.2}", r.aplicado),
+            format!("{:.2}", r.recortado),
+        ])?;
+    }
+    let datos = wtr.into_inner()?;
+    std::fs::write(&ruta, &datos)?;
+
+    logger::log_info(
+        "EXPORT",
+        &format!(
+            "Recortes de deducciones: {} conceptos, {:.2} recortado",
+            recortes.len(),
+            recortes.iter().map(|r| r.recortado).sum::<f64>()
+        ),
+    );
+
+    Ok(ResultadoExport {
+        ruta: ruta.to_string_lossy().to_string(),
+        tipo: "recortes_deducciones".to_string(),
+        tamano_original: datos.len() as u64,
+        tamano_comprimido: None,
+        hash_sha256: None,
+        hash_sha256_original: Some(exportador::generar_hash(&datos)),
+        compresion_aplicada: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deduccion(codigo: &str, valor: f64) -> ConceptoCalculado {
+        ConceptoCalculado {
+            codigo: codigo.to_string(),
+            descripcion: codigo.to_uppercase(),
+            tipo: TipoConcepto::Deduccion,
+            valor,
+            estructura: String::new(),
+            cuenta: String::new(),
+            partida: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_recorta_por_prioridad_hasta_el_limite() {
+        let mut ben: Beneficiario = serde_json::from_str(r#"{"cedula":"1"}"#).unwrap();
+        // bruto = 1000; deducciones 700 con límite del 50% => exceso 200
+        ben.total_deducciones = 700.0;
+        ben.neto = 300.0;
+        let mut conceptos = vec![
+            deduccion("sso", 100.0),
+            deduccion("prestamo", 350.0),
+            deduccion("seguro", 150.0),
+            deduccion("cuota", 100.0),
+        ];
+        let prioridad = |codigo: &str| match codigo {
+            "sso" => Some(1),
+            "prestamo" => Some(2),
+            "seguro" => Some(3),
+            _ => None,
+        };
+
+        let recortes = limitar_deducciones(&ben, &mut conceptos, 50.0, TipoNomina::Nact, prioridad);
+
+        // Primero la que no declara prioridad (cuota) y luego la de número mayor (seguro)
+        let resumen: Vec<(&str, f64, f64)> = recortes
+            .iter()
+            .map(|r| (r.codigo.as_str(), r.recortado, r.aplicado))
+            .collect();
+        assert_eq!(
+            resumen,
+            vec![("cuota", 100.0, 0.0), ("seguro", 100.0, 50.0)]
+        );
+        assert_eq!(conceptos.iter().map(|c| c.valor).sum::<f64>(), 500.0);
+
+        // NPR no descuenta deducciones del neto: no se limita
+        let mut otros = vec![deduccion("sso", 900.0)];
+        assert!(limitar_deducciones(&ben, &mut otros, 50.0, TipoNomina::Npr, prioridad).is_empty());
+    }
+}
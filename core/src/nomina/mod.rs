@@ -1,3 +1,13 @@
 pub mod concepto;
+pub mod credito;
+pub mod diferencias;
+pub mod embargo;
+pub mod limites;
+pub mod validacion;
 
-pub use concepto::{calcular_totales_conceptos, EjecutorConceptos};
+pub use concepto::{calcular_totales_conceptos, EjecutorConceptos, EntradaFase};
+pub use credito::ReporteCreditos;
+pub use diferencias::DiffNomina;
+pub use embargo::{aplicar_embargos, ReporteEmbargos};
+pub use limites::{limitar_deducciones, RecorteDeduccion};
+pub use validacion::ReporteValidacion;
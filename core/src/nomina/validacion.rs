@@ -0,0 +1,394 @@
+//! Reglas de validación declaradas en el Manifiesto, aplicadas antes de exportar.
+//!
+//! Cada regla tiene una severidad: `advertencia` solo la reporta, `excluir` saca
+//! al beneficiario de la nómina del ciclo y `abortar` detiene la ejecución antes
+//! de que el exportador escriba archivos. Todas las violaciones quedan en
+//! `validacion_nomina.csv`. Sin `validaciones` en el manifiesto se aplican
+//! `reglas_por_defecto`.
+
+use crate::banco::Banco;
+use crate::calc::obtener_directiva;
+use crate::kernel::logica::exportador::{self, ResultadoExport};
+use crate::kernel::logica::logger;
+use crate::kernel::logica::memoria::{Beneficiario, Directiva};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+pub const NOMBRE_ARCHIVO_VALIDACION: &str = "validacion_nomina.csv";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severidad {
+    #[default]
+    #[serde(alias = "warn")]
+    Advertencia,
+    #[serde(alias = "exclude")]
+    Excluir,
+    #[serde(alias = "abort")]
+    Abortar,
+}
+
+impl std::fmt::Display for Severidad {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Severidad::Advertencia => "advertencia",
+            Severidad::Excluir => "excluir",
+            Severidad::Abortar => "abortar",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TipoRegla {
+    NetoNegativo,
+    /// Neto sobre `maximos_por_grado[grado_id]` (o `maximo` si el grado no está).
+    NetoMaximoGrado,
+    CuentaFaltante,
+    /// Los 4 primeros dígitos de la cuenta no corresponden a ningún `Banco`.
+    CuentaSinBanco,
+    /// La misma cuenta aparece en más de una cédula.
+    CuentaDuplicada,
+    PorcentajeExcedido,
+    SinDirectiva,
+    /// Sueldo base con todas las primas en cero.
+    PrimasEnCero,
+    /// `n_hijos > 0` sin `prima_hijos` ni `prima_descendencia`.
+    HijosSinPrima,
+}
+
+impl std::fmt::Display for TipoRegla {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TipoRegla::NetoNegativo => "neto_negativo",
+            TipoRegla::NetoMaximoGrado => "neto_maximo_grado",
+            TipoRegla::CuentaFaltante => "cuenta_faltante",
+            TipoRegla::CuentaSinBanco => "cuenta_sin_banco",
+            TipoRegla::CuentaDuplicada => "cuenta_duplicada",
+            TipoRegla::PorcentajeExcedido => "porcentaje_excedido",
+            TipoRegla::SinDirectiva => "sin_directiva",
+            TipoRegla::PrimasEnCero => "primas_en_cero",
+            TipoRegla::HijosSinPrima => "hijos_sin_prima",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReglaValidacion {
+    pub regla: TipoRegla,
+
+    #[serde(default)]
+    pub severidad: Severidad,
+
+    /// Tope general de `neto_maximo_grado` para grados sin tope propio.
+    #[serde(default)]
+    pub maximo: Option<f64>,
+
+    #[serde(default)]
+    pub maximos_por_grado: HashMap<u32, f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Violacion {
+    pub cedula: String,
+    pub regla: TipoRegla,
+    pub severidad: Severidad,
+    pub detalle: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReporteValidacion {
+    pub violaciones: Vec<Violacion>,
+    /// Cédulas retiradas de la nómina por reglas con severidad `excluir`.
+    pub excluidos: Vec<String>,
+}
+
+impl ReglaValidacion {
+    fn detectar(
+        &self,
+        b: &Beneficiario,
+        directivas: &Vec<Directiva>,
+        cuentas: &HashMap<&str, HashSet<&str>>,
+    ) -> Option<String> {
+        let cuenta = b.numero_cuenta.trim();
+        match self.regla {
+            TipoRegla::NetoNegativo => (b.neto < 0.0).then(|| format!("neto {:.2}", b.neto)),
+            TipoRegla::NetoMaximoGrado => {
+                let tope = self
+                    .maximos_por_grado
+                    .get(&b.base.grado_id)
+                    .copied()
+                    .or(self.maximo)?;
+                (b.neto > tope).then(|| {
+                    format!(
+                        "neto {:.2} sobre el tope {:.2} del grado {}",
+                        b.neto, tope, b.base.grado_id
+                    )
+                })
+            }
+            TipoRegla::CuentaFaltante => cuenta.is_empty().then(|| "sin numero_cuenta".to_string()),
+            TipoRegla::CuentaSinBanco => {
+                if cuenta.is_empty() {
+                    return None;
+                }
+                let codigo: String = cuenta.chars().take(4).collect();
+                (Banco::from_codigo(&codigo) == Banco::Desconocido)
+                    .then(|| format!("cuenta {} sin banco conocido ({})", cuenta, codigo))
+            }
+            TipoRegla::CuentaDuplicada => {
+                let cedulas = cuentas.get(cuenta)?;
+                (cedulas.len() > 1).then(|| {
+                    let mut otras: Vec<&str> = cedulas
+                        .iter()
+                        .copied()
+                        .filter(|c| *c != b.cedula.trim())
+                        .collect();
+                    otras.sort();
+                    format!("cuenta {} también en {}", cuenta, otras.join(", "))
+                })
+            }
+            TipoRegla::PorcentajeExcedido => (b.porcentaje > 100.0)
+                .then(|| format!("porcentaje {:.2} mayor a 100", b.porcentaje)),
+            TipoRegla::SinDirectiva => {
+                obtener_directiva(b.base.grado_id, b.base.antiguedad_grado, directivas)
+                    .is_none()
+                    .then(|| {
+                        format!(
+                            "sin directiva para grado {} con {} años en grado",
+                            b.base.grado_id, b.base.antiguedad_grado
+                        )
+                    })
+            }
+            TipoRegla::PrimasEnCero => {
+                let suma: f64 = b.base.calculos.as_ref()?.values().sum();
+                (b.base.sueldo_base > 0.0 && suma == 0.0)
+                    .then(|| format!("sueldo_base {:.2} con primas en cero", b.base.sueldo_base))
+            }
+            TipoRegla::HijosSinPrima => {
+                let calculos = b.base.calculos.as_ref()?;
+                (b.base.n_hijos > 0
+                    && !calculos.contains_key("prima_hijos")
+                    && !calculos.contains_key("prima_descendencia"))
+                .then(|| {
+                    format!(
+                        "n_hijos={} sin prima_hijos/prima_descendencia",
+                        b.base.n_hijos
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Reglas vigentes cuando el manifiesto no declara `validaciones`: las
+/// advertencias de integridad de primas que el cargador ya reportaba.
+pub fn reglas_por_defecto() -> Vec<ReglaValidacion> {
+    [TipoRegla::PrimasEnCero, TipoRegla::HijosSinPrima]
+        .into_iter()
+        .map(|regla| ReglaValidacion {
+            regla,
+            severidad: Severidad::Advertencia,
+            maximo: None,
+            maximos_por_grado: HashMap::new(),
+        })
+        .collect()
+}
+
+/// Evalúa las reglas sobre los beneficiarios y retira los que violan una regla
+/// con severidad `excluir`. Con una violación `abortar` no se retira a nadie:
+/// el llamador debe detener el ciclo (`ReporteValidacion::abortar`).
+pub fn validar(
+    beneficiarios: &mut Vec<Beneficiario>,
+    directivas: &Vec<Directiva>,
+    reglas: &[ReglaValidacion],
+) -> ReporteValidacion {
+    let mut cuentas: HashMap<&str, HashSet<&str>> = HashMap::new();
+    if reglas.iter().any(|r| r.regla == TipoRegla::CuentaDuplicada) {
+        for b in beneficiarios.iter() {
+            let cuenta = b.numero_cuenta.trim();
+            if !cuenta.is_empty() {
+                cuentas.entry(cuenta).or_default().insert(b.cedula.trim());
+            }
+        }
+    }
+
+    let mut reporte = ReporteValidacion::default();
+    let mut excluir: HashSet<usize> = HashSet::new();
+    for (idx, b) in beneficiarios.iter().enumerate() {
+        for regla in reglas {
+            if let Some(detalle) = regla.detectar(b, directivas, &cuentas) {
+                if regla.severidad == Severidad::Excluir {
+                    excluir.insert(idx);
+                }
+                reporte.violaciones.push(Violacion {
+                    cedula: b.cedula.clone(),
+                    regla: regla.regla,
+                    severidad: regla.severidad,
+                    detalle,
+                });
+            }
+        }
+    }
+
+    if !reporte.abortar() && !excluir.is_empty() {
+        let mut idx = 0;
+        beneficiarios.retain(|b| {
+            let conservar = !excluir.contains(&idx);
+            if !conservar {
+                reporte.excluidos.push(b.cedula.clone());
+            }
+            idx += 1;
+            conservar
+        });
+    }
+
+    reporte
+}
+
+impl ReporteValidacion {
+    pub fn abortar(&self) -> bool {
+        self.violaciones
+            .iter()
+            .any(|v| v.severidad == Severidad::Abortar)
+    }
+
+    /// Violaciones por regla y severidad.
+    pub fn resumen(&self) -> BTreeMap<(String, Severidad), usize> {
+        let mut resumen = BTreeMap::new();
+        for v in &self.violaciones {
+            *resumen
+                .entry((v.regla.to_string(), v.severidad))
+                .or_default() += 1;
+        }
+        resumen
+    }
+
+    /// Escribe `validacion_nomina.csv` con una fila por violación.
+    pub fn exportar(&self, destino: &str) -> Result<ResultadoExport, Box<dyn std::error::Error>> {
+        let ruta = if destino == "." || destino.is_empty() {
+            PathBuf::from(NOMBRE_ARCHIVO_VALIDACION)
+        } else {
+            PathBuf::from(destino).join(NOMBRE_ARCHIVO_VALIDACION)
+        };
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_writer(Vec::new());
+        wtr.write_record(["cedula", "regla", "severidad", "detalle"])?;
+        for v in &self.violaciones {
+            wtr.write_record([
+                v.cedula.clone(),
+                v.regla.to_string(),
+                v.severidad.to_string(),
+                v.detalle.clone(),
+            ])?;
+        }
+        let datos = wtr.into_inner()?;
+        std::fs::write(&ruta, &datos)?;
+
+        logger::log_info(
+            "EXPORT",
+            &format!(
+                "Validación: {} violaciones, {} excluidos",
+                self.violaciones.len(),
+                self.excluidos.len()
+            ),
+        );
+        Ok(ResultadoExport {
+            ruta: ruta.to_string_lossy().to_string(),
+            tipo: "validacion_nomina".to_string(),
+            tamano_original: datos.len() as u64,
+            tamano_comprimido: None,
+            hash_sha256: None,
+            hash_sha256_original: Some(exportador::generar_hash(&datos)),
+            compresion_aplicada: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ben(cedula: &str, cuenta: &str, neto: f64) -> Beneficiario {
+        let mut b: Beneficiario = serde_json::from_str(&format!(
+            r#"{{"cedula":"{}","numero_cuenta":"{}"}}"#,
+            cedula, cuenta
+        ))
+        .unwrap();
+        b.neto = neto;
+        b.porcentaje = 100.0;
+        b
+    }
+
+    fn regla(regla: TipoRegla, severidad: Severidad) -> ReglaValidacion {
+        ReglaValidacion {
+            regla,
+            severidad,
+            maximo: None,
+            maximos_por_grado: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_severidades() {
+        let reglas: Vec<ReglaValidacion> = serde_json::from_str(
+            r#"[
+                {"regla": "neto_negativo", "severidad": "exclude"},
+                {"regla": "cuenta_duplicada", "severidad": "warn"},
+                {"regla": "cuenta_sin_banco"},
+                {"regla": "neto_maximo_grado", "maximo": 5000, "severidad": "excluir"}
+            ]"#,
+        )
+        .unwrap();
+        let mut beneficiarios = vec![
+            ben("1", "01020000000000000001", 1000.0),
+            ben("2", "01020000000000000001", -10.0),
+            ben("3", "99990000000000000003", 9000.0),
+            ben("4", "01750000000000000004", 500.0),
+        ];
+
+        let reporte = validar(&mut beneficiarios, &Vec::new(), &reglas);
+
+        let violaciones: Vec<(&str, TipoRegla)> = reporte
+            .violaciones
+            .iter()
+            .map(|v| (v.cedula.as_str(), v.regla))
+            .collect();
+        assert_eq!(
+            violaciones,
+            vec![
+                ("1", TipoRegla::CuentaDuplicada),
+                ("2", TipoRegla::NetoNegativo),
+                ("2", TipoRegla::CuentaDuplicada),
+                ("3", TipoRegla::CuentaSinBanco),
+                ("3", TipoRegla::NetoMaximoGrado),
+            ]
+        );
+        assert!(!reporte.abortar());
+        assert_eq!(reporte.excluidos, vec!["2", "3"]);
+        let restantes: Vec<&str> = beneficiarios.iter().map(|b| b.cedula.as_str()).collect();
+        assert_eq!(restantes, vec!["1", "4"]);
+
+        // Con una regla de aborto no se excluye a nadie
+        let mut beneficiarios = vec![ben("5", "", -1.0)];
+        let reporte = validar(
+            &mut beneficiarios,
+            &Vec::new(),
+            &[
+                regla(TipoRegla::NetoNegativo, Severidad::Excluir),
+                regla(TipoRegla::CuentaFaltante, Severidad::Abortar),
+            ],
+        );
+        assert!(reporte.abortar());
+        assert!(reporte.excluidos.is_empty());
+        assert_eq!(beneficiarios.len(), 1);
+
+        // Sin reglas en el manifiesto quedan las advertencias de primas
+        let defecto: Vec<TipoRegla> = reglas_por_defecto().iter().map(|r| r.regla).collect();
+        assert_eq!(defecto, vec![TipoRegla::PrimasEnCero, TipoRegla::HijosSinPrima]);
+    }
+}
@@ -50,10 +50,28 @@ impl TipoNomina {
         }
     }
 
+    /// Indica si el neto de la nómina descuenta las deducciones (NACT y NRCP).
+    /// En las demás los conceptos se calculan pero no se retienen.
+    pub fn descuenta_deducciones(&self) -> bool {
+        matches!(self, TipoNomina::Nact | TipoNomina::Nrcp)
+    }
+
     pub fn usa_formato_patria(&self) -> bool {
         matches!(self, TipoNomina::Npat)
     }
 
+    /// Interpreta el código corto de la nómina (`npr`, `nact`, ...), sin distinguir mayúsculas.
+    pub fn desde_codigo(codigo: &str) -> Option<Self> {
+        match codigo.trim().to_lowercase().as_str() {
+            "npr" => Some(TipoNomina::Npr),
+            "nact" => Some(TipoNomina::Nact),
+            "nrcp" => Some(TipoNomina::Nrcp),
+            "nfcp" => Some(TipoNomina::Nfcp),
+            "npat" => Some(TipoNomina::Npat),
+            _ => None,
+        }
+    }
+
     pub fn nombre_archivo(&self, ciclo: &str) -> String {
         match self {
             TipoNomina::Npat => format!("patria_{}.txt", ciclo),
@@ -0,0 +1,186 @@
+//! Cédula de identidad: letra de nacionalidad, número y formato canónico.
+//!
+//! Los valores llegan como `V-12345678`, `v12.345.678`, `E 81234567`,
+//! `012345678` o solo dígitos (se asume `V`). `Cedula::parse` valida la letra
+//! (`V`, `E`, `J`, `P`) y el largo, y es lo que usan los exportadores para la
+//! letra de la línea; `clave` es la forma que usan los cruces entre archivos.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Nacionalidad {
+    /// Venezolano.
+    #[default]
+    V,
+    /// Extranjero.
+    E,
+    /// Persona jurídica (RIF).
+    J,
+    /// Pasaporte.
+    P,
+}
+
+impl Nacionalidad {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'V' => Some(Nacionalidad::V),
+            'E' => Some(Nacionalidad::E),
+            'J' => Some(Nacionalidad::J),
+            'P' => Some(Nacionalidad::P),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Nacionalidad::V => "V",
+            Nacionalidad::E => "E",
+            Nacionalidad::J => "J",
+            Nacionalidad::P => "P",
+        }
+    }
+
+    /// Dígitos máximos del número, sin ceros a la izquierda.
+    pub fn largo_maximo(&self) -> usize {
+        match self {
+            Nacionalidad::V => 8,
+            Nacionalidad::E | Nacionalidad::J => 9,
+            Nacionalidad::P => 12,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cedula {
+    pub nacionalidad: Nacionalidad,
+    /// Solo dígitos, sin ceros a la izquierda.
+    pub numero: String,
+}
+
+impl Cedula {
+    /// Interpreta y valida una cédula; admite guiones, puntos y espacios.
+    pub fn parse(valor: &str) -> Result<Self, String> {
+        let texto = valor.trim();
+        let mut chars = texto.chars().peekable();
+        let nacionalidad = match chars.peek() {
+            None => return Err("cédula vacía".to_string()),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let letra = *c;
+                chars.next();
+                Nacionalidad::from_char(letra)
+                    .ok_or_else(|| format!("cédula {}: nacionalidad '{}' inválida", texto, letra))?
+            }
+            Some(_) => Nacionalidad::V,
+        };
+
+        let mut digitos = String::new();
+        for c in chars {
+            match c {
+                '0'..='9' => digitos.push(c),
+                '-' | '.' | ' ' => {}
+                _ => return Err(format!("cédula {}: carácter '{}' inválido", texto, c)),
+            }
+        }
+        let numero = digitos.trim_start_matches('0').to_string();
+        if numero.is_empty() {
+            return Err(format!("cédula {}: sin número", texto));
+        }
+        if numero.len() > nacionalidad.largo_maximo() {
+            return Err(format!(
+                "cédula {}: {} dígitos, máximo {} para {}",
+                texto,
+                numero.len(),
+                nacionalidad.largo_maximo(),
+                nacionalidad.as_str()
+            ));
+        }
+        Ok(Cedula {
+            nacionalidad,
+            numero,
+        })
+    }
+
+    /// Número completado con ceros a la izquierda hasta `largo`.
+    pub fn rellena(&self, largo: usize) -> String {
+        format!("{:0>largo$}", self.numero, largo = largo)
+    }
+}
+
+/// Formato canónico: `V-12345678`.
+impl fmt::Display for Cedula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.nacionalidad.as_str(), self.numero)
+    }
+}
+
+/// Todos los dígitos de la cédula, tal como vienen (con ceros a la izquierda).
+pub fn digitos(valor: &str) -> String {
+    valor.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Clave para cruzar archivos: el formato canónico (`V-12345678`), de modo que
+/// `V-12345678` y `E-12345678` no se confunden. `None` si no es una cédula válida.
+pub fn clave(valor: &str) -> Option<String> {
+    Cedula::parse(valor).ok().map(|c| c.to_string())
+}
+
+/// RIF sin separadores y en mayúsculas: letra (`J`, `G`, `V`, `E`, `P`, `C`) y
+/// 9 dígitos (`j-12345678-9` -> `J123456789`). Un número en cero es un error.
+pub fn rif(valor: &str) -> Result<String, String> {
+    let limpio: String = valor
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' ' | '.'))
+        .collect::<String>()
+        .to_uppercase();
+    let mut chars = limpio.chars();
+    let letra = chars.next().ok_or_else(|| "RIF vacío".to_string())?;
+    let numero = chars.as_str();
+    if !matches!(letra, 'J' | 'G' | 'V' | 'E' | 'P' | 'C') {
+        return Err(format!("RIF '{}': letra '{}' inválida", valor, letra));
+    }
+    if numero.len() != 9 || !numero.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("RIF '{}': se esperaban 9 dígitos", valor));
+    }
+    if numero.chars().all(|c| c == '0') {
+        return Err(format!("RIF '{}' sin número", valor));
+    }
+    Ok(limpio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cedula_parse_y_clave() {
+        let c = Cedula::parse(" v-12.345.678 ").unwrap();
+        assert_eq!(c.nacionalidad, Nacionalidad::V);
+        assert_eq!(c.to_string(), "V-12345678");
+        assert_eq!(c.rellena(9), "012345678");
+
+        let e = Cedula::parse("E81234567").unwrap();
+        assert_eq!(e.to_string(), "E-81234567");
+        assert_eq!(Cedula::parse("0001234").unwrap().to_string(), "V-1234");
+        assert_eq!(
+            Cedula::parse("J-123456789").unwrap().nacionalidad,
+            Nacionalidad::J
+        );
+
+        assert!(Cedula::parse("").is_err());
+        assert!(Cedula::parse("X-123").is_err());
+        assert!(Cedula::parse("V-12a45").is_err());
+        assert!(Cedula::parse("V-000").is_err());
+        assert!(Cedula::parse("V-123456789").is_err());
+
+        assert_eq!(digitos("V-0012"), "0012");
+        assert_eq!(clave("0012").as_deref(), Some("V-12"));
+        assert_ne!(clave("E-12345678"), clave("V-12345678"));
+        assert_eq!(clave("X-1"), None);
+
+        assert_eq!(rif("g-20000000-1").unwrap(), "G200000001");
+        assert!(rif("G000000000").is_err());
+        assert!(rif("X123456789").is_err());
+        assert!(rif("J12345").is_err());
+    }
+}
@@ -1,3 +1,5 @@
 pub mod archivo;
+pub mod cedula;
+pub mod script;
 pub mod seguridad;
 pub mod texto;
@@ -0,0 +1,26 @@
+//! Motor Rhai con límites para los scripts que vienen de configuración
+//! (fórmulas de primas, conceptos y diseños de archivo bancario).
+
+use rhai::Engine;
+
+/// Operaciones máximas por evaluación de un script.
+pub const MAX_OPERACIONES: u64 = 100_000;
+
+/// Profundidad máxima de expresiones en el nivel global.
+pub const MAX_PROFUNDIDAD: usize = 64;
+
+/// Profundidad máxima de expresiones dentro de funciones.
+pub const MAX_PROFUNDIDAD_FUNCIONES: usize = 32;
+
+/// Largo máximo de un texto producido por un script.
+pub const MAX_TEXTO: usize = 4_096;
+
+/// `Engine` con los límites de operaciones, profundidad y texto: un script mal
+/// escrito falla con error en lugar de colgar o agotar la memoria del ciclo.
+pub fn motor() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERACIONES);
+    engine.set_max_expr_depths(MAX_PROFUNDIDAD, MAX_PROFUNDIDAD_FUNCIONES);
+    engine.set_max_string_size(MAX_TEXTO);
+    engine
+}
@@ -1,4 +1,5 @@
 use chrono::NaiveDate;
+use sandra_core::util::cedula;
 
 use crate::types::ComparisonStrategy;
 
@@ -34,8 +35,8 @@ pub fn compare_values(csv_val: &str, grpc_val: &str, strategy: &ComparisonStrate
             }
         }
         ComparisonStrategy::Cedula => {
-            let a: String = csv_val.chars().filter(|c| c.is_ascii_digit()).collect();
-            let b: String = grpc_val.chars().filter(|c| c.is_ascii_digit()).collect();
+            let a = cedula::clave(csv_val).unwrap_or_else(|| csv_val.trim().to_string());
+            let b = cedula::clave(grpc_val).unwrap_or_else(|| grpc_val.trim().to_string());
             if a == b {
                 FieldComparison::Match
             } else {
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use sandra_core::util::cedula;
+
 use crate::error::Result;
 use crate::types::CsvRecord;
 
@@ -41,12 +43,10 @@ pub fn build_index(
         }
 
         let cedula_raw = record.get(0).unwrap_or("");
-        let cedula_norm: String = cedula_raw.chars().filter(|c| c.is_ascii_digit()).collect();
-
-        if cedula_norm.is_empty() {
+        let Some(cedula_norm) = cedula::clave(cedula_raw) else {
             warnings.push(format!("Fila {}: cédula vacía o inválida '{}'", idx + 2, cedula_raw));
             continue;
-        }
+        };
 
         let raw_line = record.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(&delim_str);
         let csv_record = CsvRecord { fields, raw_line };
@@ -4,6 +4,7 @@ use std::sync::Arc;
 
 use sandra_core::kernel::sandra::sentinel_dynamic_service_client::SentinelDynamicServiceClient;
 use sandra_core::kernel::sandra::DynamicRequest;
+use sandra_core::util::cedula;
 use serde_json::Value;
 
 use crate::compare::mapping::extract_value_from_json;
@@ -167,12 +168,10 @@ pub async fn run(config: ReconcilerConfig, quiet: bool) -> Result<()> {
     for batch_items in all_batches {
         for record in batch_items {
             let cedula_raw = extract_value_from_json(&record, &["cedula".to_string()]).unwrap_or_default();
-            let cedula_norm: String = cedula_raw.chars().filter(|c| c.is_ascii_digit()).collect();
-
-            if cedula_norm.is_empty() {
+            let Some(cedula_norm) = cedula::clave(&cedula_raw) else {
                 eprintln!("[WARN] Registro gRPC sin cédula válida. Ignorando.");
                 continue;
-            }
+            };
 
             processed_cedulas.insert(cedula_norm.clone());
 
@@ -253,9 +252,11 @@ pub async fn run(config: ReconcilerConfig, quiet: bool) -> Result<()> {
             huerfanos_count += 1;
             metrics.records_not_found_grpc.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 
+            // La clave es canónica (`V-12345678`); las salidas llevan solo el número
+            let numero = cedula.split_once('-').map_or(cedula.as_str(), |(_, n)| n);
             let csv_vals = extract_all_field_values_from_csv(csv_record, &field_mappings);
-            nuevos_writer.write_record(cedula, &csv_vals)?;
-            postgres_insert_builder.add(cedula, csv_vals);
+            nuevos_writer.write_record(numero, &csv_vals)?;
+            postgres_insert_builder.add(numero, csv_vals);
         }
     }
 
@@ -563,14 +564,14 @@ mod tests {
         let index = csv::index::build_index(csv_data, ',', false, 0).unwrap();
 
         assert_eq!(index.inner.len(), 2);
-        assert!(index.inner.contains_key("12345678"));
-        assert!(index.inner.contains_key("87654321"));
+        assert!(index.inner.contains_key("V-12345678"));
+        assert!(index.inner.contains_key("V-87654321"));
 
-        let rec1 = index.inner.get("12345678").unwrap();
+        let rec1 = index.inner.get("V-12345678").unwrap();
         assert_eq!(rec1.fields.get("grado"), Some(&"Coronel".to_string()));
         assert_eq!(rec1.fields.get("n_hijos"), Some(&"2".to_string()));
 
-        let rec2 = index.inner.get("87654321").unwrap();
+        let rec2 = index.inner.get("V-87654321").unwrap();
         assert_eq!(rec2.fields.get("grado"), Some(&"General".to_string()));
         assert_eq!(rec2.fields.get("n_hijos"), Some(&"3".to_string()));
     }